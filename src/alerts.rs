@@ -0,0 +1,148 @@
+use pulldown_cmark::{CowStr, Event, Tag};
+use serde::{Deserialize, Serialize};
+
+/// Plain styling for alerts when no preset is chosen, so a rendered alert
+/// still stands apart from the text around it.
+pub const DEFAULT_CSS: &str = r#"
+.markdown-alert { padding: 0.5em 1em; margin: 1em 0; border-left: 0.25em solid #888; background: rgba(128, 128, 128, 0.08); }
+.markdown-alert > :last-child { margin-bottom: 0; }
+.markdown-alert-title { margin-top: 0; font-weight: bold; }
+.markdown-alert-note { border-left-color: #0969da; }
+.markdown-alert-tip { border-left-color: #1a7f37; }
+.markdown-alert-important { border-left-color: #8250df; }
+.markdown-alert-warning { border-left-color: #9a6700; }
+.markdown-alert-caution { border-left-color: #cf222e; }
+"#;
+
+/// Extra styling for GitHub alert markup (`div.markdown-alert`), added after
+/// the user's stylesheet so it wins over generic blockquote rules.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum AlertPreset {
+    #[default]
+    None,
+    /// GitHub's own colors and octicons, light and dark.
+    GitHub,
+}
+
+impl AlertPreset {
+    pub fn label(self) -> &'static str {
+        match self {
+            AlertPreset::None => "Basic",
+            AlertPreset::GitHub => "GitHub",
+        }
+    }
+
+    /// The preset's styles. With no preset, [`DEFAULT_CSS`] when blockquotes
+    /// are `rendered` as alerts, and nothing otherwise.
+    pub fn css(self, rendered: bool) -> &'static str {
+        match self {
+            AlertPreset::None if rendered => DEFAULT_CSS,
+            AlertPreset::None => "",
+            AlertPreset::GitHub => include_str!("css/github-alerts.css"),
+        }
+    }
+}
+
+/// Alert types GitHub recognizes after `[!`, with the title shown for each.
+const KINDS: [(&str, &str); 5] =
+    [("NOTE", "Note"), ("TIP", "Tip"), ("IMPORTANT", "Important"), ("WARNING", "Warning"), ("CAUTION", "Caution")];
+
+/// The alert type and title when `text` is nothing but a marker such as
+/// `[!NOTE]`, in any case.
+fn marker(text: &str) -> Option<(&'static str, &'static str)> {
+    let kind = text.trim().strip_prefix("[!")?.strip_suffix(']')?;
+    KINDS.into_iter().find(|(name, _)| name.eq_ignore_ascii_case(kind))
+}
+
+/// Turns blockquotes whose first line is `[!NOTE]`, `[!TIP]`,
+/// `[!IMPORTANT]`, `[!WARNING]` or `[!CAUTION]` into GitHub's alert markup,
+/// which [`AlertPreset::GitHub`] styles. The marker line is dropped; other
+/// blockquotes are left exactly as they were.
+pub fn render_alerts(events: Vec<Event>) -> Vec<Event> {
+    let mut out = Vec::with_capacity(events.len());
+    // Whether each open blockquote became an alert.
+    let mut open: Vec<bool> = Vec::new();
+    let mut i = 0;
+    while i < events.len() {
+        match &events[i] {
+            Event::Start(Tag::BlockQuote) => {
+                // The marker may arrive split over several text events.
+                let mut end = i + 2;
+                let mut text = String::new();
+                if let Some(Event::Start(Tag::Paragraph)) = events.get(i + 1) {
+                    while let Some(Event::Text(t)) = events.get(end) {
+                        text += t;
+                        end += 1;
+                    }
+                }
+                // Only a marker alone on its line counts.
+                let line_ends = matches!(
+                    events.get(end),
+                    Some(Event::SoftBreak | Event::HardBreak | Event::End(Tag::Paragraph))
+                );
+                let Some((kind, title)) = marker(&text).filter(|_| line_ends) else {
+                    open.push(false);
+                    out.push(events[i].clone());
+                    i += 1;
+                    continue;
+                };
+                open.push(true);
+                out.push(Event::Html(CowStr::from(format!(
+                    "<div class=\"markdown-alert markdown-alert-{}\">\n<p class=\"markdown-alert-title\">{}</p>\n",
+                    kind.to_ascii_lowercase(),
+                    title
+                ))));
+                // Unless the marker was the whole paragraph, the rest of it
+                // follows the marker line.
+                if !matches!(events.get(end), Some(Event::End(Tag::Paragraph))) {
+                    out.push(Event::Start(Tag::Paragraph));
+                }
+                i = end + 1;
+                continue;
+            }
+            Event::End(Tag::BlockQuote) if open.pop() == Some(true) => {
+                out.push(Event::Html(CowStr::Borrowed("</div>\n")));
+            }
+            event => out.push(event.clone()),
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{html, Parser};
+
+    fn render(md: &str) -> String {
+        let mut out = String::new();
+        html::push_html(&mut out, render_alerts(Parser::new(md).collect()).into_iter());
+        out
+    }
+
+    #[test]
+    fn marker_becomes_alert_markup() {
+        let out = render("> [!WARNING]\n> Mind the gap.\n");
+        assert!(out.contains("<div class=\"markdown-alert markdown-alert-warning\">"));
+        assert!(out.contains("<p class=\"markdown-alert-title\">Warning</p>"));
+        assert!(out.contains("<p>Mind the gap.</p>\n</div>"));
+        assert!(!out.contains("[!WARNING]") && !out.contains("<blockquote>"));
+    }
+
+    #[test]
+    fn other_blockquotes_are_untouched() {
+        let md = "> Just a quote\n> [!NOTE]\n";
+        let mut plain = String::new();
+        html::push_html(&mut plain, Parser::new(md));
+        assert_eq!(render(md), plain);
+        assert_eq!(render("> [!NOTE] inline\n"), "<blockquote>\n<p>[!NOTE] inline</p>\n</blockquote>\n");
+    }
+
+    #[test]
+    fn alerts_are_styled_without_a_preset() {
+        assert_eq!(AlertPreset::None.css(true), DEFAULT_CSS);
+        assert_eq!(AlertPreset::None.css(false), "");
+        assert!(AlertPreset::GitHub.css(false).contains(".markdown-alert-note"));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::messages::Message;
+use crate::paths;
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What happens to local image and link targets when the output lives
+/// somewhere else than the Markdown.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum AssetPaths {
+    /// References are written as they are in the Markdown.
+    #[default]
+    Unchanged,
+    /// Rewritten relative to the output file, so they still reach the
+    /// files where they are.
+    RelativeToOutput,
+    /// Rewritten as absolute `file:///` URLs.
+    FileUrl,
+    /// The path relative to the Markdown, under a base URL the files are
+    /// served from.
+    BaseUrl,
+}
+
+impl AssetPaths {
+    pub const ALL: [AssetPaths; 4] =
+        [AssetPaths::Unchanged, AssetPaths::RelativeToOutput, AssetPaths::FileUrl, AssetPaths::BaseUrl];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AssetPaths::Unchanged => "Leave as written",
+            AssetPaths::RelativeToOutput => "Rewrite relative to output",
+            AssetPaths::FileUrl => "Rewrite as file:// URLs",
+            AssetPaths::BaseUrl => "Rewrite under a base URL",
+        }
+    }
+}
+
+/// Rewrites local references of one document; see [`AssetPaths`].
+pub struct PathRewriter {
+    mode: AssetPaths,
+    source_dir: PathBuf,
+    output_dir: PathBuf,
+    base_url: String,
+}
+
+impl PathRewriter {
+    /// `None` when references are left alone.
+    pub fn new(mode: AssetPaths, input: &Path, output_dir: &Path, base_url: &str) -> Option<Self> {
+        (mode != AssetPaths::Unchanged).then(|| Self {
+            mode,
+            source_dir: paths::absolute(input.parent().unwrap_or(Path::new(""))),
+            output_dir: paths::absolute(output_dir),
+            base_url: format!("{}/", base_url.trim().trim_end_matches('/')),
+        })
+    }
+
+    /// The new reference for `url`, or `None` to keep it. Remote URLs, data
+    /// URIs and other schemes, fragments and root-relative paths are kept.
+    pub fn rewrite(&self, url: &str) -> Option<String> {
+        let (path, suffix) = local_path(url)?;
+        let relative = paths::normalize(Path::new(&path.replace("%20", " ")));
+        let target = paths::normalize(&self.source_dir.join(&relative));
+        let rewritten = match self.mode {
+            AssetPaths::Unchanged => return None,
+            // Different drives can't be related, so those become absolute.
+            AssetPaths::RelativeToOutput => match paths::relative_path(&self.output_dir, &target) {
+                Some(relative) => paths::to_url_path(&relative),
+                None => paths::file_url(&target),
+            },
+            AssetPaths::FileUrl => paths::file_url(&target),
+            AssetPaths::BaseUrl => format!("{}{}", self.base_url, paths::to_url_path(&relative)),
+        };
+        Some(rewritten + suffix)
+    }
+}
+
+/// The file part of `url` and the query or fragment after it, if `url` is a
+/// relative path. Remote URLs, data URIs and other schemes, bare fragments
+/// and root-relative paths give `None`.
+fn local_path(url: &str) -> Option<(&str, &str)> {
+    let split = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(split);
+    let has_scheme = path.split('/').next().is_some_and(|first| first.contains(':'));
+    if path.is_empty() || path.starts_with('/') || path.starts_with('\\') || has_scheme {
+        return None;
+    }
+    Some((path, suffix))
+}
+
+/// Relative paths of the images in `md`, and of what raw `<img>`,
+/// `<source>` and `<link>` tags point to, each once.
+fn local_assets(md: &str, options: Options) -> Vec<String> {
+    let tag = Regex::new(r#"(?i)<(?:img|source|link)\b[^>]*?\b(?:src|href)\s*=\s*"([^"]+)""#).ok();
+    let mut urls: Vec<String> = Vec::new();
+    for event in Parser::new_ext(md, options) {
+        match event {
+            Event::Start(Tag::Image(_, dest, _)) => urls.push(dest.to_string()),
+            Event::Html(html) => {
+                if let Some(tag) = &tag {
+                    urls.extend(tag.captures_iter(&html).map(|caps| caps[1].to_string()));
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut paths: Vec<String> = Vec::new();
+    for url in urls {
+        if let Some((path, _)) = local_path(&url) {
+            let path = path.replace("%20", " ");
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+/// Copies the local images and stylesheets `md` refers to from next to
+/// `input` to the same relative place next to `output`, so the page works
+/// from there. Files already up to date are skipped. Returns a log line
+/// per file copied or failed.
+pub fn copy_assets(md: &str, options: Options, input: &Path, output: &Path) -> Vec<Message> {
+    let source_dir = paths::absolute(input.parent().unwrap_or(Path::new("")));
+    let output_dir = paths::absolute(output.parent().unwrap_or(Path::new("")));
+    if paths::normalize(&source_dir) == paths::normalize(&output_dir) {
+        return Vec::new();
+    }
+    let mut log = Vec::new();
+    for relative in local_assets(md, options) {
+        let from = source_dir.join(&relative);
+        let to = paths::normalize(&output_dir.join(&relative));
+        if !from.is_file() {
+            continue;
+        }
+        let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+        if modified(&to).is_some_and(|copied| modified(&from).is_some_and(|original| copied >= original)) {
+            continue;
+        }
+        let copied = to.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::copy(&from, &to));
+        log.push(match copied {
+            Ok(_) => Message::info("📦", format!("{}: copied {} → {}", input.display(), relative, to.display())),
+            Err(e) => Message::warning(format!("{}: failed to copy {}: {}", input.display(), relative, e)),
+        });
+    }
+    log
+}
+
+/// Rewrites the destination of a link or image with `rewriter`, if any.
+pub fn rewrite_event<'a>(event: Event<'a>, rewriter: Option<&PathRewriter>) -> Event<'a> {
+    let Some(rewriter) = rewriter else {
+        return event;
+    };
+    match event {
+        Event::Start(Tag::Link(kind, dest, title)) => match rewriter.rewrite(&dest) {
+            Some(url) => Event::Start(Tag::Link(kind, CowStr::from(url), title)),
+            None => Event::Start(Tag::Link(kind, dest, title)),
+        },
+        Event::Start(Tag::Image(kind, dest, title)) => match rewriter.rewrite(&dest) {
+            Some(url) => Event::Start(Tag::Image(kind, CowStr::from(url), title)),
+            None => Event::Start(Tag::Image(kind, dest, title)),
+        },
+        event => event,
+    }
+}
+
+/// `url` with its leading folder `from` swapped for `to` when it's a local
+/// relative path inside `from`, e.g. `./assets/a.png` with `assets` →
+/// `/static/assets` gives `/static/assets/a.png`. An empty `to` drops the
+/// folder.
+pub fn replace_prefix(url: &str, from: &str, to: &str) -> Option<String> {
+    let (path, suffix) = local_path(url)?;
+    let from = from.trim().trim_start_matches("./").trim_end_matches('/');
+    if from.is_empty() {
+        return None;
+    }
+    let rest = path.trim_start_matches("./").strip_prefix(from)?;
+    if !(rest.is_empty() || rest.starts_with('/')) {
+        return None;
+    }
+    let to = to.trim().trim_end_matches('/');
+    if to.is_empty() {
+        return Some(format!("{}{}", rest.trim_start_matches('/'), suffix));
+    }
+    Some(format!("{}{}{}", to, rest, suffix))
+}
+
+/// Applies `rule`, a `(from, to)` pair for [`replace_prefix`], to image sources.
+pub fn rewrite_image_prefix<'a>(event: Event<'a>, rule: Option<&(String, String)>) -> Event<'a> {
+    let Some((from, to)) = rule else {
+        return event;
+    };
+    match event {
+        Event::Start(Tag::Image(kind, dest, title)) => match replace_prefix(&dest, from, to) {
+            Some(url) => Event::Start(Tag::Image(kind, CowStr::from(url), title)),
+            None => Event::Start(Tag::Image(kind, dest, title)),
+        },
+        event => event,
+    }
+}
@@ -0,0 +1,49 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One converted or failed file, as a tab-separated line.
+pub struct Record {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    /// `ok`, `failed` or `timeout`.
+    pub status: &'static str,
+    pub bytes: u64,
+}
+
+/// Where the log goes once it's rotated out: `builds.log.1` for `builds.log`.
+fn rotated(log_file: &Path) -> PathBuf {
+    let mut name = log_file.file_name().unwrap_or_default().to_os_string();
+    name.push(".1");
+    log_file.with_file_name(name)
+}
+
+/// Appends `records` to `log_file`, one line each with the local time.
+/// A file already past `max_bytes` is first renamed to `<name>.1`,
+/// replacing the previous one, so at most two files' worth is kept.
+pub fn append(log_file: &Path, records: &[Record], max_bytes: u64) -> Result<(), String> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    if fs::metadata(log_file).is_ok_and(|m| m.len() > max_bytes) {
+        fs::rename(log_file, rotated(log_file)).map_err(|e| format!("failed to rotate {}: {}", log_file.display(), e))?;
+    }
+    let now = chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+    let mut text = String::new();
+    for record in records {
+        text += &format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            now,
+            record.status,
+            record.input.display(),
+            record.output.display(),
+            record.bytes
+        );
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|e| format!("failed to write {}: {}", log_file.display(), e))
+}
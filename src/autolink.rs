@@ -0,0 +1,232 @@
+use pulldown_cmark::{CowStr, Event, LinkType, Tag};
+use regex::Regex;
+use std::collections::VecDeque;
+
+const PATTERN: &str = r"(?i)\b(?:https?://|www\.)[^\s<>]+|\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b";
+
+/// `#123` and `@name`, GitHub style.
+const REFERENCE_PATTERN: &str = r"#[0-9]+\b|@[A-Za-z0-9](?:[A-Za-z0-9-]*[A-Za-z0-9])?\b";
+
+/// Where `#123` and `@name` point, worked out from a repository URL such
+/// as `https://github.com/owner/repo`.
+struct References {
+    issues: String,
+    profiles: String,
+}
+
+impl References {
+    fn new(repo: &str) -> Option<Self> {
+        let repo = repo.trim().trim_end_matches('/');
+        let (scheme, rest) = repo.split_once("://")?;
+        let host = rest.split('/').next().filter(|host| !host.is_empty())?;
+        Some(Self { issues: format!("{}/issues/", repo), profiles: format!("{}://{}/", scheme, host) })
+    }
+}
+
+/// Turns bare URLs and email addresses in prose into links, leaving text
+/// inside links, images and code blocks alone. See [`autolink`].
+pub struct Autolink<'a, I: Iterator<Item = Event<'a>>> {
+    events: I,
+    pattern: Option<Regex>,
+    references: Option<References>,
+    /// Links, images, code blocks and raw `<a>` tags we're inside of.
+    skip_depth: usize,
+    ready: VecDeque<Event<'a>>,
+}
+
+/// Wraps `events` so bare URLs and addresses become links when `urls` is
+/// set, and `#123` and `@name` link into `repo` when it isn't empty.
+pub fn autolink<'a, I: Iterator<Item = Event<'a>>>(events: I, urls: bool, repo: &str) -> Autolink<'a, I> {
+    let references = References::new(repo);
+    let mut patterns = Vec::new();
+    if urls {
+        patterns.push(PATTERN);
+    }
+    if references.is_some() {
+        patterns.push(REFERENCE_PATTERN);
+    }
+    let pattern = if patterns.is_empty() { None } else { Regex::new(&patterns.join("|")).ok() };
+    Autolink { events, pattern, references, skip_depth: 0, ready: VecDeque::new() }
+}
+
+impl<'a, I: Iterator<Item = Event<'a>>> Autolink<'a, I> {
+    fn track(&mut self, event: &Event) {
+        match event {
+            Event::Start(Tag::Link(..) | Tag::Image(..) | Tag::CodeBlock(_)) => self.skip_depth += 1,
+            Event::End(Tag::Link(..) | Tag::Image(..) | Tag::CodeBlock(_)) => {
+                self.skip_depth = self.skip_depth.saturating_sub(1)
+            }
+            Event::Html(html) => {
+                let tag = html.trim_start().to_ascii_lowercase();
+                if tag.starts_with("<a ") || tag.starts_with("<a>") {
+                    self.skip_depth += 1;
+                } else if tag.starts_with("</a") {
+                    self.skip_depth = self.skip_depth.saturating_sub(1);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = Event<'a>>> Iterator for Autolink<'a, I> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Event<'a>> {
+        loop {
+            if let Some(event) = self.ready.pop_front() {
+                return Some(event);
+            }
+            let Some(pattern) = &self.pattern else {
+                return self.events.next();
+            };
+            // The parser can split one run of text into several events, e.g.
+            // at `&` or `_`, so a URL is only seen whole once they're joined.
+            let mut texts: Vec<CowStr<'a>> = Vec::new();
+            let mut after = None;
+            for event in self.events.by_ref() {
+                match event {
+                    Event::Text(text) if self.skip_depth == 0 => texts.push(text),
+                    event => {
+                        after = Some(event);
+                        break;
+                    }
+                }
+            }
+            let text = match texts.len() {
+                0 => None,
+                1 => texts.pop(),
+                _ => Some(CowStr::from(texts.concat())),
+            };
+            if let Some(text) = text {
+                self.ready.extend(link_text(pattern, self.references.as_ref(), text));
+            }
+            match after {
+                Some(event) => {
+                    self.track(&event);
+                    self.ready.push_back(event);
+                }
+                None if self.ready.is_empty() => return None,
+                None => {}
+            }
+        }
+    }
+}
+
+/// `url` without punctuation that more likely ends the sentence: a final
+/// period or comma, or a closing paren or bracket with no opening one in
+/// the URL itself.
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    while let Some(last) = url.chars().last() {
+        let trailing = match last {
+            '.' | ',' | ':' | ';' | '!' | '?' | '\'' | '"' | '*' | '_' | '~' => true,
+            ')' => url.matches(')').count() > url.matches('(').count(),
+            ']' => url.matches(']').count() > url.matches('[').count(),
+            _ => false,
+        };
+        if !trailing {
+            break;
+        }
+        url = &url[..url.len() - last.len_utf8()];
+    }
+    url
+}
+
+/// Whether a `#` or `@` reference starting at `start` is really part of a
+/// word, path or address, as in `C#1`, `a/#2` or `me@example`.
+fn inside_word(text: &str, start: usize) -> bool {
+    text[..start].chars().last().is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '/' | '@' | '#' | '&'))
+}
+
+fn link_text<'a>(pattern: &Regex, references: Option<&References>, text: CowStr<'a>) -> Vec<Event<'a>> {
+    let mut events = Vec::new();
+    let mut last = 0;
+    for found in pattern.find_iter(&text) {
+        if found.start() < last {
+            continue;
+        }
+        let matched = found.as_str();
+        let lower = matched.to_ascii_lowercase();
+        let (kind, label, dest) = if let (Some(refs), Some(number)) = (references, matched.strip_prefix('#')) {
+            if inside_word(&text, found.start()) {
+                continue;
+            }
+            (LinkType::Inline, matched, format!("{}{}", refs.issues, number))
+        } else if let (Some(refs), Some(name)) = (references, matched.strip_prefix('@')) {
+            if inside_word(&text, found.start()) {
+                continue;
+            }
+            (LinkType::Inline, matched, format!("{}{}", refs.profiles, name))
+        } else if lower.starts_with("http://") || lower.starts_with("https://") {
+            let url = trim_url(matched);
+            (LinkType::Autolink, url, url.to_string())
+        } else if lower.starts_with("www.") {
+            let url = trim_url(matched);
+            (LinkType::Autolink, url, format!("http://{}", url))
+        } else {
+            (LinkType::Email, matched, matched.to_string())
+        };
+        let host = label.split_once("://").map_or(label, |(_, rest)| rest);
+        if host.is_empty() || host.eq_ignore_ascii_case("www") {
+            continue;
+        }
+        if found.start() > last {
+            events.push(Event::Text(CowStr::from(text[last..found.start()].to_string())));
+        }
+        let dest = CowStr::from(dest);
+        events.push(Event::Start(Tag::Link(kind, dest.clone(), CowStr::from(""))));
+        events.push(Event::Text(CowStr::from(label.to_string())));
+        events.push(Event::End(Tag::Link(kind, dest, CowStr::from(""))));
+        last = found.start() + label.len();
+    }
+    if last == 0 {
+        events.push(Event::Text(text));
+    } else if last < text.len() {
+        events.push(Event::Text(CowStr::from(text[last..].to_string())));
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Destinations of the links found in `text`, in order.
+    fn links(text: &str) -> Vec<String> {
+        let pattern = Regex::new(PATTERN).unwrap();
+        link_text(&pattern, None, CowStr::from(text.to_string()))
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link(_, dest, _)) => Some(dest.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn url_inside_parentheses() {
+        assert_eq!(links("Docs (https://example.com/docs) here"), ["https://example.com/docs"]);
+        assert_eq!(
+            links("See https://en.wikipedia.org/wiki/Rust_(programming_language)."),
+            ["https://en.wikipedia.org/wiki/Rust_(programming_language)"]
+        );
+        assert_eq!(trim_url("https://example.com/a_(b))"), "https://example.com/a_(b)");
+    }
+
+    #[test]
+    fn url_with_query_string() {
+        assert_eq!(links("Search https://example.com/find?q=a&page=2 now"), ["https://example.com/find?q=a&page=2"]);
+        assert_eq!(links("www.example.com/?x=1#top"), ["http://www.example.com/?x=1#top"]);
+    }
+
+    #[test]
+    fn trailing_comma_or_period_is_not_part_of_the_url() {
+        assert_eq!(
+            links("Read https://example.com/a, then https://example.com/b."),
+            ["https://example.com/a", "https://example.com/b"]
+        );
+        assert_eq!(trim_url("https://example.com/a.html."), "https://example.com/a.html");
+        assert_eq!(trim_url("https://example.com/x?y=1,"), "https://example.com/x?y=1");
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{rename, AppState};
+use eframe::egui;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long a check stays current when nothing in the list changed; files
+/// can still appear or vanish on disk meanwhile.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What would stop the next batch, or surprise whoever starts it.
+#[derive(Default)]
+pub struct BatchCheck {
+    signature: u64,
+    checked_at: Option<Instant>,
+    /// Entries the next batch would convert.
+    pub enabled: usize,
+    /// The input and output lists have different lengths.
+    pub mismatch: bool,
+    pub missing: Vec<usize>,
+    /// Entries that write to the same output as another one.
+    pub collisions: Vec<usize>,
+    /// Entries whose output already exists; only a warning.
+    pub overwrites: Vec<usize>,
+}
+
+impl BatchCheck {
+    /// Whether Convert can start: something to do and no hard errors.
+    pub fn runnable(&self) -> bool {
+        self.enabled > 0 && !self.mismatch && self.missing.is_empty() && self.collisions.is_empty()
+    }
+
+    /// Why row `i` is marked, and whether it's an error rather than a warning.
+    pub fn row_problem(&self, i: usize) -> Option<(&'static str, bool)> {
+        if self.missing.contains(&i) {
+            Some(("Input file not found", true))
+        } else if self.collisions.contains(&i) {
+            Some(("Another file writes to the same output", true))
+        } else if self.overwrites.contains(&i) {
+            Some(("The output already exists and will be overwritten", false))
+        } else {
+            None
+        }
+    }
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+impl AppState {
+    fn batch_signature(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.input_files.hash(&mut hasher);
+        self.output_files.hash(&mut hasher);
+        for entry in &self.entries {
+            (entry.enabled, entry.draft).hash(&mut hasher);
+        }
+        (self.include_drafts, self.dated_subfolder).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Recomputes [`BatchCheck`] when the list changed, or when the last
+    /// check is older than [`RECHECK_INTERVAL`] or `force` is set.
+    pub(crate) fn refresh_batch_check(&mut self, force: bool) {
+        let signature = self.batch_signature();
+        let current = self.batch_check.signature == signature
+            && self.batch_check.checked_at.is_some_and(|at| at.elapsed() < RECHECK_INTERVAL);
+        if current && !force {
+            return;
+        }
+        let included: Vec<usize> = (0..self.input_files.len().min(self.output_files.len()))
+            .filter(|&i| self.entries.get(i).is_none_or(|e| e.included(self.include_drafts)))
+            .collect();
+        let outputs: Vec<PathBuf> = included.iter().map(|&i| self.output_files[i].clone()).collect();
+        self.batch_check = BatchCheck {
+            signature,
+            checked_at: Some(Instant::now()),
+            enabled: included.len(),
+            mismatch: self.input_files.len() != self.output_files.len(),
+            missing: included.iter().copied().filter(|&i| !self.input_files[i].is_file()).collect(),
+            collisions: rename::duplicate_indices(&outputs).into_iter().map(|d| included[d]).collect(),
+            // A dated subfolder writes somewhere new every batch.
+            overwrites: if self.dated_subfolder {
+                Vec::new()
+            } else {
+                included.iter().copied().filter(|&i| self.output_files[i].exists()).collect()
+            },
+        };
+    }
+
+    /// The problems above the Convert button; clicking one scrolls to the
+    /// first file it's about.
+    pub(crate) fn batch_check_ui(&mut self, ui: &mut egui::Ui) {
+        let check = &self.batch_check;
+        let mut lines: Vec<(String, bool, Option<usize>)> = Vec::new();
+        if check.mismatch {
+            lines.push(("Input and output lists differ in length".to_string(), true, None));
+        } else if check.enabled == 0 && !self.input_files.is_empty() {
+            lines.push(("No files enabled".to_string(), true, None));
+        }
+        if !check.missing.is_empty() {
+            let text = plural(check.missing.len(), "missing input", "missing inputs");
+            lines.push((text, true, check.missing.first().copied()));
+        }
+        if !check.collisions.is_empty() {
+            let text = plural(check.collisions.len(), "output collision", "output collisions");
+            lines.push((text, true, check.collisions.first().copied()));
+        }
+        if !check.overwrites.is_empty() {
+            let text = format!("{} will be overwritten", plural(check.overwrites.len(), "output", "outputs"));
+            lines.push((text, false, check.overwrites.first().copied()));
+        }
+        let collisions = !check.collisions.is_empty();
+        for (text, error, row) in lines {
+            let (icon, color) = if error {
+                ("❌", egui::Color32::LIGHT_RED)
+            } else {
+                ("⚠", egui::Color32::LIGHT_YELLOW)
+            };
+            let label = egui::RichText::new(format!("{} {}", icon, text)).color(color);
+            match row {
+                Some(row) => {
+                    if ui.link(label).on_hover_text("Show the first one").clicked() {
+                        self.jump_to_row = Some(row);
+                    }
+                }
+                None => {
+                    ui.label(label);
+                }
+            }
+        }
+        if collisions
+            && ui
+                .button("🔢 Add -2, -3… suffixes")
+                .on_hover_text("Rename every output that repeats an earlier one, e.g. notes.html to notes-2.html")
+                .clicked()
+        {
+            self.sync_entries();
+            self.rename_undo = None;
+            for (i, renamed) in rename::suffixed_duplicates(&self.output_files) {
+                self.output_files[i] = renamed;
+            }
+            self.refresh_batch_check(true);
+        }
+    }
+}
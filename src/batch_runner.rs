@@ -0,0 +1,151 @@
+use crate::excerpt::LineRange;
+use crate::limits::Slots;
+use crate::messages::Message;
+use crate::timeout;
+use crate::{AppState, Converted};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// One file of a batch, with what its conversion needs from its entry.
+#[derive(Clone)]
+pub struct Job {
+    pub index: usize,
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub range: Option<LineRange>,
+    pub template: Option<PathBuf>,
+}
+
+/// A job's log lines and its result.
+pub type Outcome = (Vec<Message>, Result<Converted, Message>);
+
+/// Jobs converted side by side, with what each produced.
+pub type Group = Vec<(Job, Outcome)>;
+
+/// A batch converting on a thread of its own, so the window keeps drawing.
+///
+/// Files are converted in groups of `max_parallel`; each finished group waits
+/// in `done` until the interface collects it. A failure other than a timeout
+/// stops the batch after its group.
+pub struct Runner {
+    done: Arc<Mutex<Vec<Group>>>,
+    finished: Arc<AtomicBool>,
+    pub total: usize,
+    pub completed: usize,
+    /// Entries converted so far.
+    pub converted: Vec<usize>,
+    /// Empty inputs left out of the batch.
+    pub skipped_empty: usize,
+}
+
+impl Runner {
+    /// Starts converting `jobs` with `settings`, a snapshot taken for this
+    /// batch. All of them share one set of `io_workers` I/O slots.
+    pub fn start(settings: AppState, jobs: Vec<Job>, in_folder: bool, parallel: usize, io_workers: usize) -> Runner {
+        let done = Arc::new(Mutex::new(Vec::new()));
+        let finished = Arc::new(AtomicBool::new(false));
+        let runner = Runner {
+            done: Arc::clone(&done),
+            finished: Arc::clone(&finished),
+            total: jobs.len(),
+            completed: 0,
+            converted: Vec::new(),
+            skipped_empty: 0,
+        };
+        let settings = Arc::new(settings);
+        let io_slots = Arc::new(Slots::new(io_workers));
+        thread::spawn(move || {
+            for chunk in jobs.chunks(parallel.max(1)) {
+                let outcomes: Vec<Outcome> = if chunk.len() == 1 {
+                    vec![settings.convert_job_timed(&chunk[0], in_folder, &io_slots)]
+                } else {
+                    thread::scope(|scope| {
+                        let handles: Vec<_> = chunk
+                            .iter()
+                            .map(|job| {
+                                let (settings, io_slots) = (&settings, &io_slots);
+                                scope.spawn(move || settings.convert_job_timed(job, in_folder, io_slots))
+                            })
+                            .collect();
+                        handles
+                            .into_iter()
+                            .map(|handle| {
+                                handle
+                                    .join()
+                                    .unwrap_or_else(|_| (Vec::new(), Err(Message::error("Conversion thread panicked."))))
+                            })
+                            .collect()
+                    })
+                };
+                let stop = outcomes
+                    .iter()
+                    .any(|(_, result)| result.as_ref().is_err_and(|message| !timeout::is_timeout(message)));
+                done.lock().unwrap_or_else(|e| e.into_inner()).push(chunk.iter().cloned().zip(outcomes).collect());
+                if stop {
+                    break;
+                }
+            }
+            finished.store(true, Ordering::Release);
+        });
+        runner
+    }
+
+    /// Groups finished since the last call, and whether the batch is over
+    /// with these.
+    pub fn collect(&self) -> (Vec<Group>, bool) {
+        let over = self.finished.load(Ordering::Acquire);
+        let groups = std::mem::take(&mut *self.done.lock().unwrap_or_else(|e| e.into_inner()));
+        (groups, over)
+    }
+}
+
+impl AppState {
+    /// A copy of the settings for a batch's thread. The file list and the
+    /// other workspaces are left out, so only the options are serialized.
+    pub(crate) fn settings_snapshot(&mut self) -> Result<AppState, String> {
+        let input_files = std::mem::take(&mut self.input_files);
+        let output_files = std::mem::take(&mut self.output_files);
+        let entries = std::mem::take(&mut self.entries);
+        let workspaces = std::mem::take(&mut self.workspaces);
+        let value = serde_json::to_value(&*self).map_err(|e| e.to_string());
+        self.input_files = input_files;
+        self.output_files = output_files;
+        self.entries = entries;
+        self.workspaces = workspaces;
+        let mut copy = AppState::from_settings_value(value?)?;
+        copy.lint_strict = self.lint_strict;
+        copy.range_override = self.range_override;
+        copy.headless = self.headless;
+        Ok(copy)
+    }
+
+    pub(crate) fn batch_running(&self) -> bool {
+        self.batch.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileEntry;
+
+    #[test]
+    fn snapshot_keeps_settings_but_not_files() {
+        let mut state = AppState {
+            input_files: vec![PathBuf::from("a.md")],
+            output_files: vec![PathBuf::from("a.html")],
+            entries: vec![FileEntry::default()],
+            title: "Docs".to_string(),
+            lint_strict: true,
+            ..AppState::default()
+        };
+        let copy = state.settings_snapshot().unwrap();
+        assert_eq!(copy.title, "Docs");
+        assert!(copy.lint_strict);
+        assert!(copy.input_files.is_empty() && copy.entries.is_empty());
+        assert_eq!(state.input_files.len(), 1);
+        assert_eq!(state.entries.len(), 1);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::AppState;
+use crate::messages::Message;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the open command gets to fail before it counts as launched.
+/// Some launchers stay around until the browser closes, so they can't be
+/// waited for.
+const SETTLE: Duration = Duration::from_millis(500);
+
+/// Pause before trying again, long enough for a just-written file to show up.
+const RETRY_DELAY: Duration = Duration::from_millis(300);
+
+/// Attempts when the setting is 0.
+const DEFAULT_ATTEMPTS: u32 = 3;
+
+fn open_command(path: &Path) -> Command {
+    #[cfg(target_os = "linux")]
+    let mut command = Command::new("xdg-open");
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    command.arg(path);
+    command
+}
+
+/// Runs the open command once, failing if it exits unsuccessfully within
+/// [`SETTLE`].
+fn launch(path: &Path) -> Result<(), String> {
+    let mut child = open_command(path).spawn().map_err(|e| e.to_string())?;
+    let clock = Instant::now();
+    while clock.elapsed() < SETTLE {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(status.to_string()),
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(())
+}
+
+/// Opens `path` in the default browser, trying up to `attempts` times (0
+/// means 3) with a short pause in between. Returns the last failure.
+pub fn open_in_browser(path: &Path, attempts: u32) -> Result<(), String> {
+    let attempts = if attempts == 0 { DEFAULT_ATTEMPTS } else { attempts };
+    let mut failure = String::new();
+    for attempt in 0..attempts {
+        if attempt > 0 {
+            thread::sleep(RETRY_DELAY);
+        }
+        match launch(path) {
+            Ok(()) => return Ok(()),
+            Err(e) => failure = e,
+        }
+    }
+    Err(failure)
+}
+
+/// Pages being opened on threads of their own, so the open command's
+/// waiting and retries don't hold up the window, and what went wrong.
+#[derive(Default)]
+pub struct BackgroundOpen {
+    running: Arc<AtomicUsize>,
+    failures: Arc<Mutex<Vec<Message>>>,
+}
+
+impl BackgroundOpen {
+    /// Opens each of `pages` in turn on a new thread.
+    pub fn start(&self, pages: Vec<PathBuf>, attempts: u32) {
+        let running = Arc::clone(&self.running);
+        let failures = Arc::clone(&self.failures);
+        running.fetch_add(1, Ordering::Relaxed);
+        thread::spawn(move || {
+            for page in pages {
+                if let Err(e) = open_in_browser(&page, attempts) {
+                    let failure = Message::warning(format!("Could not open {} in the browser: {}", page.display(), e));
+                    failures.lock().unwrap_or_else(|e| e.into_inner()).push(failure);
+                }
+            }
+            running.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed) > 0
+    }
+
+    /// Failures reported since the last call.
+    pub fn take_failures(&self) -> Vec<Message> {
+        std::mem::take(&mut *self.failures.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl AppState {
+    /// Logs any failed browser opens and shows the latest in the status
+    /// line; returns whether pages are still being opened.
+    pub(crate) fn poll_browser_open(&mut self) -> bool {
+        for failure in self.browser_open.take_failures() {
+            self.status_message = failure.clone();
+            self.log.push(failure);
+        }
+        self.browser_open.is_running()
+    }
+}
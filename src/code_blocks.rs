@@ -0,0 +1,282 @@
+use crate::toc::{escape_attr, escape_text};
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag};
+use std::collections::HashMap;
+
+pub const BADGE_CSS: &str = r#"
+.code-block { position: relative; }
+.code-block > .code-lang { position: absolute; top: 0; right: 0; padding: 0.1em 0.6em; font-size: 0.75em; font-family: sans-serif; line-height: 1.6; opacity: 0.75; background: rgba(127, 127, 127, 0.15); border-bottom-left-radius: 4px; pointer-events: none; user-select: none; }
+"#;
+
+const DISPLAY_NAMES: &[(&str, &str)] = &[
+    ("bash", "Bash"),
+    ("c", "C"),
+    ("c++", "C++"),
+    ("cpp", "C++"),
+    ("cs", "C#"),
+    ("csharp", "C#"),
+    ("css", "CSS"),
+    ("diff", "Diff"),
+    ("dockerfile", "Dockerfile"),
+    ("go", "Go"),
+    ("html", "HTML"),
+    ("java", "Java"),
+    ("javascript", "JavaScript"),
+    ("js", "JavaScript"),
+    ("json", "JSON"),
+    ("jsx", "JSX"),
+    ("kotlin", "Kotlin"),
+    ("markdown", "Markdown"),
+    ("md", "Markdown"),
+    ("php", "PHP"),
+    ("powershell", "PowerShell"),
+    ("ps1", "PowerShell"),
+    ("py", "Python"),
+    ("python", "Python"),
+    ("rb", "Ruby"),
+    ("ruby", "Ruby"),
+    ("rs", "Rust"),
+    ("rust", "Rust"),
+    ("sh", "Shell"),
+    ("shell", "Shell"),
+    ("sql", "SQL"),
+    ("swift", "Swift"),
+    ("toml", "TOML"),
+    ("ts", "TypeScript"),
+    ("tsx", "TSX"),
+    ("typescript", "TypeScript"),
+    ("xml", "XML"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("zsh", "Zsh"),
+];
+
+/// The language part of a fence info string, e.g. `rust` in ```` ```rust,ignore ````.
+pub fn info_language(info: &str) -> &str {
+    info.split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+        .next()
+        .unwrap_or_default()
+}
+
+pub fn display_name(language: &str) -> String {
+    let lower = language.to_ascii_lowercase();
+    DISPLAY_NAMES
+        .iter()
+        .find(|(key, _)| *key == lower)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| language.to_string())
+}
+
+/// Wraps fenced code blocks that declare a language in a `.code-block`
+/// container carrying a language badge. The wrapper is positioned so other
+/// code-block decorations can sit alongside the badge.
+pub fn add_language_badges(events: Vec<Event>) -> Vec<Event> {
+    let mut out = Vec::with_capacity(events.len());
+    let mut wrapped = false;
+    for event in events {
+        match &event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                let language = info_language(info);
+                if !language.is_empty() {
+                    out.push(Event::Html(CowStr::from(format!(
+                        "<div class=\"code-block\" data-lang=\"{}\"><span class=\"code-lang\">{}</span>",
+                        escape_attr(language),
+                        escape_text(&display_name(language))
+                    ))));
+                    wrapped = true;
+                }
+                out.push(event);
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                out.push(event);
+                if wrapped {
+                    out.push(Event::Html(CowStr::Borrowed("</div>\n")));
+                    wrapped = false;
+                }
+            }
+            _ => out.push(event),
+        }
+    }
+    out
+}
+
+/// Radio buttons as tabs, so a group works without script. Each panel
+/// follows its button and label; the labels are ordered before the panels.
+pub const TABS_CSS: &str = r#"
+.code-tabs { display: flex; flex-wrap: wrap; margin: 1em 0; }
+.code-tabs > input { position: absolute; opacity: 0; pointer-events: none; }
+.code-tabs > label { order: 0; padding: 0.3em 0.9em; cursor: pointer; font-size: 0.9em; border-bottom: 2px solid transparent; opacity: 0.7; }
+.code-tabs > input:checked + label { border-bottom-color: var(--accent-color, #0969da); opacity: 1; font-weight: 600; }
+.code-tabs > input:focus-visible + label { outline: 2px solid var(--accent-color, #0969da); }
+.code-tabs > .code-tab-panel { order: 1; width: 100%; display: none; }
+.code-tabs > .code-tab-panel > pre, .code-tabs > .code-tab-panel > .code-block > pre { margin-top: 0; }
+.code-tabs > input:checked + label + .code-tab-panel { display: block; }
+"#;
+
+/// Cheap scan for tab annotations, so [`TABS_CSS`] is only added where needed.
+pub fn has_code_tabs(markdown: &str) -> bool {
+    markdown.lines().any(|line| {
+        let line = line.trim_start();
+        (line.starts_with("```") || line.starts_with("~~~")) && line.contains("tab")
+    })
+}
+
+/// The tab label of a fence info string such as `python tab="Python"`: the
+/// `tab=` value, or the language's name for a bare `tab`. `None` when the
+/// block isn't a tab.
+fn tab_label(info: &str) -> Option<String> {
+    let language = info_language(info);
+    let rest = &info[language.len()..];
+    let is_separator = |c: char| c.is_whitespace() || c == ',' || c == '{' || c == '}';
+    let label = if let Some(at) = rest.find("tab=") {
+        let value = &rest[at + "tab=".len()..];
+        match value.strip_prefix('"') {
+            Some(quoted) => quoted.split('"').next().unwrap_or_default(),
+            None => value.split(is_separator).next().unwrap_or_default(),
+        }
+    } else if rest.split(is_separator).any(|word| word == "tab") {
+        ""
+    } else {
+        return None;
+    };
+    Some(if label.is_empty() { display_name(language) } else { label.to_string() })
+}
+
+/// Groups runs of two or more adjacent fenced code blocks annotated with
+/// `tab="Label"` (or a bare `tab`) into one tabbed container that shows the
+/// first block until another label is clicked. Other blocks are untouched.
+///
+/// Runs before [`add_language_badges`], so badges end up inside the panels.
+pub fn group_code_tabs(events: Vec<Event>) -> Vec<Event> {
+    // Every annotated block as (start, end, label).
+    let mut blocks: Vec<(usize, usize, String)> = Vec::new();
+    let mut open: Option<(usize, String)> = None;
+    for (i, event) in events.iter().enumerate() {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => open = tab_label(info).map(|label| (i, label)),
+            Event::End(Tag::CodeBlock(_)) => {
+                if let Some((start, label)) = open.take() {
+                    blocks.push((start, i, label));
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut groups: Vec<Vec<(usize, usize, String)>> = Vec::new();
+    for block in blocks {
+        match groups.last_mut() {
+            Some(group) if group.last().is_some_and(|last| last.1 + 1 == block.0) => group.push(block),
+            _ => groups.push(vec![block]),
+        }
+    }
+    let mut before: HashMap<usize, String> = HashMap::new();
+    let mut after: HashMap<usize, String> = HashMap::new();
+    for (g, group) in groups.iter().filter(|group| group.len() > 1).enumerate() {
+        for (k, (start, end, label)) in group.iter().enumerate() {
+            let id = format!("code-tabs-{}-{}", g + 1, k);
+            let mut html = if k == 0 { "<div class=\"code-tabs\">".to_string() } else { String::new() };
+            html += &format!(
+                "<input type=\"radio\" name=\"code-tabs-{}\" id=\"{id}\"{}><label for=\"{id}\">{}</label><div class=\"code-tab-panel\">",
+                g + 1,
+                if k == 0 { " checked" } else { "" },
+                escape_text(label)
+            );
+            before.insert(*start, html);
+            after.insert(*end, if k + 1 == group.len() { "</div></div>\n" } else { "</div>" }.to_string());
+        }
+    }
+    if before.is_empty() {
+        return events;
+    }
+    let mut out = Vec::with_capacity(events.len() + before.len() * 2);
+    for (i, event) in events.into_iter().enumerate() {
+        if let Some(html) = before.remove(&i) {
+            out.push(Event::Html(CowStr::from(html)));
+        }
+        out.push(event);
+        if let Some(html) = after.remove(&i) {
+            out.push(Event::Html(CowStr::from(html)));
+        }
+    }
+    out
+}
+
+pub const DIFF_CSS: &str = r#"
+pre code.language-diff .diff-add { color: #1a7f37; background: rgba(46, 160, 67, 0.15); }
+pre code.language-diff .diff-del { color: #cf222e; background: rgba(248, 81, 73, 0.15); }
+pre code.language-diff .diff-hunk { color: #8250df; }
+"#;
+
+fn diff_line_class(line: &str) -> &'static str {
+    if line.starts_with("@@")
+        || line.starts_with("+++ ")
+        || line.starts_with("--- ")
+        || line.starts_with("diff ")
+        || line.starts_with("index ")
+    {
+        "diff-hunk"
+    } else if line.starts_with('+') {
+        "diff-add"
+    } else if line.starts_with('-') {
+        "diff-del"
+    } else {
+        "diff-ctx"
+    }
+}
+
+fn is_diff_language(info: &str) -> bool {
+    matches!(info_language(info).to_ascii_lowercase().as_str(), "diff" | "patch")
+}
+
+/// Cheap scan for a ```` ```diff ```` fence, so [`DIFF_CSS`] can be emitted in
+/// the `<head>` before the body has been rendered.
+pub fn has_diff_block(markdown: &str) -> bool {
+    markdown.lines().any(|line| {
+        let line = line.trim_start();
+        let info = line.trim_start_matches('`');
+        let info = if info.len() == line.len() { line.trim_start_matches('~') } else { info };
+        line.len() - info.len() >= 3 && is_diff_language(info.trim())
+    })
+}
+
+/// Renders ```` ```diff ```` blocks with one classed span per line.
+///
+/// The `+`/`-` markers stay in the text and every newline stays inside its
+/// span, so copying the block still yields a valid patch. Only the events of
+/// the current diff block are held back, so this works on a streamed parser.
+pub fn highlight_diff_blocks<'a, I: Iterator<Item = Event<'a>>>(events: I) -> DiffBlocks<I> {
+    DiffBlocks { events }
+}
+
+pub struct DiffBlocks<I> {
+    events: I,
+}
+
+impl<'a, I: Iterator<Item = Event<'a>>> Iterator for DiffBlocks<I> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Event<'a>> {
+        match self.events.next()? {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) if is_diff_language(&info) => {
+                let mut code = String::new();
+                for event in self.events.by_ref() {
+                    match event {
+                        Event::Text(text) => code.push_str(&text),
+                        Event::End(Tag::CodeBlock(_)) => break,
+                        _ => {}
+                    }
+                }
+                let mut html = String::from("<pre><code class=\"language-diff\">");
+                for line in code.split_inclusive('\n') {
+                    html.push_str(&format!(
+                        "<span class=\"{}\">{}</span>",
+                        diff_line_class(line),
+                        escape_text(line)
+                    ));
+                }
+                html.push_str("</code></pre>\n");
+                Some(Event::Html(CowStr::from(html)))
+            }
+            event => Some(event),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::AppState;
+use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+
+/// Actions reachable from the menu bar, keyboard shortcuts and the command
+/// palette, which all read `COMMANDS` so they can't drift apart.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Command {
+    AddFile,
+    RemoveLastAdded,
+    ClearUnpinned,
+    RenameOutputs,
+    ExportScript,
+    OpenProject,
+    SaveProject,
+    Convert,
+    Resume,
+    ResetStatuses,
+    TogglePreview,
+    ToggleDrafts,
+    ToggleReaderMode,
+    ToggleSanitize,
+    OpenPalette,
+    OpenScratch,
+    CompareWorkspaces,
+}
+
+pub struct CommandInfo {
+    pub command: Command,
+    pub name: &'static str,
+    /// Menu the command is listed under.
+    pub category: &'static str,
+    pub shortcut: Option<KeyboardShortcut>,
+    pub enabled: fn(&AppState) -> bool,
+}
+
+/// Menus in the order they appear in the menu bar.
+pub const CATEGORIES: [&str; 4] = ["File", "Convert", "Options", "View"];
+
+fn always(_: &AppState) -> bool {
+    true
+}
+
+fn has_files(state: &AppState) -> bool {
+    !state.input_files.is_empty()
+}
+
+fn can_convert(state: &AppState) -> bool {
+    state.batch_check.runnable()
+}
+
+pub const COMMANDS: &[CommandInfo] = &[
+    CommandInfo {
+        command: Command::AddFile,
+        name: "Add Markdown file…",
+        category: "File",
+        shortcut: Some(KeyboardShortcut::new(Modifiers::COMMAND, Key::O)),
+        enabled: always,
+    },
+    CommandInfo {
+        command: Command::RemoveLastAdded,
+        name: "Remove last added file",
+        category: "File",
+        shortcut: Some(KeyboardShortcut::new(Modifiers::COMMAND, Key::Backspace)),
+        enabled: |state| state.last_added_index().is_some(),
+    },
+    CommandInfo {
+        command: Command::ClearUnpinned,
+        name: "Clear unpinned files",
+        category: "File",
+        shortcut: None,
+        enabled: |state| state.entries.iter().any(|e| !e.pinned),
+    },
+    CommandInfo {
+        command: Command::RenameOutputs,
+        name: "Rename outputs…",
+        category: "File",
+        shortcut: None,
+        enabled: |state| !state.output_files.is_empty(),
+    },
+    CommandInfo {
+        command: Command::ExportScript,
+        name: "Export as script…",
+        category: "File",
+        shortcut: None,
+        enabled: has_files,
+    },
+    CommandInfo {
+        command: Command::OpenProject,
+        name: "Open project…",
+        category: "File",
+        shortcut: None,
+        enabled: always,
+    },
+    CommandInfo {
+        command: Command::SaveProject,
+        name: "Save project…",
+        category: "File",
+        shortcut: Some(KeyboardShortcut::new(Modifiers::COMMAND, Key::S)),
+        enabled: has_files,
+    },
+    CommandInfo {
+        command: Command::Convert,
+        name: "Convert to HTML",
+        category: "Convert",
+        shortcut: Some(KeyboardShortcut::new(Modifiers::COMMAND, Key::Enter)),
+        enabled: can_convert,
+    },
+    CommandInfo {
+        command: Command::Resume,
+        name: "Resume interrupted batch",
+        category: "Convert",
+        shortcut: None,
+        enabled: |state| state.resume_prompt,
+    },
+    CommandInfo {
+        command: Command::ResetStatuses,
+        name: "Reset file statuses",
+        category: "Convert",
+        shortcut: None,
+        enabled: has_files,
+    },
+    CommandInfo {
+        command: Command::TogglePreview,
+        name: "Toggle open in browser after conversion",
+        category: "Options",
+        shortcut: None,
+        enabled: always,
+    },
+    CommandInfo {
+        command: Command::ToggleDrafts,
+        name: "Toggle include drafts",
+        category: "Options",
+        shortcut: None,
+        enabled: always,
+    },
+    CommandInfo {
+        command: Command::ToggleReaderMode,
+        name: "Toggle reader mode",
+        category: "Options",
+        shortcut: None,
+        enabled: always,
+    },
+    CommandInfo {
+        command: Command::ToggleSanitize,
+        name: "Toggle sanitize generated HTML",
+        category: "Options",
+        shortcut: None,
+        enabled: always,
+    },
+    CommandInfo {
+        command: Command::OpenPalette,
+        name: "Command palette…",
+        category: "View",
+        shortcut: Some(KeyboardShortcut::new(Modifiers::COMMAND, Key::P)),
+        enabled: always,
+    },
+    CommandInfo {
+        command: Command::OpenScratch,
+        name: "Scratch editor",
+        category: "View",
+        shortcut: None,
+        enabled: always,
+    },
+    CommandInfo {
+        command: Command::CompareWorkspaces,
+        name: "Compare workspace settings…",
+        category: "View",
+        shortcut: None,
+        enabled: |state| state.workspaces.len() > 1,
+    },
+];
+
+/// The open command palette.
+#[derive(Default)]
+pub struct Palette {
+    pub query: String,
+    /// Index into the current `matches`.
+    pub selected: usize,
+}
+
+/// Commands matching `query`, best first. Every character of the query has
+/// to appear in order in "category: name"; adjacent and word-start matches
+/// rank higher.
+pub fn matches(query: &str) -> Vec<&'static CommandInfo> {
+    let mut scored: Vec<(i32, usize, &CommandInfo)> = COMMANDS
+        .iter()
+        .enumerate()
+        .filter_map(|(i, info)| {
+            let text = format!("{}: {}", info.category, info.name);
+            fuzzy_score(query, &text).map(|score| (score, i, info))
+        })
+        .collect();
+    scored.sort_by_key(|&(score, i, _)| (-score, i));
+    scored.into_iter().map(|(_, _, info)| info).collect()
+}
+
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (position..text.len()).find(|&i| text[i] == wanted)?;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 3;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 2;
+        }
+        score -= (found - position) as i32 / 4;
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
@@ -0,0 +1,56 @@
+/// After these, whitespace never matters.
+const NO_SPACE_AFTER: [char; 7] = ['{', '}', ';', ',', '>', '(', ':'];
+
+/// Before these, whitespace never matters. `(` and `:` aren't here: a space
+/// before them separates `and (` from a function call, `a :hover` from `a:hover`.
+const NO_SPACE_BEFORE: [char; 6] = ['{', '}', ';', ',', '>', ')'];
+
+/// Strips comments and the whitespace that doesn't change what `css`
+/// means, leaving strings alone. Not a full minifier: values, colours and
+/// rules are kept exactly as written.
+pub fn minify_css(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    let mut space = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+                // `a/**/b` is still two words.
+                space = true;
+            }
+            c if c.is_whitespace() => space = true,
+            c => {
+                if std::mem::take(&mut space)
+                    && !out.is_empty()
+                    && !out.ends_with(NO_SPACE_AFTER)
+                    && !NO_SPACE_BEFORE.contains(&c)
+                {
+                    out.push(' ');
+                }
+                if c == '}' && out.ends_with(';') {
+                    out.pop();
+                }
+                out.push(c);
+                if c == '"' || c == '\'' {
+                    while let Some(s) = chars.next() {
+                        out.push(s);
+                        if s == '\\' {
+                            out.extend(chars.next());
+                        } else if s == c {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    out
+}
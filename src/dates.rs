@@ -0,0 +1,58 @@
+use crate::frontmatter::FrontMatter;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone};
+use std::fs;
+use std::path::Path;
+
+pub fn file_modified(path: &Path) -> Option<DateTime<FixedOffset>> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(DateTime::<Local>::from(modified).fixed_offset())
+}
+
+/// Reads the date formats front matter commonly uses: RFC 3339, or a plain
+/// date / date-time taken to be local time.
+pub fn parse_date(text: &str) -> Option<DateTime<FixedOffset>> {
+    let text = text.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(text) {
+        return Some(date);
+    }
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S"))
+        .or_else(|_| NaiveDate::parse_from_str(text, "%Y-%m-%d").map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default()))
+        .ok()?;
+    Local.from_local_datetime(&naive).earliest().map(|d| d.fixed_offset())
+}
+
+/// The document's date: front-matter `date` when present and readable,
+/// otherwise the input file's modification time.
+pub fn document_date(front_matter: Option<&FrontMatter>, input: &Path) -> Option<DateTime<FixedOffset>> {
+    front_matter
+        .and_then(|fm| fm.get_str("date"))
+        .and_then(parse_date)
+        .or_else(|| file_modified(input))
+}
+
+/// RFC 3339 at whole-second precision, as used in meta tags and feeds.
+pub fn iso(date: &DateTime<FixedOffset>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+pub const DEFAULT_SUBFOLDER_PATTERN: &str = "%Y-%m-%d_%H%M";
+
+/// Formats `pattern` for `now` as a single folder name, rejecting strftime
+/// mistakes and characters that Windows doesn't allow in file names.
+pub fn folder_stamp(pattern: &str, now: &DateTime<Local>) -> Result<String, String> {
+    let pattern = if pattern.trim().is_empty() { DEFAULT_SUBFOLDER_PATTERN } else { pattern };
+    if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("invalid date pattern \"{}\"", pattern));
+    }
+    let name = now.format(pattern).to_string();
+    if let Some(c) = name.chars().find(|c| r#"<>:"/\|?*"#.contains(*c) || c.is_control()) {
+        return Err(format!("folder name \"{}\" contains the illegal character {:?}", name, c));
+    }
+    let trimmed = name.trim_end_matches(['.', ' ']);
+    if trimmed.is_empty() || trimmed != name {
+        return Err(format!("folder name \"{}\" can't be empty or end with a dot or space", name));
+    }
+    Ok(name)
+}
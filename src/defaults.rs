@@ -0,0 +1,61 @@
+use crate::AppState;
+use crate::messages::Message;
+use std::fs;
+use std::path::PathBuf;
+
+/// Settings baked into a preconfigured build, as JSON in the same shape as
+/// an exported options file:
+/// `MD2HTML_DEFAULTS_JSON='{"sanitize": true}' cargo build --release`.
+const EMBEDDED: Option<&str> = option_env!("MD2HTML_DEFAULTS_JSON");
+
+/// Where an administrator can put `defaults.json` for every user of the
+/// machine.
+fn system_path() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    return std::env::var_os("ProgramData").map(|dir| PathBuf::from(dir).join("md2html-gui").join("defaults.json"));
+    #[cfg(target_os = "macos")]
+    return Some(PathBuf::from("/Library/Application Support/md2html-gui/defaults.json"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    return Some(PathBuf::from("/etc/md2html-gui/defaults.json"));
+}
+
+/// Copies the keys of `layer` over `base`; keys it leaves out keep their value.
+fn merge(base: &mut serde_json::Value, layer: serde_json::Value) {
+    if let (Some(base), serde_json::Value::Object(layer)) = (base.as_object_mut(), layer) {
+        base.extend(layer);
+    }
+}
+
+impl AppState {
+    /// The state a first run starts from, before `app_state.json` exists:
+    /// the built-in defaults, then the build's embedded ones, then the
+    /// system-wide file, each overriding the one before. Once the user
+    /// changes anything it's saved to `app_state.json` as usual.
+    pub(crate) fn initial_state() -> Self {
+        let mut notes = Vec::new();
+        let mut value = serde_json::json!({});
+        if let Some(json) = EMBEDDED {
+            match serde_json::from_str(json) {
+                Ok(layer) => merge(&mut value, layer),
+                Err(e) => notes.push(Message::warning(format!("Built-in defaults ignored: {}", e))),
+            }
+        }
+        if let Some(path) = system_path().filter(|path| path.exists()) {
+            match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|text| {
+                serde_json::from_str(&text).map_err(|e| e.to_string())
+            }) {
+                Ok(layer) => {
+                    merge(&mut value, layer);
+                    notes.push(Message::info("⚙", format!("Defaults loaded from {}", path.display())));
+                }
+                Err(e) => notes.push(Message::warning(format!("{} ignored: {}", path.display(), e))),
+            }
+        }
+        let mut state = Self::from_settings_value(value).unwrap_or_else(|e| {
+            notes.push(Message::warning(format!("Defaults ignored: {}", e)));
+            Self::default()
+        });
+        state.log.extend(notes);
+        state
+    }
+}
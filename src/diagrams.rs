@@ -0,0 +1,184 @@
+use crate::code_blocks::info_language;
+use crate::limits::Slots;
+use crate::messages::Message;
+use crate::timeout::Cancel;
+use crate::toc::escape_attr;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum PlantUmlRenderer {
+    #[default]
+    Off,
+    /// `java -jar plantuml.jar -tsvg -pipe`, inlining the SVG.
+    LocalJar,
+    /// An `<img>` pointing at a PlantUML server.
+    Server,
+}
+
+impl PlantUmlRenderer {
+    pub fn label(self) -> &'static str {
+        match self {
+            PlantUmlRenderer::Off => "Off",
+            PlantUmlRenderer::LocalJar => "Local plantuml.jar",
+            PlantUmlRenderer::Server => "PlantUML server",
+        }
+    }
+}
+
+pub struct PlantUmlSettings<'a> {
+    pub renderer: PlantUmlRenderer,
+    pub jar: Option<&'a Path>,
+    pub server: &'a str,
+    pub timeout: Duration,
+    /// Shared with the batch's other workers to cap concurrent renders.
+    pub io_slots: &'a Slots,
+    /// Once set, the remaining diagrams are left as code.
+    pub cancel: &'a Cancel,
+}
+
+/// Replaces ```` ```plantuml ```` blocks with rendered diagrams.
+///
+/// Any failure leaves the block as ordinary code and adds a warning to `log`.
+pub fn render_plantuml<'a>(events: Vec<Event<'a>>, settings: &PlantUmlSettings, log: &mut Vec<Message>) -> Vec<Event<'a>> {
+    if settings.renderer == PlantUmlRenderer::Off {
+        return events;
+    }
+    let mut out = Vec::with_capacity(events.len());
+    let mut block: Option<Vec<Event>> = None;
+    for event in events {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(ref info)))
+                if matches!(info_language(info).to_ascii_lowercase().as_str(), "plantuml" | "puml") =>
+            {
+                block = Some(vec![event]);
+            }
+            Event::End(Tag::CodeBlock(_)) if block.is_some() => {
+                let mut original = block.take().unwrap_or_default();
+                original.push(event);
+                let source: String = original
+                    .iter()
+                    .filter_map(|e| match e {
+                        Event::Text(text) => Some(text.as_ref()),
+                        _ => None,
+                    })
+                    .collect();
+                if settings.cancel.is_cancelled() {
+                    out.extend(original);
+                    continue;
+                }
+                let rendered = {
+                    let _slot = settings.io_slots.acquire();
+                    render_diagram(&wrap_source(&source), settings)
+                };
+                match rendered {
+                    Ok(html) => out.push(Event::Html(CowStr::from(html))),
+                    Err(e) => {
+                        log.push(Message::warning(format!("PlantUML diagram left as code: {}", e)));
+                        out.extend(original);
+                    }
+                }
+            }
+            event => match block.as_mut() {
+                Some(block) => block.push(event),
+                None => out.push(event),
+            },
+        }
+    }
+    // An unterminated block can't happen with pulldown-cmark, but don't lose it.
+    out.extend(block.unwrap_or_default());
+    out
+}
+
+/// Adds `@startuml`/`@enduml` unless the source already has its own fences.
+fn wrap_source(source: &str) -> String {
+    if source.trim_start().starts_with("@start") {
+        source.to_string()
+    } else {
+        format!("@startuml\n{}@enduml\n", source)
+    }
+}
+
+fn render_diagram(source: &str, settings: &PlantUmlSettings) -> Result<String, String> {
+    match settings.renderer {
+        PlantUmlRenderer::Off => Err("rendering is off".to_string()),
+        PlantUmlRenderer::LocalJar => {
+            let jar = settings.jar.ok_or("no plantuml.jar configured")?;
+            let svg = run_local(jar, source, settings.timeout)?;
+            Ok(format!("<div class=\"plantuml\">{}</div>\n", strip_xml_prolog(&svg)))
+        }
+        PlantUmlRenderer::Server => {
+            let base = settings.server.trim().trim_end_matches('/');
+            if base.is_empty() {
+                return Err("no PlantUML server configured".to_string());
+            }
+            let url = format!("{}/svg/~h{}", base, hex(source));
+            // Check the server answers so an unreachable one degrades to code.
+            ureq::get(&url)
+                .timeout(settings.timeout)
+                .call()
+                .map_err(|e| format!("server request failed: {}", e))?;
+            Ok(format!(
+                "<div class=\"plantuml\"><img src=\"{}\" alt=\"PlantUML diagram\"></div>\n",
+                escape_attr(&url)
+            ))
+        }
+    }
+}
+
+fn run_local(jar: &Path, source: &str, timeout: Duration) -> Result<String, String> {
+    let mut child = Command::new("java")
+        .arg("-jar")
+        .arg(jar)
+        .args(["-tsvg", "-pipe"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("could not start java: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(source.as_bytes())
+            .map_err(|e| format!("could not write to plantuml: {}", e))?;
+    }
+    let mut stdout = child.stdout.take().ok_or("no plantuml output")?;
+    let reader = thread::spawn(move || {
+        let mut svg = String::new();
+        stdout.read_to_string(&mut svg).map(|_| svg)
+    });
+    let started = Instant::now();
+    loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) if status.success() => break,
+            Some(status) => return Err(format!("plantuml exited with {}", status)),
+            None if started.elapsed() > timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("plantuml timed out after {}s", timeout.as_secs()));
+            }
+            None => thread::sleep(Duration::from_millis(20)),
+        }
+    }
+    reader
+        .join()
+        .map_err(|_| "plantuml output reader panicked".to_string())?
+        .map_err(|e| format!("could not read plantuml output: {}", e))
+}
+
+pub fn strip_xml_prolog(svg: &str) -> &str {
+    let svg = svg.trim_start();
+    match svg.strip_prefix("<?xml") {
+        Some(rest) => rest.find("?>").map(|i| rest[i + 2..].trim_start()).unwrap_or(svg),
+        None => svg,
+    }
+}
+
+/// PlantUML's `~h` encoding: the UTF-8 source as lowercase hex.
+fn hex(source: &str) -> String {
+    source.bytes().map(|b| format!("{:02x}", b)).collect()
+}
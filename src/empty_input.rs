@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+/// What a batch does with an input that has nothing to convert.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum EmptyInput {
+    /// Writes the page anyway, e.g. as a placeholder.
+    #[default]
+    Convert,
+    /// Writes the page and logs a warning.
+    Warn,
+    /// Leaves the file out of the batch and logs it.
+    Skip,
+}
+
+impl EmptyInput {
+    pub const ALL: [EmptyInput; 3] = [EmptyInput::Convert, EmptyInput::Warn, EmptyInput::Skip];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EmptyInput::Convert => "Convert silently",
+            EmptyInput::Warn => "Convert with a warning",
+            EmptyInput::Skip => "Skip with a warning",
+        }
+    }
+}
+
+/// Whether `md`, a page's Markdown once its front matter is split off and
+/// the pre-process hook and replacements have run, is only whitespace.
+pub fn is_empty(md: &str) -> bool {
+    md.trim().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch_runner::Job;
+    use crate::limits::Slots;
+    use crate::timeout::Cancel;
+    use crate::AppState;
+
+    #[test]
+    fn only_whitespace_is_empty() {
+        assert!(is_empty(""));
+        assert!(is_empty(" \n\t\n"));
+        assert!(!is_empty("\n# Title\n"));
+    }
+
+    /// Converts `markdown`, written to a file of its own, with `settings`.
+    /// Returns whether it was skipped as empty and whether the page was written.
+    fn convert(name: &str, markdown: &str, settings: AppState) -> (bool, bool) {
+        let dir = std::env::temp_dir().join(format!("md2html-empty-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (input, output) = (dir.join("page.md"), dir.join("page.html"));
+        std::fs::write(&input, markdown).unwrap();
+        let job = Job { index: 0, input: input.clone(), output: output.clone(), range: None, template: None };
+        let (_, result) = settings.convert_job(&job, false, &Slots::new(1), &Cancel::default());
+        let skipped = result.unwrap().skipped_empty;
+        let written = output.exists();
+        std::fs::remove_dir_all(&dir).ok();
+        (skipped, written)
+    }
+
+    #[test]
+    fn empty_input_is_skipped_after_front_matter() {
+        let settings = AppState { on_empty_input: EmptyInput::Skip, ..AppState::default() };
+        assert_eq!(convert("skip", "---\ntitle: Nothing\n---\n\n", settings), (true, false));
+        let settings = AppState { on_empty_input: EmptyInput::Warn, ..AppState::default() };
+        assert_eq!(convert("warn", "", settings), (false, true));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn content_from_the_hook_counts() {
+        let hook = std::env::temp_dir().join(format!("md2html-empty-hook-{}.sh", std::process::id()));
+        std::fs::write(&hook, "printf '# Generated\\n' > \"$1\"\n").unwrap();
+        let settings = AppState {
+            on_empty_input: EmptyInput::Skip,
+            pre_process_command: format!("sh {}", hook.display()),
+            ..AppState::default()
+        };
+        let converted = convert("hook", "", settings);
+        std::fs::remove_file(&hook).ok();
+        assert_eq!(converted, (false, true));
+    }
+}
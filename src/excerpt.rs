@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// 1-based, inclusive range of source lines to convert.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl LineRange {
+    /// Parses `start:end`, where either side may be left out, e.g. `10:`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (start, end) = text
+            .split_once(':')
+            .ok_or_else(|| format!("expected start:end, got \"{}\"", text))?;
+        let number = |part: &str, default: usize| match part.trim() {
+            "" => Ok(default),
+            part => part.parse::<usize>().map_err(|_| format!("not a line number: \"{}\"", part)),
+        };
+        Ok(Self { start: number(start, 1)?, end: number(end, usize::MAX)? })
+    }
+
+    /// The lines of `source` in range, clamped to the lines that exist, with
+    /// the first and last line actually taken. Past the end gives nothing.
+    pub fn slice<'a>(&self, source: &'a str) -> (&'a str, usize, usize) {
+        let start = self.start.max(1);
+        let end = self.end.max(start);
+        let mut begin = None;
+        let mut finish = source.len();
+        let mut last = 0;
+        let mut offset = 0;
+        for (i, line) in source.split_inclusive('\n').enumerate() {
+            let number = i + 1;
+            if number == start {
+                begin = Some(offset);
+            }
+            offset += line.len();
+            last = number;
+            if number == end {
+                finish = offset;
+                break;
+            }
+        }
+        match begin {
+            Some(begin) => (&source[begin..finish], start, last),
+            None => ("", start, start.saturating_sub(1)),
+        }
+    }
+}
+
+impl std::fmt::Display for LineRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.end {
+            usize::MAX => write!(f, "{}:", self.start),
+            end => write!(f, "{}:{}", self.start, end),
+        }
+    }
+}
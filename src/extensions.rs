@@ -0,0 +1,52 @@
+use pulldown_cmark::Options;
+use serde::{Deserialize, Serialize};
+
+/// The Markdown extensions the parser runs with, saved as one number: the
+/// bits of [`Options`].
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "u32", into = "u32")]
+pub struct Extensions(pub Options);
+
+impl Default for Extensions {
+    fn default() -> Self {
+        Extensions(Options::ENABLE_STRIKETHROUGH)
+    }
+}
+
+impl From<u32> for Extensions {
+    fn from(bits: u32) -> Self {
+        Extensions(Options::from_bits_truncate(bits))
+    }
+}
+
+impl From<Extensions> for u32 {
+    fn from(extensions: Extensions) -> Self {
+        extensions.0.bits()
+    }
+}
+
+/// Extensions with a checkbox. Footnotes follow the footnote layout setting
+/// instead, which also says how they're shown.
+pub const TOGGLES: [(Options, &str, &str); 5] = [
+    (Options::ENABLE_STRIKETHROUGH, "~~Strikethrough~~", "~~text~~ is struck through"),
+    (Options::ENABLE_TABLES, "Tables", "GitHub-style pipe tables"),
+    (Options::ENABLE_TASKLISTS, "Task lists", "- [ ] and - [x] list items become checkboxes"),
+    (Options::ENABLE_SMART_PUNCTUATION, "Smart punctuation", "Curly quotes, en and em dashes, and ellipses"),
+    (Options::ENABLE_HEADING_ATTRIBUTES, "Heading attributes", "# Heading {#id .class} sets the heading's id and classes"),
+];
+
+/// Adds `extensions` to settings saved before it existed, keeping what they
+/// implied: strikethrough was always on, and sortable tables parsed tables.
+pub fn migrate(settings: &mut serde_json::Value) {
+    let Some(map) = settings.as_object_mut() else {
+        return;
+    };
+    if map.contains_key("extensions") {
+        return;
+    }
+    let mut options = Extensions::default().0;
+    if map.get("sortable_tables").and_then(|mode| mode.as_str()).is_some_and(|mode| mode != "Off") {
+        options.insert(Options::ENABLE_TABLES);
+    }
+    map.insert("extensions".to_string(), options.bits().into());
+}
@@ -0,0 +1,151 @@
+use crate::dates;
+use crate::frontmatter::{self, FrontMatter};
+use crate::messages::Message;
+use crate::paths::{self, relative_path, to_url_path};
+use crate::toc::{escape_attr, escape_text};
+use chrono::{DateTime, FixedOffset, Local};
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Longest summary taken from a post's first paragraph, in characters.
+const SUMMARY_CHARS: usize = 280;
+
+/// One converted post, as listed in the feed.
+pub struct Post {
+    pub title: String,
+    pub date: Option<DateTime<FixedOffset>>,
+    pub output: PathBuf,
+    pub summary: String,
+}
+
+impl Post {
+    /// Reads `input` again for its date and summary: front-matter `date` and
+    /// `description` when present, otherwise the file's modification time
+    /// and the start of its first paragraph.
+    pub fn read(input: &Path, output: &Path, title: String) -> Post {
+        let source = fs::read_to_string(input).unwrap_or_default();
+        let (front_matter, md, _) = frontmatter::split_front_matter(&source);
+        let summary = match front_matter.as_ref().and_then(FrontMatter::description) {
+            Some(description) => description.to_string(),
+            None => first_paragraph(md),
+        };
+        Post { title, date: dates::document_date(front_matter.as_ref(), input), output: output.to_path_buf(), summary }
+    }
+}
+
+/// Plain text of the first paragraph, cut at a word near [`SUMMARY_CHARS`].
+fn first_paragraph(md: &str) -> String {
+    let mut text = String::new();
+    let mut inside = false;
+    for event in Parser::new_ext(md, Options::ENABLE_STRIKETHROUGH) {
+        match event {
+            Event::Start(Tag::Paragraph) => inside = true,
+            Event::End(Tag::Paragraph) if inside => break,
+            Event::Text(t) | Event::Code(t) if inside => text += &t,
+            Event::SoftBreak | Event::HardBreak if inside => text.push(' '),
+            _ => {}
+        }
+    }
+    if text.chars().count() <= SUMMARY_CHARS {
+        return text;
+    }
+    let cut: String = text.chars().take(SUMMARY_CHARS).collect();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    format!("{}…", cut.trim_end_matches([',', '.', ';', ':']))
+}
+
+/// Writes an Atom feed of `posts`, newest first, with links built from
+/// `base_url` and each page's path relative to the feed's own directory.
+/// An empty `title` uses that directory's name.
+///
+/// Pages outside that directory are returned as warnings instead, as in
+/// [`crate::sitemap::write_sitemap`].
+pub fn write_feed(feed: &Path, title: &str, base_url: &str, mut posts: Vec<Post>) -> Result<Vec<Message>, String> {
+    let feed = paths::absolute(feed);
+    let root = feed.parent().unwrap_or(Path::new("/"));
+    let base_url = base_url.trim().trim_end_matches('/');
+    let title = match title.trim() {
+        "" => root.file_name().map_or("Feed".into(), |name| name.to_string_lossy()).into_owned(),
+        title => title.to_string(),
+    };
+    posts.sort_by_key(|post| std::cmp::Reverse(post.date));
+    let updated = posts.iter().filter_map(|post| post.date).max().unwrap_or_else(|| Local::now().fixed_offset());
+    let self_url = format!("{}/{}", base_url, feed.file_name().unwrap_or_default().to_string_lossy());
+    let mut warnings = Vec::new();
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n  <title>{}</title>\n  <id>{}/</id>\n  <link href=\"{}/\"/>\n  <link rel=\"self\" href=\"{}\"/>\n  <updated>{}</updated>\n",
+        escape_text(&title),
+        escape_text(base_url),
+        escape_attr(base_url),
+        escape_attr(&self_url),
+        dates::iso(&updated),
+    );
+    for post in &posts {
+        let page = paths::absolute(&post.output);
+        let Some(relative) = relative_path(root, &page).filter(|r| !r.starts_with("..")) else {
+            warnings.push(Message::warning(format!("Feed: {} is outside {}, skipped", page.display(), root.display())));
+            continue;
+        };
+        let url = format!("{}/{}", base_url, to_url_path(&relative));
+        let date = dates::iso(&post.date.unwrap_or(updated));
+        xml += &format!(
+            "  <entry>\n    <title>{}</title>\n    <id>{}</id>\n    <link href=\"{}\"/>\n    <updated>{}</updated>\n",
+            escape_text(&post.title),
+            escape_text(&url),
+            escape_attr(&url),
+            date,
+        );
+        if !post.summary.is_empty() {
+            xml += &format!("    <summary>{}</summary>\n", escape_text(&post.summary));
+        }
+        xml += "  </entry>\n";
+    }
+    xml += "</feed>\n";
+    fs::write(&feed, xml).map_err(|e| format!("failed to write {}: {}", feed.display(), e))?;
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_first_paragraph_is_kept_whole() {
+        assert_eq!(first_paragraph("# Title\n\nFirst *line*\nand `code`.\n\nSecond."), "First line and code.");
+    }
+
+    #[test]
+    fn long_first_paragraph_is_cut_at_a_word() {
+        let md = "word, ".repeat(100);
+        let summary = first_paragraph(&md);
+        assert!(summary.ends_with("word…"), "{}", summary);
+        assert!(summary.chars().count() <= SUMMARY_CHARS + 1);
+    }
+
+    #[test]
+    fn feed_lists_posts_newest_first() {
+        let dir = std::env::temp_dir().join(format!("md2html-feed-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let date = |s| DateTime::parse_from_rfc3339(s).ok();
+        let posts = vec![
+            Post { title: "Old".into(), date: date("2024-01-01T00:00:00+00:00"), output: dir.join("old.html"), summary: String::new() },
+            Post { title: "New & shiny".into(), date: date("2024-06-01T00:00:00+00:00"), output: dir.join("new.html"), summary: "Hi".into() },
+            Post { title: "Away".into(), date: None, output: std::env::temp_dir().join("away.html"), summary: String::new() },
+        ];
+        let feed = dir.join("feed.xml");
+        let warnings = write_feed(&feed, "Blog", "https://example.com/", posts).unwrap();
+        let xml = fs::read_to_string(&feed).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(warnings.len(), 1);
+        assert!(xml.contains("<title>Blog</title>"));
+        assert!(xml.contains("<link rel=\"self\" href=\"https://example.com/feed.xml\"/>"));
+        assert!(xml.contains("<updated>2024-06-01T00:00:00+00:00</updated>\n  <entry>"));
+        let new = xml.find("<title>New &amp; shiny</title>").unwrap();
+        let old = xml.find("<title>Old</title>").unwrap();
+        assert!(new < old);
+        assert!(xml.contains("<link href=\"https://example.com/new.html\"/>"));
+        assert!(xml.contains("<summary>Hi</summary>"));
+        assert!(!xml.contains("Away"));
+    }
+}
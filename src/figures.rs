@@ -0,0 +1,154 @@
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+
+pub const FIGURE_CSS: &str = r#"
+figure { margin: 1.5em 0; text-align: center; }
+figure img { max-width: 100%; }
+figcaption { margin-top: 0.5em; font-size: 0.9em; opacity: 0.8; }
+figcaption .figure-number { font-weight: bold; }
+"#;
+
+/// Cheap scan for images, so [`FIGURE_CSS`] is only added where it may apply.
+pub fn has_images(md: &str) -> bool {
+    md.contains("![")
+}
+
+/// Where a figure's caption comes from.
+#[derive(Clone, Copy)]
+pub struct Captions {
+    /// The image title, `![alt](img.png "Caption")`.
+    pub titles: bool,
+    /// The alt text, for images without a title when `titles` is set.
+    pub alt: bool,
+}
+
+/// A paragraph holding nothing but a captioned image, optionally inside a link.
+struct Span {
+    start: usize,
+    end: usize,
+    /// The caption's HTML.
+    caption: String,
+    /// The caption is the title, so the image drops it.
+    from_title: bool,
+}
+
+fn figure_span(events: &[Event], start: usize, captions: Captions) -> Option<Span> {
+    let mut i = start + 1;
+    let linked = matches!(events.get(i), Some(Event::Start(Tag::Link(..))));
+    if linked {
+        i += 1;
+    }
+    let Some(Event::Start(Tag::Image(_, _, title))) = events.get(i) else {
+        return None;
+    };
+    let from_title = captions.titles && !title.trim().is_empty();
+    if !from_title && !captions.alt {
+        return None;
+    }
+    let image_start = i;
+    let mut depth = 0;
+    loop {
+        match events.get(i)? {
+            Event::Start(Tag::Image(..)) => depth += 1,
+            Event::End(Tag::Image(..)) => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    let caption = if from_title {
+        caption_html(title)
+    } else {
+        let mut alt = String::new();
+        html::push_html(&mut alt, events[image_start + 1..i].iter().cloned());
+        alt.trim().to_string()
+    };
+    if caption.is_empty() {
+        return None;
+    }
+    i += 1;
+    if linked {
+        if !matches!(events.get(i), Some(Event::End(Tag::Link(..)))) {
+            return None;
+        }
+        i += 1;
+    }
+    if !matches!(events.get(i), Some(Event::End(Tag::Paragraph))) {
+        return None;
+    }
+    Some(Span { start, end: i, caption, from_title })
+}
+
+/// The caption's HTML, with its inline Markdown rendered.
+fn caption_html(title: &str) -> String {
+    let mut html_text = String::new();
+    html::push_html(&mut html_text, Parser::new_ext(title, Options::ENABLE_STRIKETHROUGH));
+    let text = html_text.trim();
+    let text = text.strip_prefix("<p>").unwrap_or(text);
+    text.strip_suffix("</p>").unwrap_or(text).to_string()
+}
+
+/// Turns images that stand alone in their paragraph into `<figure>`s with
+/// their title or alt text as `<figcaption>`, as `captions` allows. Images
+/// in running text stay as they are, since a figure can't sit inside a
+/// paragraph.
+///
+/// A link around the image stays around the image, inside the figure, so a
+/// link in the caption isn't nested in it. With `numbered`, captions start
+/// with "Figure N:" and each figure gets the id `figure-N`.
+pub fn wrap_figures(events: Vec<Event>, captions: Captions, numbered: bool) -> Vec<Event> {
+    let spans: Vec<Span> = events
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| matches!(event, Event::Start(Tag::Paragraph)))
+        .filter_map(|(i, _)| figure_span(&events, i, captions))
+        .collect();
+    if spans.is_empty() {
+        return events;
+    }
+    let mut spans = spans.into_iter().enumerate().peekable();
+    let mut current: Option<(usize, Span)> = None;
+    let mut out = Vec::with_capacity(events.len());
+    for (i, event) in events.into_iter().enumerate() {
+        if let Some((number, span)) = spans.next_if(|(_, span)| span.start == i) {
+            out.push(Event::Html(CowStr::from(if numbered {
+                format!("<figure id=\"figure-{}\">", number + 1)
+            } else {
+                "<figure>".to_string()
+            })));
+            current = Some((number, span));
+            continue;
+        }
+        let Some((number, span)) = current.as_ref() else {
+            out.push(event);
+            continue;
+        };
+        if i == span.end {
+            let label = if numbered {
+                format!("<span class=\"figure-number\">Figure {}:</span> ", number + 1)
+            } else {
+                String::new()
+            };
+            out.push(Event::Html(CowStr::from(format!(
+                "<figcaption>{}{}</figcaption></figure>\n",
+                label, span.caption
+            ))));
+            current = None;
+            continue;
+        }
+        // The caption already shows the title.
+        out.push(match event {
+            Event::Start(Tag::Image(kind, dest, _)) if span.from_title => {
+                Event::Start(Tag::Image(kind, dest, CowStr::from("")))
+            }
+            Event::End(Tag::Image(kind, dest, _)) if span.from_title => {
+                Event::End(Tag::Image(kind, dest, CowStr::from("")))
+            }
+            event => event,
+        });
+    }
+    out
+}
@@ -0,0 +1,45 @@
+use base64::Engine;
+use std::fs;
+use std::path::Path;
+
+fn font_format(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("woff2") => "woff2",
+        Some("woff") => "woff",
+        Some("otf") => "opentype",
+        _ => "truetype",
+    }
+}
+
+fn mime_type(format: &str) -> &'static str {
+    match format {
+        "woff2" => "font/woff2",
+        "woff" => "font/woff",
+        "opentype" => "font/otf",
+        _ => "font/ttf",
+    }
+}
+
+/// An `@font-face` rule with the font inlined as a data URI, plus a body rule
+/// using it, so the page renders the same without the font installed.
+pub fn embedded_font_css(family: &str, path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("failed to read font {}: {}", path.display(), e))?;
+    let family = match family.trim() {
+        "" => path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+        family => family.to_string(),
+    };
+    let family = family.replace(['"', '\\'], "");
+    let format = font_format(path);
+    Ok(format!(
+        "@font-face {{ font-family: \"{family}\"; src: url(data:{mime};base64,{data}) format(\"{format}\"); font-display: swap; }}\nbody {{ font-family: \"{family}\", sans-serif; }}\n",
+        family = family,
+        mime = mime_type(format),
+        data = base64::engine::general_purpose::STANDARD.encode(bytes),
+        format = format,
+    ))
+}
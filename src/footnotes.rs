@@ -0,0 +1,248 @@
+use pulldown_cmark::{html, CowStr, Event, Tag};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum FootnoteMode {
+    /// `[^1]` isn't footnote syntax and stays as written.
+    #[default]
+    Off,
+    /// Numbered references with the notes where they're defined, usually the end.
+    Bottom,
+    /// Each note next to its reference, in the margin on wide screens.
+    Sidenotes,
+    /// Sidenotes, and the notes at the bottom too.
+    Both,
+    /// Notes at the bottom, also shown over their reference on hover or focus.
+    Tooltips,
+}
+
+impl FootnoteMode {
+    pub const ALL: [FootnoteMode; 5] = [
+        FootnoteMode::Off,
+        FootnoteMode::Bottom,
+        FootnoteMode::Sidenotes,
+        FootnoteMode::Both,
+        FootnoteMode::Tooltips,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FootnoteMode::Off => "Off",
+            FootnoteMode::Bottom => "Bottom of page",
+            FootnoteMode::Sidenotes => "Sidenotes",
+            FootnoteMode::Both => "Sidenotes and bottom",
+            FootnoteMode::Tooltips => "Tooltips and bottom",
+        }
+    }
+
+    pub fn sidenotes(self) -> bool {
+        matches!(self, FootnoteMode::Sidenotes | FootnoteMode::Both)
+    }
+
+    /// Whether notes are copied next to their references.
+    pub fn inline_notes(self) -> bool {
+        self.sidenotes() || self == FootnoteMode::Tooltips
+    }
+
+    /// Whether the definitions stay at the bottom as well.
+    fn keeps_bottom(self) -> bool {
+        matches!(self, FootnoteMode::Both | FootnoteMode::Tooltips)
+    }
+
+    /// Styles the notes placed next to their references need, if any.
+    pub fn css(self) -> Option<&'static str> {
+        match self {
+            FootnoteMode::Sidenotes | FootnoteMode::Both => Some(SIDENOTE_CSS),
+            FootnoteMode::Tooltips => Some(TOOLTIP_CSS),
+            FootnoteMode::Off | FootnoteMode::Bottom => None,
+        }
+    }
+}
+
+/// Floats notes into the right margin on wide screens. On narrow ones they
+/// are hidden until their number is tapped, then shown inline.
+pub const SIDENOTE_CSS: &str = r#"
+.sidenote-number { cursor: pointer; font-size: 0.75em; vertical-align: super; line-height: 0; color: var(--accent-color, #0969da); }
+.sidenote-toggle { display: none; }
+.sidenote { display: none; font-size: 0.85em; line-height: 1.4; }
+.sidenote-toggle:checked + .sidenote { display: block; margin: 0.5em 0 0.5em 1em; }
+@media (min-width: 72em) {
+  body { padding-right: 22rem; }
+  .sidenote, .sidenote-toggle:checked + .sidenote { display: block; float: right; clear: right; position: relative; width: 18rem; margin: 0 -21rem 1em 0; }
+  .sidenote > .sidenote-label { font-weight: bold; }
+  .sidenote-number { cursor: default; }
+}
+"#;
+
+/// Shows a note in a box above its reference while the pointer is over it or
+/// its link has keyboard focus. The link still jumps to the note at the bottom.
+pub const TOOLTIP_CSS: &str = r#"
+.footnote-tip { position: relative; }
+.footnote-tip-text { display: none; position: absolute; bottom: 1.6em; left: 50%; transform: translateX(-50%); z-index: 10; width: max-content; max-width: min(24rem, 80vw); padding: 0.5em 0.75em; font-size: 0.85em; font-weight: normal; line-height: 1.4; text-align: left; color: var(--text-color, #1f2328); background: var(--background-color, #fff); border: 1px solid rgba(127, 127, 127, 0.4); border-radius: 6px; box-shadow: 0 4px 12px rgba(0, 0, 0, 0.15); }
+.footnote-tip:hover .footnote-tip-text, .footnote-tip:focus-within .footnote-tip-text { display: block; }
+@media print { .footnote-tip-text { display: none !important; } }
+"#;
+
+/// Cheap scan for footnote syntax, so the note styles are only added to
+/// pages that might need it.
+pub fn has_footnotes(md: &str) -> bool {
+    md.contains("[^")
+}
+
+/// Puts each footnote next to its reference as a sidenote or tooltip,
+/// numbered the way the bottom section numbers them: by first appearance of
+/// the label.
+///
+/// The sidenote toggle is a `<label>` and a hidden checkbox rather than
+/// `<details>`, which isn't allowed inside a paragraph. In
+/// `FootnoteMode::Sidenotes` the definitions are dropped from the bottom.
+/// A tooltip wraps the usual reference link, so it still leads there.
+pub fn render_sidenotes(events: Vec<Event>, mode: FootnoteMode) -> Vec<Event> {
+    if !mode.inline_notes() {
+        return events;
+    }
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+    let mut number_of = |label: &str| {
+        let next = numbers.len() + 1;
+        *numbers.entry(label.to_string()).or_insert(next)
+    };
+    let mut notes: HashMap<String, String> = HashMap::new();
+    let mut kept = Vec::with_capacity(events.len());
+    let mut definition: Option<(String, Vec<Event>)> = None;
+    for event in events {
+        match event {
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                number_of(&label);
+                definition = Some((label.to_string(), Vec::new()));
+                if mode.keeps_bottom() {
+                    kept.push(Event::Start(Tag::FootnoteDefinition(label)));
+                }
+            }
+            Event::End(Tag::FootnoteDefinition(label)) => {
+                if let Some((name, inner)) = definition.take() {
+                    notes.insert(name, inline_html(inner.clone()));
+                    if mode.keeps_bottom() {
+                        kept.extend(inner);
+                    }
+                }
+                if mode.keeps_bottom() {
+                    kept.push(Event::End(Tag::FootnoteDefinition(label)));
+                }
+            }
+            event => {
+                if let Event::FootnoteReference(label) = &event {
+                    number_of(label);
+                }
+                match &mut definition {
+                    Some((_, inner)) => inner.push(event),
+                    None => kept.push(event),
+                }
+            }
+        }
+    }
+    let mut out = Vec::with_capacity(kept.len());
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for event in kept {
+        let Event::FootnoteReference(label) = &event else {
+            out.push(event);
+            continue;
+        };
+        let (Some(note), Some(&number)) = (notes.get(&**label), numbers.get(&**label)) else {
+            out.push(event);
+            continue;
+        };
+        if mode == FootnoteMode::Tooltips {
+            out.push(Event::Html(CowStr::from("<span class=\"footnote-tip\">")));
+            out.push(event);
+            out.push(Event::Html(CowStr::from(format!(
+                "<span class=\"footnote-tip-text\" role=\"tooltip\">{note}</span></span>"
+            ))));
+            continue;
+        }
+        // Every reference gets its own toggle, so ids need the repeat count.
+        let repeat = seen.entry(label.to_string()).or_default();
+        *repeat += 1;
+        let id = format!("sn-{}-{}", number, repeat);
+        let marker = if mode == FootnoteMode::Both {
+            out.push(event);
+            String::new()
+        } else {
+            number.to_string()
+        };
+        out.push(Event::Html(CowStr::from(format!(
+            "<label for=\"{id}\" class=\"sidenote-number\">{marker}</label><input type=\"checkbox\" id=\"{id}\" class=\"sidenote-toggle\"><span class=\"sidenote\"><span class=\"sidenote-label\">{number}.</span> {note}</span>"
+        ))));
+    }
+    out
+}
+
+/// A note's HTML with its paragraphs turned into line breaks, so it can sit
+/// inside a `<span>`.
+fn inline_html(events: Vec<Event>) -> String {
+    let mut html_text = String::new();
+    html::push_html(&mut html_text, events.into_iter());
+    let text = html_text.trim();
+    let text = text.strip_prefix("<p>").unwrap_or(text);
+    let text = text.strip_suffix("</p>").unwrap_or(text);
+    text.replace("</p>\n<p>", "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Options, Parser};
+
+    fn render(md: &str, mode: FootnoteMode) -> String {
+        let events = render_sidenotes(Parser::new_ext(md, Options::ENABLE_FOOTNOTES).collect(), mode);
+        let mut out = String::new();
+        html::push_html(&mut out, events.into_iter());
+        out
+    }
+
+    const TEXT: &str = "One[^b] and two[^a], one again[^b].\n\n[^a]: First *defined*.\n\n[^b]: Second.\n";
+
+    #[test]
+    fn sidenotes_follow_reference_order_and_drop_the_bottom() {
+        let out = render(TEXT, FootnoteMode::Sidenotes);
+        assert!(out.contains("<label for=\"sn-1-1\" class=\"sidenote-number\">1</label>"));
+        assert!(out.contains("<span class=\"sidenote-label\">1.</span> Second.</span>"));
+        assert!(out.contains("<span class=\"sidenote-label\">2.</span> First <em>defined</em>.</span>"));
+        // The repeated reference gets a toggle of its own.
+        assert!(out.contains("id=\"sn-1-2\""));
+        assert!(!out.contains("footnote-definition"));
+    }
+
+    #[test]
+    fn both_keeps_the_reference_links_and_the_bottom() {
+        let out = render(TEXT, FootnoteMode::Both);
+        assert!(out.contains("class=\"sidenote-number\"></label>"));
+        assert!(out.contains("<sup class=\"footnote-reference\">"));
+        assert!(out.contains("<div class=\"footnote-definition\" id=\"a\">"));
+    }
+
+    #[test]
+    fn multi_paragraph_notes_become_line_breaks() {
+        assert_eq!(inline_html(Parser::new("One.\n\nTwo *more*.\n").collect()), "One.<br>Two <em>more</em>.");
+    }
+
+    #[test]
+    fn tooltips_wrap_each_reference_and_keep_the_bottom() {
+        let out = render(TEXT, FootnoteMode::Tooltips);
+        assert_eq!(out.matches("<span class=\"footnote-tip\"><sup class=\"footnote-reference\">").count(), 3);
+        assert!(out.contains("<span class=\"footnote-tip-text\" role=\"tooltip\">First <em>defined</em>.</span></span>"));
+        assert!(out.contains("<div class=\"footnote-definition\" id=\"b\">"));
+        assert!(!out.contains("sidenote"));
+        assert_eq!(FootnoteMode::Tooltips.css(), Some(TOOLTIP_CSS));
+    }
+
+    #[test]
+    fn off_and_bottom_leave_the_events_alone() {
+        for mode in [FootnoteMode::Off, FootnoteMode::Bottom] {
+            let md = "See[^n].\n\n[^n]: Note.\n";
+            let mut plain = String::new();
+            html::push_html(&mut plain, Parser::new_ext(md, Options::ENABLE_FOOTNOTES));
+            assert_eq!(render(md, mode), plain);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// How much of a file `file_is_draft` reads looking for front matter.
+const DRAFT_SCAN_BYTES: u64 = 64 * 1024;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FrontMatterFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl FrontMatterFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            FrontMatterFormat::Yaml => "YAML",
+            FrontMatterFormat::Toml => "TOML",
+            FrontMatterFormat::Json => "JSON",
+        }
+    }
+}
+
+/// Metadata block found at the very top of a Markdown file.
+pub struct FrontMatter {
+    pub format: FrontMatterFormat,
+    /// All keys, normalised to JSON values whatever the source format.
+    pub values: Map<String, Value>,
+}
+
+impl FrontMatter {
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.values.get(key).and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty())
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.values.get(key).and_then(Value::as_bool)
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.get_str("title")
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.get_str("author")
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.get_str("description")
+    }
+
+    /// `draft: true`, also accepting the string `"true"`.
+    pub fn is_draft(&self) -> bool {
+        match self.values.get("draft") {
+            Some(Value::Bool(draft)) => *draft,
+            Some(Value::String(s)) => s.trim().eq_ignore_ascii_case("true"),
+            _ => false,
+        }
+    }
+
+    /// `tags` as either a list or a comma-separated string.
+    pub fn tags(&self) -> Vec<String> {
+        match self.values.get("tags") {
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string).or_else(|| (!v.is_null()).then(|| v.to_string())))
+                .collect(),
+            Some(Value::String(s)) => s.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Splits front matter off `source`, returning it with the remaining
+/// Markdown and, when a fenced block didn't parse, why it was kept as
+/// Markdown instead.
+///
+/// The format is picked by the opening delimiter: `---` for YAML, `+++` for
+/// TOML, and either `;;;` fences or a bare leading `{` object for JSON.
+/// A leading `{` only counts when a whole JSON object follows and ends its
+/// line; anything else is Markdown that happens to start with a brace.
+pub fn split_front_matter(source: &str) -> (Option<FrontMatter>, &str, Option<String>) {
+    let text = source.strip_prefix('\u{feff}').unwrap_or(source);
+    if text.starts_with('{') {
+        let mut stream = serde_json::Deserializer::from_str(text).into_iter::<Value>();
+        return match stream.next() {
+            Some(Ok(Value::Object(values))) if first_line(&text[stream.byte_offset()..]).trim().is_empty() => {
+                let end = stream.byte_offset();
+                (Some(FrontMatter { format: FrontMatterFormat::Json, values }), &text[end..], None)
+            }
+            _ => (None, source, None),
+        };
+    }
+    let (format, fence) = match first_line(text) {
+        "---" => (FrontMatterFormat::Yaml, "---"),
+        "+++" => (FrontMatterFormat::Toml, "+++"),
+        ";;;" => (FrontMatterFormat::Json, ";;;"),
+        _ => return (None, source, None),
+    };
+    let body_start = text.find('\n').map(|i| i + 1).unwrap_or(text.len());
+    let mut offset = body_start;
+    let mut block_end = None;
+    for line in text[body_start..].split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == fence || (format == FrontMatterFormat::Yaml && trimmed == "...") {
+            block_end = Some((offset, offset + line.len()));
+            break;
+        }
+        offset += line.len();
+    }
+    // An opening fence without a closing one is an ordinary thematic break.
+    let Some((raw_end, rest_start)) = block_end else {
+        return (None, source, None);
+    };
+    let raw = &text[body_start..raw_end];
+    match parse_block(format, raw) {
+        Ok(Some(values)) => (Some(FrontMatter { format, values }), &text[rest_start..], None),
+        // e.g. two thematic breaks around a plain paragraph.
+        Ok(None) => (None, source, None),
+        // Just as likely a thematic break and a setext heading, so the
+        // text is rendered rather than the conversion failed.
+        Err(e) => (None, source, Some(format!("not {} front matter, kept as Markdown: {}", format.label(), e))),
+    }
+}
+
+/// The front matter at the top of `source`, where `md` is what
+/// [`split_front_matter`] left after it, as an `<!-- frontmatter … -->`
+/// comment. Fences are dropped; the block is otherwise kept as written,
+/// except that `-->` can't appear inside a comment.
+pub fn as_html_comment(source: &str, md: &str) -> String {
+    let block = &source[..source.len() - md.len()];
+    let block = block.strip_prefix('\u{feff}').unwrap_or(block).trim();
+    let mut lines: Vec<&str> = block.lines().collect();
+    if matches!(lines.first().map(|line| line.trim_end()), Some("---" | "+++" | ";;;")) {
+        lines.remove(0);
+        lines.pop();
+    }
+    let text = lines.join("\n").replace("-->", "-- >").replace("--!>", "--! >");
+    format!("<!-- frontmatter\n{}\n-->\n", text)
+}
+
+/// Whether the file's front matter marks it as a draft, parsing only the
+/// block at the top rather than the whole document.
+///
+/// Unreadable files and invalid front matter count as not drafts; the
+/// conversion itself reports those.
+pub fn file_is_draft(path: &Path) -> bool {
+    let mut head = Vec::new();
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    if file.take(DRAFT_SCAN_BYTES).read_to_end(&mut head).is_err() {
+        return false;
+    }
+    let head = String::from_utf8_lossy(&head);
+    matches!(split_front_matter(&head), (Some(fm), _, _) if fm.is_draft())
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default().trim_end()
+}
+
+/// Parses a fenced block; `None` means it holds something other than key/value pairs.
+fn parse_block(format: FrontMatterFormat, raw: &str) -> Result<Option<Map<String, Value>>, String> {
+    if raw.trim().is_empty() {
+        return Ok(Some(Map::new()));
+    }
+    let value = match format {
+        FrontMatterFormat::Yaml => serde_yaml::from_str::<Value>(raw).map_err(|e| e.to_string())?,
+        FrontMatterFormat::Toml => toml_to_json(raw.parse::<toml::Value>().map_err(|e| e.to_string())?),
+        FrontMatterFormat::Json => {
+            let trimmed = raw.trim();
+            // `;;;` blocks may hold the object's members without the braces.
+            if trimmed.starts_with('{') {
+                serde_json::from_str(trimmed).map_err(|e| e.to_string())?
+            } else {
+                serde_json::from_str(&format!("{{{}}}", trimmed)).map_err(|e| e.to_string())?
+            }
+        }
+    };
+    match value {
+        Value::Object(map) => Ok(Some(map)),
+        Value::Null => Ok(Some(Map::new())),
+        _ => Ok(None),
+    }
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_block_is_split_off() {
+        let (fm, md, warning) = split_front_matter("---\ntitle: Hello\ndraft: true\n---\n# Body\n");
+        let fm = fm.unwrap();
+        assert!(fm.format == FrontMatterFormat::Yaml);
+        assert_eq!(fm.title(), Some("Hello"));
+        assert!(fm.is_draft());
+        assert_eq!(md, "# Body\n");
+        assert!(warning.is_none());
+    }
+
+    #[test]
+    fn toml_block_is_split_off() {
+        let (fm, md, _) = split_front_matter("+++\ntitle = \"Hello\"\ntags = [\"a\", \"b\"]\n+++\nBody\n");
+        let fm = fm.unwrap();
+        assert!(fm.format == FrontMatterFormat::Toml);
+        assert_eq!(fm.tags(), ["a", "b"]);
+        assert_eq!(md, "Body\n");
+    }
+
+    #[test]
+    fn json_object_and_fenced_members_are_split_off() {
+        let (fm, md, _) = split_front_matter("{\"title\": \"Hello\"}\nBody\n");
+        assert_eq!(fm.unwrap().title(), Some("Hello"));
+        assert_eq!(md, "\nBody\n");
+        let (fm, md, _) = split_front_matter(";;;\n\"title\": \"Fenced\"\n;;;\nBody\n");
+        assert_eq!(fm.unwrap().title(), Some("Fenced"));
+        assert_eq!(md, "Body\n");
+    }
+
+    #[test]
+    fn leading_brace_that_isnt_json_is_markdown() {
+        for source in ["{not json}\n", "{\"a\": 1} and then prose\n", "[1, 2]\n"] {
+            let (fm, md, warning) = split_front_matter(source);
+            assert!(fm.is_none() && warning.is_none());
+            assert_eq!(md, source);
+        }
+    }
+
+    #[test]
+    fn thematic_breaks_are_not_front_matter() {
+        // A setext heading between thematic breaks, which isn't valid YAML.
+        let source = "---\nNote: the setup: it works\n---\nBody\n";
+        let (fm, md, warning) = split_front_matter(source);
+        assert!(fm.is_none());
+        assert_eq!(md, source);
+        assert!(warning.unwrap().contains("YAML"));
+        // A plain paragraph parses as a YAML string, which isn't front matter either.
+        let (fm, md, warning) = split_front_matter("---\nJust text\n---\n");
+        assert!(fm.is_none() && warning.is_none());
+        assert_eq!(md, "---\nJust text\n---\n");
+        // An opening fence alone is a thematic break.
+        assert!(split_front_matter("---\n# Heading\n").0.is_none());
+    }
+}
@@ -0,0 +1,180 @@
+use pulldown_cmark::{CowStr, Event, Tag};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum HeadingCase {
+    /// Headings keep their source capitalization.
+    #[default]
+    None,
+    /// "The Lord of the Rings": every word but small ones capitalized.
+    TitleCase,
+    /// "The lord of the rings": only the first word capitalized.
+    SentenceCase,
+}
+
+impl HeadingCase {
+    pub fn label(self) -> &'static str {
+        match self {
+            HeadingCase::None => "As written",
+            HeadingCase::TitleCase => "Title Case",
+            HeadingCase::SentenceCase => "Sentence case",
+        }
+    }
+}
+
+/// Lowercased in title case unless they start or end the heading or follow a colon.
+const SMALL_WORDS: [&str; 22] = [
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "off", "on", "or", "per", "so", "the", "to",
+    "up", "via", "vs", "yet",
+];
+
+/// Re-capitalizes the text of every heading in place.
+///
+/// Inline code is left alone, as are words that look like acronyms or
+/// brand names because they have capitals after the first letter ("API",
+/// "iPhone", "GitHub").
+pub fn apply_heading_case(events: &mut [Event], case: HeadingCase) {
+    if case == HeadingCase::None {
+        return;
+    }
+    let mut i = 0;
+    while i < events.len() {
+        if let Event::Start(Tag::Heading(..)) = events[i] {
+            let end = (i..events.len())
+                .find(|&j| matches!(events[j], Event::End(Tag::Heading(..))))
+                .unwrap_or(events.len());
+            // The heading's words as one text, inline code standing in as a
+            // single word, so words split over events are counted once.
+            let words: String = events[i + 1..end]
+                .iter()
+                .map(|event| match event {
+                    Event::Text(text) => text,
+                    Event::Code(_) => "x",
+                    Event::SoftBreak | Event::HardBreak => " ",
+                    _ => "",
+                })
+                .collect();
+            let total = words.split_whitespace().count();
+            let mut recaser = Recaser { case, index: 0, total, after_colon: false, in_word: false };
+            for event in &mut events[i + 1..end] {
+                match event {
+                    Event::Text(text) => *event = Event::Text(CowStr::from(recaser.recase(text))),
+                    Event::Code(_) => recaser.skip_word(),
+                    Event::SoftBreak | Event::HardBreak => recaser.in_word = false,
+                    _ => {}
+                }
+            }
+            i = end;
+        }
+        i += 1;
+    }
+}
+
+/// Walks the words of one heading, which may be split over several events,
+/// e.g. at `&` or around inline code.
+struct Recaser {
+    case: HeadingCase,
+    /// Position in the heading of the word being recased.
+    index: usize,
+    total: usize,
+    after_colon: bool,
+    /// Whether the last event ended inside a word, which the next continues.
+    in_word: bool,
+}
+
+impl Recaser {
+    fn recase(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for piece in text.split_inclusive(char::is_whitespace) {
+            let word = piece.trim_end();
+            if !word.is_empty() {
+                if self.in_word {
+                    // The rest of a word begun in an earlier event, as written.
+                    out.push_str(word);
+                } else {
+                    let first = self.index == 0;
+                    let last = self.index + 1 == self.total;
+                    out.push_str(&self.recase_word(word, first, last));
+                    self.index += 1;
+                }
+                self.after_colon = word.ends_with(':');
+            }
+            out.push_str(&piece[word.len()..]);
+            self.in_word = !word.is_empty() && word.len() == piece.len();
+        }
+        out
+    }
+
+    /// Steps over inline code, which keeps its case but takes a word's place.
+    fn skip_word(&mut self) {
+        if !self.in_word {
+            self.index += 1;
+        }
+        self.in_word = true;
+        self.after_colon = false;
+    }
+
+    fn recase_word(&self, word: &str, first: bool, last: bool) -> String {
+        let Some(start) = word.find(char::is_alphabetic) else {
+            return word.to_string();
+        };
+        let (prefix, letters) = word.split_at(start);
+        let mut chars = letters.chars();
+        let head = chars.next().unwrap_or_default();
+        let rest = chars.as_str();
+        if rest.chars().any(char::is_uppercase) || letters == "I" || letters.starts_with("I'") {
+            return word.to_string();
+        }
+        let capitalize = match self.case {
+            HeadingCase::None => return word.to_string(),
+            HeadingCase::TitleCase => {
+                let bare = letters.trim_end_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+                first || last || self.after_colon || !SMALL_WORDS.contains(&bare.as_str())
+            }
+            HeadingCase::SentenceCase => first,
+        };
+        let head: String = if capitalize { head.to_uppercase().collect() } else { head.to_lowercase().collect() };
+        format!("{}{}{}", prefix, head, rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{html, Parser};
+
+    fn render(md: &str, case: HeadingCase) -> String {
+        let mut events: Vec<Event> = Parser::new(md).collect();
+        apply_heading_case(&mut events, case);
+        let mut out = String::new();
+        html::push_html(&mut out, events.into_iter());
+        out
+    }
+
+    #[test]
+    fn title_case_keeps_small_words_low() {
+        assert_eq!(render("# the lord of the rings", HeadingCase::TitleCase), "<h1>The Lord of the Rings</h1>\n");
+        assert_eq!(render("# using the GitHub API", HeadingCase::TitleCase), "<h1>Using the GitHub API</h1>\n");
+        assert_eq!(render("# part one: the end", HeadingCase::TitleCase), "<h1>Part One: The End</h1>\n");
+    }
+
+    #[test]
+    fn words_split_at_ampersands_stay_one_word() {
+        assert_eq!(render("# rock&amp;roll of the day", HeadingCase::TitleCase), "<h1>Rock&amp;roll of the Day</h1>\n");
+        assert_eq!(render("# q&amp;a for the team", HeadingCase::SentenceCase), "<h1>Q&amp;a for the team</h1>\n");
+        assert_eq!(render("# salt &amp; the sea", HeadingCase::TitleCase), "<h1>Salt &amp; the Sea</h1>\n");
+    }
+
+    #[test]
+    fn inline_code_takes_a_word_position() {
+        assert_eq!(render("# `npm` Install Guide", HeadingCase::SentenceCase), "<h1><code>npm</code> install guide</h1>\n");
+        assert_eq!(render("# running `cargo` in a loop", HeadingCase::TitleCase), "<h1>Running <code>cargo</code> in a Loop</h1>\n");
+        assert_eq!(render("# the `x` of", HeadingCase::TitleCase), "<h1>The <code>x</code> Of</h1>\n");
+    }
+
+    #[test]
+    fn sentence_case_capitalizes_only_the_first_word() {
+        assert_eq!(render("# The Lord Of The Rings", HeadingCase::SentenceCase), "<h1>The lord of the rings</h1>\n");
+        assert_eq!(render("# Tom & Jerry Go West", HeadingCase::SentenceCase), "<h1>Tom &amp; jerry go west</h1>\n");
+    }
+}
@@ -0,0 +1,132 @@
+use pulldown_cmark::{CowStr, Event, Tag};
+use regex::Regex;
+use std::ops::Range;
+
+/// Default look of highlighted terms, in light and dark themes alike.
+pub const MARK_CSS: &str = r#"
+mark { background: rgba(255, 213, 0, 0.4); color: inherit; padding: 0 0.1em; border-radius: 2px; }
+"#;
+
+/// Wraps whole-word, case-insensitive matches of a list of terms in
+/// `<mark>`, leaving code blocks, inline code and image alt text alone.
+///
+/// A match counts as a whole word when no letter, digit or underscore is
+/// right before or after it, which also works for terms such as "C++",
+/// ".NET" or "#tag" that start or end with punctuation, where `\b` doesn't.
+pub struct Highlighter {
+    pattern: Option<Regex>,
+    /// Code blocks and images we're inside of.
+    skip_depth: usize,
+}
+
+impl Highlighter {
+    pub fn new(terms: &[String]) -> Self {
+        let mut terms: Vec<&str> = terms.iter().map(|term| term.trim()).filter(|term| !term.is_empty()).collect();
+        // Longest first, so "C++" wins over "C" where both match.
+        terms.sort_by_key(|term| std::cmp::Reverse(term.len()));
+        let pattern = if terms.is_empty() {
+            None
+        } else {
+            let alternatives: Vec<String> = terms.into_iter().map(regex::escape).collect();
+            Regex::new(&format!("(?i)(?:{})", alternatives.join("|"))).ok()
+        };
+        Self { pattern, skip_depth: 0 }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.pattern.is_some()
+    }
+
+    pub fn apply<'a>(&mut self, event: Event<'a>) -> Vec<Event<'a>> {
+        let Some(pattern) = &self.pattern else {
+            return vec![event];
+        };
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::Image(..)) => self.skip_depth += 1,
+            Event::End(Tag::CodeBlock(_) | Tag::Image(..)) => self.skip_depth = self.skip_depth.saturating_sub(1),
+            Event::Text(text) if self.skip_depth == 0 => {
+                let found = whole_words(pattern, &text);
+                if found.is_empty() {
+                    return vec![Event::Text(text)];
+                }
+                let mut events = Vec::new();
+                let mut last = 0;
+                for found in found {
+                    if found.start > last {
+                        events.push(Event::Text(CowStr::from(text[last..found.start].to_string())));
+                    }
+                    events.push(Event::Html(CowStr::from("<mark>")));
+                    events.push(Event::Text(CowStr::from(text[found.clone()].to_string())));
+                    events.push(Event::Html(CowStr::from("</mark>")));
+                    last = found.end;
+                }
+                if last < text.len() {
+                    events.push(Event::Text(CowStr::from(text[last..].to_string())));
+                }
+                return events;
+            }
+            _ => {}
+        }
+        vec![event]
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Matches of `pattern` in `text` with no word character on either side.
+fn whole_words(pattern: &Regex, text: &str) -> Vec<Range<usize>> {
+    let mut found = Vec::new();
+    let mut at = 0;
+    while let Some(m) = pattern.find_at(text, at) {
+        let before = text[..m.start()].chars().next_back();
+        let after = text[m.end()..].chars().next();
+        if m.is_empty() || before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+            // Try again from the next character, where a shorter term may fit.
+            at = m.start() + text[m.start()..].chars().next().map_or(1, char::len_utf8);
+        } else {
+            found.push(m.range());
+            at = m.end();
+        }
+        if at >= text.len() {
+            break;
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{html, Parser};
+
+    fn render(md: &str, terms: &[&str]) -> String {
+        let terms: Vec<String> = terms.iter().map(|term| term.to_string()).collect();
+        let mut highlighter = Highlighter::new(&terms);
+        let mut out = String::new();
+        html::push_html(&mut out, Parser::new(md).flat_map(|event| highlighter.apply(event)));
+        out
+    }
+
+    #[test]
+    fn matches_whole_words_in_any_case() {
+        assert_eq!(render("Rust and rust", &["rust"]), "<p><mark>Rust</mark> and <mark>rust</mark></p>\n");
+        assert_eq!(render("trusty crust", &["rust"]), "<p>trusty crust</p>\n");
+    }
+
+    #[test]
+    fn terms_with_punctuation_at_the_edges_match() {
+        assert_eq!(render("I like C++.", &["C++"]), "<p>I like <mark>C++</mark>.</p>\n");
+        assert_eq!(render("Uses .NET today", &[".NET"]), "<p>Uses <mark>.NET</mark> today</p>\n");
+        assert_eq!(render("see #tag, not #tags", &["#tag"]), "<p>see <mark>#tag</mark>, not #tags</p>\n");
+        assert_eq!(render("C and C++", &["C", "C++"]), "<p><mark>C</mark> and <mark>C++</mark></p>\n");
+    }
+
+    #[test]
+    fn code_and_alt_text_are_skipped() {
+        assert_eq!(render("`rust` rust", &["rust"]), "<p><code>rust</code> <mark>rust</mark></p>\n");
+        assert_eq!(render("```\nrust\n```\n", &["rust"]), "<pre><code>rust\n</code></pre>\n");
+        assert_eq!(render("![rust](a.png)", &["rust"]), "<p><img src=\"a.png\" alt=\"rust\" /></p>\n");
+    }
+}
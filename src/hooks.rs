@@ -0,0 +1,69 @@
+use crate::messages::Message;
+use crate::timeout::Cancel;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// How often a running hook is checked for having finished or been cancelled.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs `command` with `input` appended before the file is read, e.g. a
+/// generator that writes the Markdown. What it printed goes to `log`; it's
+/// an error if it couldn't run or exited unsuccessfully.
+///
+/// The command is killed if `cancel` is set while it runs.
+pub fn run_pre_process(command: &str, input: &Path, cancel: &Cancel, log: &mut Vec<Message>) -> Result<(), Message> {
+    let mut parts = command.split_whitespace();
+    let Some(program) = parts.next() else {
+        return Ok(());
+    };
+    let mut child = Command::new(program)
+        .args(parts)
+        .arg(input)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Message::error(format!("{}: could not run pre-process command {}: {}", input.display(), program, e)))?;
+    // Read both pipes while waiting, so a chatty command can't fill one and stall.
+    let read_all = |pipe: Option<Box<dyn Read + Send>>| {
+        thread::spawn(move || {
+            let mut text = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut text);
+            }
+            String::from_utf8_lossy(&text).into_owned()
+        })
+    };
+    let stdout = read_all(child.stdout.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+    let stderr = read_all(child.stderr.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if cancel.is_cancelled() => {
+                let _ = child.kill();
+                let _ = child.wait();
+                cancel.check()?;
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                return Err(Message::error(format!("{}: pre-process command {} failed: {}", input.display(), program, e)))
+            }
+        }
+    };
+    let (stdout, stderr) = (stdout.join().unwrap_or_default(), stderr.join().unwrap_or_default());
+    log.extend(
+        stdout
+            .lines()
+            .chain(stderr.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| Message::info("🪝", format!("{}: {}", input.display(), line))),
+    );
+    if !status.success() {
+        return Err(Message::error(format!("{}: pre-process command {} exited with {}", input.display(), program, status)));
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use regex::{Captures, NoExpand, Regex};
+use serde::{Deserialize, Serialize};
+
+/// Elements extra classes and attributes can be added to.
+pub const ELEMENTS: [&str; 11] = ["table", "img", "blockquote", "pre", "a", "h1", "h2", "h3", "h4", "h5", "h6"];
+
+/// Classes and attributes added to every generated element of one kind.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ElementRule {
+    pub element: String,
+    /// Space-separated, merged into any `class` the element already has.
+    pub classes: String,
+    /// Raw attributes such as `loading="lazy"`; ones already present are kept.
+    pub attributes: String,
+}
+
+impl Default for ElementRule {
+    fn default() -> Self {
+        Self { element: "table".to_string(), classes: String::new(), attributes: String::new() }
+    }
+}
+
+/// Adds the rules' classes and attributes to the matching opening tags in
+/// `html`, including tags other features already gave a class.
+pub fn inject_attributes(html: &str, rules: &[ElementRule]) -> String {
+    let rules: Vec<&ElementRule> = rules
+        .iter()
+        .filter(|r| !r.classes.trim().is_empty() || !r.attributes.trim().is_empty())
+        .collect();
+    if rules.is_empty() {
+        return html.to_string();
+    }
+    let (Ok(tag), Ok(class)) = (
+        Regex::new(r"(?i)<(table|img|blockquote|pre|a|h[1-6])(\s[^>]*?)?(\s*/)?>"),
+        Regex::new(r#"(?i)\bclass\s*=\s*"([^"]*)""#),
+    ) else {
+        return html.to_string();
+    };
+    tag.replace_all(html, |caps: &Captures| {
+        let name = &caps[1];
+        let mut attrs = caps.get(2).map_or("", |m| m.as_str()).to_string();
+        for rule in rules.iter().filter(|r| r.element.eq_ignore_ascii_case(name)) {
+            let classes = rule.classes.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !classes.is_empty() {
+                let merged = match class.captures(&attrs) {
+                    Some(existing) => {
+                        let joined = format!("{} {}", existing[1].trim(), classes);
+                        let merged = format!("class=\"{}\"", joined.trim());
+                        class.replace(&attrs, NoExpand(&merged)).into_owned()
+                    }
+                    None => format!("{} class=\"{}\"", attrs, classes),
+                };
+                attrs = merged;
+            }
+            for attribute in split_attributes(&rule.attributes) {
+                let attr_name = attribute.split('=').next().unwrap_or_default();
+                let present = Regex::new(&format!(r"(?i)\s{}(\s|=|$)", regex::escape(attr_name)))
+                    .is_ok_and(|re| re.is_match(&attrs));
+                if !present {
+                    attrs = format!("{} {}", attrs, attribute);
+                }
+            }
+        }
+        format!("<{}{}{}>", name, attrs, caps.get(3).map_or("", |m| m.as_str()))
+    })
+    .into_owned()
+}
+
+/// Splits `a="x y" b` into `a="x y"` and `b`, respecting quotes.
+fn split_attributes(text: &str) -> Vec<String> {
+    let mut attributes = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for c in text.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (Some(q), c) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (None, c) if c.is_whitespace() => {
+                if !current.is_empty() {
+                    attributes.push(std::mem::take(&mut current));
+                }
+            }
+            (_, c) => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        attributes.push(current);
+    }
+    attributes
+}
@@ -0,0 +1,155 @@
+use crate::diagrams::strip_xml_prolog;
+use crate::messages::Message;
+use crate::toc::escape_attr;
+use crate::validate::check_well_formed;
+use pulldown_cmark::{CowStr, Event, Tag};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+/// Replaces images of small local `.svg` files with the SVG markup itself,
+/// so the drawing picks up the page's fonts and CSS.
+///
+/// Paths are resolved against `base_dir`, using the dest of each image's
+/// `End` event: link and asset rewriting only change the `Start` event, so
+/// the `End` still has the path as written in the Markdown. Remote SVGs, files larger than
+/// `max_bytes` and files that can't be read or fail `load_svg` keep their
+/// `<img>`; the last two also add a warning to `log`.
+pub fn inline_svg_images<'a>(
+    events: Vec<Event<'a>>,
+    base_dir: &Path,
+    max_bytes: u64,
+    log: &mut Vec<Message>,
+) -> Vec<Event<'a>> {
+    let mut out = Vec::with_capacity(events.len());
+    let mut image: Option<Vec<Event>> = None;
+    for event in events {
+        match event {
+            Event::Start(Tag::Image(..)) if image.is_none() => {
+                image = Some(vec![event]);
+            }
+            Event::End(Tag::Image(_, ref dest, _)) if image.is_some() => {
+                let mut original = image.take().unwrap_or_default();
+                original.push(event.clone());
+                if !is_local_svg(dest) {
+                    out.extend(original);
+                    continue;
+                }
+                let path = base_dir.join(dest.split(['?', '#']).next().unwrap_or_default());
+                let alt: String = original
+                    .iter()
+                    .filter_map(|e| match e {
+                        Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+                        _ => None,
+                    })
+                    .collect();
+                match fs::metadata(&path) {
+                    Ok(metadata) if metadata.len() <= max_bytes => {}
+                    Ok(_) => {
+                        out.extend(original);
+                        continue;
+                    }
+                    Err(e) => {
+                        log.push(Message::warning(format!("{} kept as <img>: {}", path.display(), e)));
+                        out.extend(original);
+                        continue;
+                    }
+                }
+                match load_svg(&path, &alt) {
+                    Ok(svg) => out.push(Event::Html(CowStr::from(svg))),
+                    Err(e) => {
+                        log.push(Message::warning(format!("{} kept as <img>: {}", path.display(), e)));
+                        out.extend(original);
+                    }
+                }
+            }
+            event => match image.as_mut() {
+                Some(image) => image.push(event),
+                None => out.push(event),
+            },
+        }
+    }
+    out.extend(image.unwrap_or_default());
+    out
+}
+
+fn is_local_svg(dest: &str) -> bool {
+    let path = dest.split(['?', '#']).next().unwrap_or_default();
+    !dest.contains("://")
+        && !dest.starts_with("//")
+        && !dest.starts_with("data:")
+        && path.to_ascii_lowercase().ends_with(".svg")
+}
+
+/// Reads an SVG file without its prolog, doctype or `<script>` elements and
+/// labels the root `<svg>` with `alt`.
+///
+/// Files with `on…` event handlers or `javascript:` links are refused rather
+/// than cleaned, since inlined they'd run on the page itself.
+fn load_svg(path: &Path, alt: &str) -> Result<String, String> {
+    let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    clean_svg(&source, alt)
+}
+
+fn clean_svg(source: &str, alt: &str) -> Result<String, String> {
+    let doctype = Regex::new(r"(?is)^\s*(<!--.*?-->\s*)*<!DOCTYPE[^>]*>").map_err(|e| e.to_string())?;
+    let scripts = Regex::new(r"(?is)<script\b[^>]*/>|<script\b.*?</script\s*>").map_err(|e| e.to_string())?;
+    let handlers = Regex::new(r#"(?i)[\s"'/]on[a-z]+\s*="#).map_err(|e| e.to_string())?;
+    // Browsers drop whitespace inside a URL's scheme, and a character
+    // reference in a link or animated value could spell it out too.
+    let javascript = Regex::new(
+        r#"(?i)j\s*a\s*v\s*a\s*s\s*c\s*r\s*i\s*p\s*t\s*:|\b(?:href|to|from|by|values)\s*=\s*("[^"]*|'[^']*)&"#,
+    )
+    .map_err(|e| e.to_string())?;
+    let svg = strip_xml_prolog(source);
+    let svg = doctype.replace(svg, "");
+    let svg = scripts.replace_all(svg.trim(), "");
+    if handlers.is_match(&svg) {
+        return Err("has event handlers".to_string());
+    }
+    if javascript.is_match(&svg) {
+        return Err("has javascript: links".to_string());
+    }
+    if !svg.starts_with("<svg") || !svg.ends_with("</svg>") {
+        return Err("not a single <svg> element".to_string());
+    }
+    if let Some(problem) = check_well_formed(&svg).into_iter().next() {
+        return Err(format!("malformed SVG ({})", problem));
+    }
+    let label = if alt.trim().is_empty() {
+        " aria-hidden=\"true\"".to_string()
+    } else {
+        format!(" role=\"img\" aria-label=\"{}\"", escape_attr(alt.trim()))
+    };
+    Ok(format!("<svg{}{}\n", label, &svg[4..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_svg_is_labelled_and_stripped() {
+        let source = "<?xml version=\"1.0\"?>\n<svg viewBox=\"0 0 1 1\"><script>alert(1)</script><rect/></svg>\n";
+        assert_eq!(
+            clean_svg(source, "A box").unwrap(),
+            "<svg role=\"img\" aria-label=\"A box\" viewBox=\"0 0 1 1\"><rect/></svg>\n"
+        );
+        assert!(clean_svg("<svg><a href=\"#top\"><text>&#169;</text></a></svg>", "").is_ok());
+    }
+
+    #[test]
+    fn event_handlers_are_refused() {
+        assert!(clean_svg("<svg onload=\"alert(1)\"></svg>", "").is_err());
+        assert!(clean_svg("<svg><rect ONCLICK = 'x()'/></svg>", "").is_err());
+        assert!(clean_svg("<svg><g/onmouseover=x()></g></svg>", "").is_err());
+    }
+
+    #[test]
+    fn javascript_links_are_refused() {
+        assert!(clean_svg("<svg><a href=\"javascript:alert(1)\"><rect/></a></svg>", "").is_err());
+        assert!(clean_svg("<svg><a xlink:href=\" java\tscript:x()\"><rect/></a></svg>", "").is_err());
+        assert!(clean_svg("<svg><a href=\"&#106;avascript:x()\"><rect/></a></svg>", "").is_err());
+        assert!(clean_svg("<svg><set attributeName=\"href\" to=\"javascript:x()\"/></svg>", "").is_err());
+    }
+}
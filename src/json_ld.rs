@@ -0,0 +1,57 @@
+use crate::dates;
+use crate::frontmatter::FrontMatter;
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+/// Longer headlines are cut off in search results.
+const MAX_HEADLINE_CHARS: usize = 110;
+
+/// A `<script type="application/ld+json">` describing the page as a
+/// schema.org `kind`, such as `Article` or `BlogPosting`, plus a warning for
+/// each property search engines expect but the page doesn't give.
+///
+/// The headline is the page title; the description, author and publishing
+/// date come from front matter, with `default_author` for pages without one.
+pub fn script(
+    kind: &str,
+    headline: &str,
+    front_matter: Option<&FrontMatter>,
+    default_author: &str,
+    input: &Path,
+) -> (String, Vec<String>) {
+    let mut warnings = Vec::new();
+    let mut data = Map::new();
+    data.insert("@context".into(), json!("https://schema.org"));
+    data.insert("@type".into(), json!(kind.trim()));
+    if headline.trim().is_empty() {
+        warnings.push("JSON-LD has no headline".to_string());
+    } else {
+        if headline.chars().count() > MAX_HEADLINE_CHARS {
+            warnings.push(format!("JSON-LD headline is over {} characters", MAX_HEADLINE_CHARS));
+        }
+        data.insert("headline".into(), json!(headline));
+    }
+    if let Some(description) = front_matter.and_then(FrontMatter::description) {
+        data.insert("description".into(), json!(description));
+    }
+    let author = front_matter.and_then(FrontMatter::author).or(Some(default_author.trim()).filter(|a| !a.is_empty()));
+    match author {
+        Some(author) => {
+            data.insert("author".into(), json!({ "@type": "Person", "name": author }));
+        }
+        None => warnings.push("JSON-LD has no author; set one in front matter or the settings".to_string()),
+    }
+    match front_matter.and_then(|fm| fm.get_str("date")).and_then(dates::parse_date) {
+        Some(date) => {
+            data.insert("datePublished".into(), json!(dates::iso(&date)));
+        }
+        None => warnings.push("JSON-LD has no datePublished; add a front matter date".to_string()),
+    }
+    if let Some(modified) = dates::file_modified(input) {
+        data.insert("dateModified".into(), json!(dates::iso(&modified)));
+    }
+    let text = serde_json::to_string(&Value::Object(data)).unwrap_or_default();
+    // `</script>` in a value would end the block early; `<\/` means the same in JSON.
+    let script = format!("<script type=\"application/ld+json\">{}</script>", text.replace("</", "<\\/"));
+    (script, warnings)
+}
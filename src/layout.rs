@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum OutputLayout {
+    #[default]
+    Standard,
+    /// Two columns: a sticky table of contents next to the content.
+    SidebarToc,
+}
+
+impl OutputLayout {
+    pub fn label(self) -> &'static str {
+        match self {
+            OutputLayout::Standard => "Standard",
+            OutputLayout::SidebarToc => "Sidebar TOC",
+        }
+    }
+}
+
+pub const SIDEBAR_CSS: &str = r#"
+.md2html-layout { display: grid; grid-template-columns: 16rem minmax(0, 1fr); gap: 2rem; align-items: start; }
+.md2html-sidebar { position: sticky; top: 1rem; max-height: calc(100vh - 2rem); overflow-y: auto; font-size: 0.9em; }
+.md2html-sidebar summary { display: none; }
+.md2html-sidebar ul { list-style: none; padding-left: 1em; margin: 0; }
+.md2html-sidebar > details > nav > ul { padding-left: 0; }
+.md2html-sidebar a { display: block; padding: 0.15em 0.5em; border-left: 2px solid transparent; text-decoration: none; color: inherit; }
+.md2html-sidebar a.active { border-left-color: var(--accent-color, #0969da); color: var(--accent-color, #0969da); font-weight: 600; }
+@media (max-width: 900px) {
+  .md2html-layout { display: block; }
+  .md2html-sidebar { position: static; max-height: none; margin-bottom: 1rem; }
+  .md2html-sidebar summary { display: list-item; cursor: pointer; font-weight: 600; }
+}
+"#;
+
+/// Opens the TOC on wide screens and highlights the section being read.
+pub const SCROLLSPY_JS: &str = r##"
+(function () {
+  var sidebar = document.querySelector('.md2html-sidebar');
+  if (!sidebar) return;
+  var details = sidebar.querySelector('details');
+  var wide = window.matchMedia('(min-width: 901px)');
+  function syncOpen() { if (wide.matches) details.open = true; }
+  syncOpen();
+  wide.addEventListener('change', syncOpen);
+  if (!('IntersectionObserver' in window)) return;
+  var links = {};
+  sidebar.querySelectorAll('a[href^="#"]').forEach(function (a) {
+    links[decodeURIComponent(a.getAttribute('href').slice(1))] = a;
+  });
+  var current = null;
+  function activate(id) {
+    if (current) current.classList.remove('active');
+    current = links[id] || null;
+    if (current) current.classList.add('active');
+  }
+  var visible = new Set();
+  var headings = document.querySelectorAll('.md2html-content [id]');
+  var observer = new IntersectionObserver(function (entries) {
+    entries.forEach(function (entry) {
+      if (entry.isIntersecting) visible.add(entry.target); else visible.delete(entry.target);
+    });
+    for (var i = 0; i < headings.length; i++) {
+      if (visible.has(headings[i]) && links[headings[i].id]) { activate(headings[i].id); break; }
+    }
+  }, { rootMargin: '0px 0px -70% 0px' });
+  headings.forEach(function (h) { if (links[h.id]) observer.observe(h); });
+})();
+"##;
+
+/// A thin bar fixed above everything, including the sticky sidebar.
+pub const PROGRESS_HTML: &str = "<div class=\"md2html-progress\" aria-hidden=\"true\"><div></div></div>";
+
+pub const PROGRESS_CSS: &str = r#"
+.md2html-progress { position: fixed; top: 0; left: 0; right: 0; height: 3px; z-index: 1000; pointer-events: none; }
+.md2html-progress > div { height: 100%; width: 100%; background: var(--accent-color, #0969da); transform: scaleX(0); transform-origin: 0 50%; }
+"#;
+
+/// Scales the bar with how far the page has been scrolled.
+pub const PROGRESS_JS: &str = r##"
+(function () {
+  var bar = document.querySelector('.md2html-progress > div');
+  if (!bar) return;
+  var pending = false;
+  function update() {
+    pending = false;
+    var doc = document.documentElement;
+    var scrollable = doc.scrollHeight - doc.clientHeight;
+    var ratio = scrollable > 0 ? Math.min(1, Math.max(0, doc.scrollTop / scrollable)) : 1;
+    bar.style.transform = 'scaleX(' + ratio + ')';
+  }
+  function schedule() { if (!pending) { pending = true; requestAnimationFrame(update); } }
+  window.addEventListener('scroll', schedule, { passive: true });
+  window.addEventListener('resize', schedule);
+  update();
+})();
+"##;
+
+/// Fixed in the bottom corner; hidden until the page has been scrolled.
+pub const BACK_TO_TOP_HTML: &str = "<a class=\"md2html-back-to-top\" href=\"#top\" aria-label=\"Back to top\">↑ Top</a>";
+
+pub const BACK_TO_TOP_CSS: &str = r#"
+.md2html-back-to-top { position: fixed; right: 1.5rem; bottom: 1.5rem; z-index: 1000; padding: 0.4em 0.8em; border-radius: 999px; background: var(--accent-color, #0969da); color: #fff; text-decoration: none; font-size: 0.9em; opacity: 0; visibility: hidden; transition: opacity 0.2s, visibility 0.2s; }
+.md2html-back-to-top.visible { opacity: 0.85; visibility: visible; }
+.md2html-back-to-top:hover, .md2html-back-to-top:focus { opacity: 1; }
+"#;
+
+/// Shows the button once the reader is a screen or more down the page.
+pub const BACK_TO_TOP_JS: &str = r##"
+(function () {
+  var button = document.querySelector('.md2html-back-to-top');
+  if (!button) return;
+  function update() { button.classList.toggle('visible', window.scrollY > window.innerHeight); }
+  window.addEventListener('scroll', update, { passive: true });
+  update();
+})();
+"##;
+
+/// `value` trimmed if it's a plain CSS length or `calc()`, e.g. `70ch`.
+fn css_length(value: &str) -> Option<&str> {
+    let value = value.trim();
+    let plain = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || " .%()+-*/".contains(c));
+    (!value.is_empty() && plain).then_some(value)
+}
+
+/// Keeps anchored headings clear of a fixed header, e.g. `4rem` or `72px`.
+/// Values that aren't a plain CSS length or `calc()` give `None`.
+pub fn scroll_margin_css(value: &str) -> Option<String> {
+    Some(format!(
+        "\nh1[id], h2[id], h3[id], h4[id], h5[id], h6[id] {{ scroll-margin-top: {}; }}\n",
+        css_length(value)?
+    ))
+}
+
+/// Caps the width of the text and centres it, e.g. at `70ch`: the body's,
+/// or the wrapper element's when there is one. Like [`scroll_margin_css`],
+/// other values give `None`.
+pub fn max_width_css(value: &str, wrapped: bool) -> Option<String> {
+    Some(format!(
+        "\n{} {{ max-width: {}; margin-left: auto; margin-right: auto; }}\n",
+        if wrapped { ".md2html-wrapper" } else { "body" },
+        css_length(value)?
+    ))
+}
+
+/// Starts every heading down to `h{level}` on a new printed page, except
+/// one that opens the page anyway. Screens are unaffected.
+pub fn page_break_css(level: u8) -> String {
+    let headings: Vec<String> = (1..=level.clamp(1, 6)).map(|n| format!("h{}", n)).collect();
+    let first: Vec<String> = headings.iter().map(|h| format!("{}:first-child", h)).collect();
+    format!(
+        "\n@media print {{\n  {} {{ break-before: page; page-break-before: always; }}\n  {} {{ break-before: auto; page-break-before: auto; }}\n}}\n",
+        headings.join(", "),
+        first.join(", ")
+    )
+}
+
+/// Wraps the converted body in the sidebar layout markup.
+pub fn wrap_sidebar(body: &str, toc: &str, landmarks: bool) -> String {
+    let label = if landmarks { " aria-label=\"Table of contents\"" } else { "" };
+    format!(
+        "<div class=\"md2html-layout\"><aside class=\"md2html-sidebar\"><details><summary>Contents</summary><nav class=\"md2html-toc\"{}>{}</nav></details></aside><main class=\"md2html-content\">{}</main></div>",
+        label, toc, body
+    )
+}
@@ -0,0 +1,47 @@
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+/// Resolves a "0 = auto" concurrency setting to one worker per CPU core.
+pub fn resolve_parallel(setting: usize) -> usize {
+    match setting {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+}
+
+/// A counting semaphore shared by a batch's workers, so I/O-bound sub-tasks
+/// can be capped separately from the number of files converted at once.
+pub struct Slots {
+    free: Mutex<usize>,
+    released: Condvar,
+}
+
+impl Slots {
+    pub fn new(count: usize) -> Self {
+        Self {
+            free: Mutex::new(count.max(1)),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks until a slot is free; it's handed back when the guard drops.
+    pub fn acquire(&self) -> SlotGuard<'_> {
+        let mut free = self.free.lock().unwrap_or_else(|e| e.into_inner());
+        while *free == 0 {
+            free = self.released.wait(free).unwrap_or_else(|e| e.into_inner());
+        }
+        *free -= 1;
+        SlotGuard { slots: self }
+    }
+}
+
+pub struct SlotGuard<'a> {
+    slots: &'a Slots,
+}
+
+impl Drop for SlotGuard<'_> {
+    fn drop(&mut self) {
+        *self.slots.free.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        self.slots.released.notify_one();
+    }
+}
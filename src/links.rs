@@ -0,0 +1,108 @@
+use crate::limits::Slots;
+use crate::messages::Message;
+use crate::timeout::Cancel;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Remote links checked at once per file; `io_slots` caps them across files.
+const REMOTE_WORKERS: usize = 8;
+
+/// How long a remote server gets to answer before the link counts as broken.
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Link and image targets in `md`, in order, each once.
+fn targets(md: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    for event in Parser::new_ext(md, Options::ENABLE_STRIKETHROUGH) {
+        if let Event::Start(Tag::Link(_, dest, _) | Tag::Image(_, dest, _)) = event {
+            if !targets.iter().any(|t| *t == *dest) {
+                targets.push(dest.to_string());
+            }
+        }
+    }
+    targets
+}
+
+/// Reports links in `md` that point nowhere, one log line each.
+///
+/// Relative paths are looked up next to the Markdown file. `http(s)` links
+/// are only checked when `remote` is set, with a HEAD request (falling back
+/// to GET for servers that refuse HEAD) that holds one of `io_slots`; none
+/// are started once `cancel` is set.
+pub fn check_links(md: &str, input: &Path, remote: bool, io_slots: &Slots, cancel: &Cancel) -> Vec<Message> {
+    let base = input.parent().unwrap_or(Path::new(""));
+    let mut problems = Vec::new();
+    let mut urls = Vec::new();
+    for target in targets(md) {
+        let lower = target.to_ascii_lowercase();
+        if lower.starts_with("http://") || lower.starts_with("https://") {
+            urls.push(target);
+            continue;
+        }
+        if target.starts_with('#') || target.starts_with("//") || lower.contains(':') {
+            // Anchors, protocol-relative and other schemes such as mailto:.
+            continue;
+        }
+        let path = target.split(['#', '?']).next().unwrap_or_default();
+        if path.is_empty() || path.starts_with('/') {
+            // Root-relative paths depend on where the site is served from.
+            continue;
+        }
+        if !base.join(path.replace("%20", " ")).exists() {
+            problems.push(Message::warning(format!("{}: broken link {} (file not found)", input.display(), target)));
+        }
+    }
+    if remote && !urls.is_empty() {
+        let next = AtomicUsize::new(0);
+        let failures = Mutex::new(Vec::new());
+        thread::scope(|scope| {
+            for _ in 0..REMOTE_WORKERS.min(urls.len()) {
+                scope.spawn(|| {
+                    while let Some(url) = urls.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let result = {
+                            let _slot = io_slots.acquire();
+                            if cancel.is_cancelled() {
+                                break;
+                            }
+                            check_remote(url, REMOTE_TIMEOUT)
+                        };
+                        if let Err(reason) = result {
+                            failures.lock().unwrap_or_else(|e| e.into_inner()).push((url, reason));
+                        }
+                    }
+                });
+            }
+        });
+        let mut failures = failures.into_inner().unwrap_or_else(|e| e.into_inner());
+        // Keep the document's order regardless of which request finished first.
+        failures.sort_by_key(|(url, _)| urls.iter().position(|u| u == *url));
+        problems.extend(
+            failures
+                .into_iter()
+                .map(|(url, reason)| Message::warning(format!("{}: broken link {} ({})", input.display(), url, reason))),
+        );
+    }
+    problems
+}
+
+fn check_remote(url: &str, timeout: Duration) -> Result<(), String> {
+    match ureq::head(url).timeout(timeout).call() {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(405 | 501, _)) => match ureq::get(url).timeout(timeout).call() {
+            Ok(_) => Ok(()),
+            Err(e) => Err(describe(e)),
+        },
+        Err(e) => Err(describe(e)),
+    }
+}
+
+fn describe(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, _) => format!("HTTP {}", code),
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
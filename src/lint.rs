@@ -0,0 +1,242 @@
+use crate::frontmatter::split_front_matter;
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+/// Every rule as `(id, description)`, in the order they're listed in settings.
+pub const RULES: [(&str, &str); 7] = [
+    ("MD001", "Heading levels go up one at a time"),
+    ("MD009", "Trailing spaces that make a line break"),
+    ("MD013", "Table rows longer than 120 characters"),
+    ("MD024", "Duplicate heading text"),
+    ("MD034", "Bare URL that won't become a link"),
+    ("MD2H01", "Unclosed emphasis marker"),
+    ("MD2H02", "Unclosed code fence"),
+];
+
+const TABLE_LINE_LIMIT: usize = 120;
+
+pub struct LintWarning {
+    pub line: usize,
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {} {}", self.line, self.rule, self.message)
+    }
+}
+
+/// Checks a Markdown file's text for common authoring mistakes, skipping any
+/// front matter but numbering lines from the top of the file.
+///
+/// Rules listed in `disabled` are not checked. The source is only read.
+pub fn lint_source(source: &str, disabled: &[String]) -> Vec<LintWarning> {
+    let body_start = match split_front_matter(source) {
+        (Some(_), rest, _) => source.len() - rest.len(),
+        _ => 0,
+    };
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= body_start + offset);
+    let enabled = |rule: &str| !disabled.iter().any(|d| d == rule);
+    let md = &source[body_start..];
+
+    let mut warnings = Vec::new();
+    let mut warn = |offset: usize, rule: &'static str, message: String| {
+        if enabled(rule) {
+            warnings.push(LintWarning { line: line_of(offset), rule, message });
+        }
+    };
+    let mut previous_level = None;
+    let mut heading: Option<(usize, String)> = None;
+    let mut seen_headings: HashMap<String, usize> = HashMap::new();
+    let mut link_depth = 0;
+    let mut in_code = false;
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    for (event, range) in Parser::new_ext(md, options).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading(level, ..)) => {
+                let level = heading_number(level);
+                if let Some(previous) = previous_level {
+                    if level > previous + 1 {
+                        warn(range.start, "MD001", format!("h{} follows h{}", level, previous));
+                    }
+                }
+                previous_level = Some(level);
+                heading = Some((range.start, String::new()));
+            }
+            Event::End(Tag::Heading(..)) => {
+                if let Some((start, text)) = heading.take() {
+                    let key = text.trim().to_lowercase();
+                    match seen_headings.get(&key) {
+                        Some(&first) => warn(start, "MD024", format!("\"{}\" already used on line {}", text.trim(), first)),
+                        None => {
+                            seen_headings.insert(key, line_of(start));
+                        }
+                    }
+                }
+            }
+            Event::Start(Tag::Link(..)) | Event::Start(Tag::Image(..)) => link_depth += 1,
+            Event::End(Tag::Link(..)) | Event::End(Tag::Image(..)) => link_depth -= 1,
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(Tag::CodeBlock(kind)) => {
+                in_code = false;
+                if let CodeBlockKind::Fenced(_) = kind {
+                    if !has_closing_fence(&md[range.clone()]) {
+                        warn(range.start, "MD2H02", "code fence is never closed".to_string());
+                    }
+                }
+            }
+            Event::Start(Tag::Table(_)) => {
+                let mut offset = range.start;
+                for line in md[range.clone()].split_inclusive('\n') {
+                    let length = line.trim_end().chars().count();
+                    if length > TABLE_LINE_LIMIT {
+                        warn(offset, "MD013", format!("table row is {} characters long", length));
+                    }
+                    offset += line.len();
+                }
+            }
+            Event::HardBreak if md[range.clone()].starts_with(' ') => {
+                warn(range.start, "MD009", "trailing spaces create a line break".to_string());
+            }
+            Event::Text(text) if !in_code => {
+                if let Some((_, heading_text)) = heading.as_mut() {
+                    heading_text.push_str(&text);
+                }
+                if link_depth == 0 {
+                    if let Some(at) = text.find("http://").or_else(|| text.find("https://")) {
+                        let url: String = text[at..].chars().take_while(|c| !c.is_whitespace()).collect();
+                        warn(range.start, "MD034", format!("bare URL {}; wrap it in <…>", url));
+                    }
+                }
+                if is_unclosed_emphasis(md, range.clone()) {
+                    warn(range.start, "MD2H01", format!("\"{}\" is never closed", text));
+                }
+            }
+            Event::Code(text) => {
+                if let Some((_, heading_text)) = heading.as_mut() {
+                    heading_text.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+    warnings
+}
+
+fn heading_number(level: HeadingLevel) -> u32 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// Whether a fenced block's source ends with a fence line, rather than
+/// running to the end of the document.
+fn has_closing_fence(block: &str) -> bool {
+    let mut lines = block.trim_end().lines();
+    let opening = lines.next().unwrap_or_default().trim_start();
+    let marker = if opening.starts_with('~') { '~' } else { '`' };
+    let width = opening.chars().take_while(|&c| c == marker).count();
+    lines.last().is_some_and(|last| {
+        let last = last.trim_start_matches(|c: char| c == '>' || c.is_whitespace()).trim_end();
+        last.chars().all(|c| c == marker) && last.chars().count() >= width
+    })
+}
+
+/// A run of `*` or `_` that pulldown-cmark left as text even though it's
+/// placed to open or close emphasis, i.e. its partner is missing.
+///
+/// The parser may hand a run over one marker at a time, so only the run's
+/// first marker counts.
+fn is_unclosed_emphasis(md: &str, range: Range<usize>) -> bool {
+    let text = &md[range.clone()];
+    let Some(marker) = text.chars().next().filter(|c| *c == '*' || *c == '_') else {
+        return false;
+    };
+    if !text.chars().all(|c| c == marker) || md[..range.start].ends_with(['\\', marker]) {
+        return false;
+    }
+    let run_end = range.end + md[range.end..].chars().take_while(|&c| c == marker).count();
+    let before = md[..range.start].chars().next_back();
+    let after = md[run_end..].chars().next();
+    let opens = after.is_some_and(|c| !c.is_whitespace());
+    let closes = before.is_some_and(|c| !c.is_whitespace());
+    if marker == '_' && before.is_some_and(char::is_alphanumeric) && after.is_some_and(char::is_alphanumeric) {
+        // snake_case words never open emphasis.
+        return false;
+    }
+    opens || closes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(line, rule)` of every warning for `source`.
+    fn lint(source: &str) -> Vec<(usize, &'static str)> {
+        lint_source(source, &[]).into_iter().map(|w| (w.line, w.rule)).collect()
+    }
+
+    #[test]
+    fn md001_skipped_heading_level() {
+        assert_eq!(lint("# One\n\n### Three\n"), [(3, "MD001")]);
+        assert!(lint("# One\n\n## Two\n\n# One again\n").is_empty());
+    }
+
+    #[test]
+    fn md009_trailing_space_break() {
+        assert_eq!(lint("First  \nsecond\n"), [(1, "MD009")]);
+        assert!(lint("First\\\nsecond\n").is_empty());
+    }
+
+    #[test]
+    fn md013_long_table_row() {
+        let long = format!("| a | b |\n|---|---|\n| {} | b |\n", "x".repeat(TABLE_LINE_LIMIT));
+        assert_eq!(lint(&long), [(3, "MD013")]);
+        assert!(lint("| a | b |\n|---|---|\n| c | d |\n").is_empty());
+    }
+
+    #[test]
+    fn md024_duplicate_heading() {
+        assert_eq!(lint("# Setup\n\n## setup\n"), [(3, "MD024")]);
+        let warnings = lint_source("# Setup\n\n## setup\n", &[]);
+        assert_eq!(warnings[0].message, "\"setup\" already used on line 1");
+    }
+
+    #[test]
+    fn md034_bare_url() {
+        assert_eq!(lint("See https://example.com for more.\n"), [(1, "MD034")]);
+        assert!(lint("See <https://example.com> or [it](https://example.com).\n").is_empty());
+        assert!(lint("```\nhttps://example.com\n```\n").is_empty());
+    }
+
+    #[test]
+    fn md2h01_unclosed_emphasis() {
+        assert_eq!(lint("Some **bold text\n"), [(1, "MD2H01")]);
+        assert!(lint("Some **bold** and snake_case_name and 2 * 3\n").is_empty());
+    }
+
+    #[test]
+    fn md2h02_unclosed_fence() {
+        assert_eq!(lint("Text\n\n```rust\nfn main() {}\n"), [(3, "MD2H02")]);
+        assert!(lint("````\ncode\n````\n").is_empty());
+    }
+
+    #[test]
+    fn lines_count_front_matter_and_disabled_rules_are_skipped() {
+        assert_eq!(lint("---\ntitle: T\n---\n# One\n\n### Three\n"), [(6, "MD001")]);
+        assert!(lint_source("# One\n\n### Three\n", &["MD001".to_string()]).is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+use pulldown_cmark::{Event, Tag};
+
+/// With `renumber`, makes every ordered list count from 1, whatever its
+/// first item says. Otherwise `3. Step` keeps starting the list at 3, as
+/// when a code block or paragraph splits one list into two.
+pub fn renumber_event(event: Event, renumber: bool) -> Event {
+    match event {
+        Event::Start(Tag::List(Some(_))) if renumber => Event::Start(Tag::List(Some(1))),
+        Event::End(Tag::List(Some(_))) if renumber => Event::End(Tag::List(Some(1))),
+        event => event,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{html, Parser};
+
+    fn render(md: &str, renumber: bool) -> String {
+        let mut out = String::new();
+        html::push_html(&mut out, Parser::new(md).map(|event| renumber_event(event, renumber)));
+        out
+    }
+
+    #[test]
+    fn renumbered_lists_start_at_one() {
+        assert!(render("3. Three\n4. Four\n", true).starts_with("<ol>\n<li>Three</li>"));
+        assert!(render("0. Zero\n1. One\n", true).starts_with("<ol>\n<li>Zero</li>"));
+    }
+
+    #[test]
+    fn lists_keep_their_start_when_off() {
+        assert!(render("3. Three\n4. Four\n", false).starts_with("<ol start=\"3\">"));
+        assert!(render("0. Zero\n1. One\n", false).starts_with("<ol start=\"0\">"));
+        assert!(render("1. One\n", false).starts_with("<ol>"));
+    }
+}
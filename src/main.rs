@@ -1,9 +1,192 @@
+mod alerts;
+mod assets;
+mod audit;
+mod autolink;
+mod batch_check;
+mod batch_runner;
+mod browser;
+mod code_blocks;
+mod commands;
+mod css_minify;
+mod dates;
+mod defaults;
+mod diagrams;
+mod empty_input;
+mod excerpt;
+mod extensions;
+mod feed;
+mod figures;
+mod fonts;
+mod footnotes;
+mod frontmatter;
+mod heading_case;
+mod highlight;
+mod hooks;
+mod inject;
+mod inline_svg;
+mod json_ld;
+mod layout;
+mod limits;
+mod links;
+mod lists;
+mod manifest;
+mod messages;
+mod outline;
+mod lint;
+mod paths;
+mod projects;
+mod prose;
+mod rename;
+mod replace;
+mod replace_preview;
+mod sitemap;
+mod sanitize;
+mod scratch;
+mod script;
+mod serve;
+mod snippets;
+mod split;
+mod styles;
+mod sub_sup;
+mod tables;
+mod template;
+mod timeout;
+mod timing;
+mod titles;
+mod toc;
+mod tui;
+mod urls;
+mod validate;
+mod watch;
+mod whitespace;
+mod wiki;
+mod workspace_diff;
+mod workspaces;
+
+use alerts::AlertPreset;
+use assets::AssetPaths;
+use batch_runner::Job;
+use commands::{Command, COMMANDS};
 use eframe::egui;
-use pulldown_cmark::{html, Options, Parser};
+use empty_input::EmptyInput;
+use excerpt::LineRange;
+use footnotes::FootnoteMode;
+use heading_case::HeadingCase;
+use diagrams::PlantUmlRenderer;
+use layout::OutputLayout;
+use limits::Slots;
+use messages::{Message, MessageStyle};
+use pulldown_cmark::{html, Event, Options, Parser};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+use tables::SortableTables;
+use timeout::Cancel;
+use timing::{Phase, PhaseTimings};
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum EntryStatus {
+    #[default]
+    Pending,
+    Done,
+    Failed,
+}
+
+/// How `app_state.json` is written; loading accepts either layout.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum StateFormat {
+    /// Pretty-printed while the file is small, compact once it's large.
+    #[default]
+    Auto,
+    Pretty,
+    Compact,
+}
+
+impl StateFormat {
+    fn label(self) -> &'static str {
+        match self {
+            StateFormat::Auto => "Auto",
+            StateFormat::Pretty => "Pretty",
+            StateFormat::Compact => "Compact",
+        }
+    }
+}
+
+const READER_CSS: &str = include_str!("css/reader.css");
+
+/// Written next to the outputs when `bundle_css` is on.
+const CSS_BUNDLE_NAME: &str = "bundle.css";
+
+/// Above this many bytes of compact JSON, `StateFormat::Auto` stops pretty-printing.
+const PRETTY_STATE_LIMIT: usize = 64 * 1024;
+
+/// Per-entry metadata, kept index-aligned with `input_files`/`output_files`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct FileEntry {
+    /// Disabled entries stay in the list but are skipped by conversions.
+    enabled: bool,
+    /// Pinned entries sort to the top and survive clearing the list.
+    pinned: bool,
+    status: EntryStatus,
+    /// Where the last conversion actually wrote, e.g. inside a dated subfolder.
+    last_output: Option<PathBuf>,
+    /// Bytes written by the last conversion.
+    output_size: Option<u64>,
+    /// RFC 3339 time of the last successful conversion.
+    converted_at: Option<String>,
+    /// Pages after the first when the last conversion split the file by heading.
+    split_pages: Vec<PathBuf>,
+    /// Convert only these source lines, e.g. for an excerpt.
+    line_range: Option<LineRange>,
+    /// Used instead of the global template for this file.
+    template: Option<PathBuf>,
+    /// Page title the last conversion used.
+    title: Option<String>,
+    /// Warnings the last conversion in this session logged.
+    #[serde(skip)]
+    warnings: usize,
+    #[serde(skip)]
+    input_size: Option<u64>,
+    /// Front matter says `draft: true`; re-read before every batch.
+    #[serde(skip)]
+    draft: bool,
+    /// Phase breakdown of the last conversion in this session.
+    #[serde(skip)]
+    timings: Option<PhaseTimings>,
+}
+
+impl Default for FileEntry {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pinned: false,
+            status: EntryStatus::default(),
+            last_output: None,
+            output_size: None,
+            converted_at: None,
+            split_pages: Vec::new(),
+            line_range: None,
+            template: None,
+            title: None,
+            warnings: 0,
+            input_size: None,
+            draft: false,
+            timings: None,
+        }
+    }
+}
+
+impl FileEntry {
+    /// Whether the next batch converts this entry.
+    fn included(&self, include_drafts: bool) -> bool {
+        self.enabled && (include_drafts || !self.draft)
+    }
+}
 
 #[derive(Default, Serialize, Deserialize)]
 #[serde(default)]
@@ -11,78 +194,2293 @@ struct AppState {
     input_files: Vec<PathBuf>,
     output_files: Vec<PathBuf>,
     css_path: Option<PathBuf>,
+    /// Stylesheets documents can pick by name with front matter `style:`.
+    css_presets: Vec<(String, PathBuf)>,
+    /// Reading-progress bar for documents of at least `progress_bar_min_words`;
+    /// `progress_bar: true/false` in front matter overrides both per file.
+    progress_bar: bool,
+    /// 0 means 1000.
+    progress_bar_min_words: usize,
+    /// Floating "↑ Top" link for documents of at least `back_to_top_min_words`;
+    /// `back_to_top: true/false` in front matter overrides both per file.
+    back_to_top: bool,
+    /// 0 means 1500.
+    back_to_top_min_words: usize,
+    /// Style pages as a single centered serif column for long-form reading.
+    reader_mode: bool,
+    /// In reader mode, leave out the selected CSS file instead of layering it on top.
+    reader_replaces_css: bool,
+    /// Write every stylesheet into one `bundle.css` per output folder and
+    /// link it, instead of inlining the styles into each page.
+    bundle_css: bool,
+    /// Strip comments and needless whitespace from inlined and bundled CSS.
+    minify_css: bool,
     title: String,
+    /// How a page without a title setting or front matter title is named.
+    filename_title: titles::FilenameTitle,
+    /// Keep the front matter block as an HTML comment at the top of the body.
+    front_matter_comment: bool,
+    /// schema.org type such as `Article` described in a JSON-LD block; empty for none.
+    json_ld_type: String,
+    /// JSON-LD author for pages whose front matter doesn't name one.
+    json_ld_author: String,
     preview: bool,
+    replacements: Vec<replace::Replacement>,
+    /// Log markdownlint-style warnings for each source before converting it.
+    lint: bool,
+    /// Log links to local files that don't exist.
+    check_links: bool,
+    /// Also send a HEAD request to every `http(s)` link; slow, and needs the network.
+    check_remote_links: bool,
+    /// Rule ids from `lint::RULES` that aren't checked.
+    lint_disabled_rules: Vec<String>,
+    /// Fail files that have lint warnings; set by `--strict-lint`.
+    #[serde(skip)]
+    lint_strict: bool,
+    /// From `--range start:end`; replaces every entry's line range for this run.
+    #[serde(skip)]
+    range_override: Option<LineRange>,
+    /// Running a single `--convert`, e.g. from an exported script; never
+    /// touches `app_state.json`.
+    #[serde(skip)]
+    headless: bool,
+    /// Turn `[[Page]]` and `[[target|label]]` into links to `page.html`.
+    wiki_links: bool,
+    /// Parser extensions such as tables and task lists.
+    extensions: extensions::Extensions,
+    /// Turn bare URLs and email addresses in prose into links.
+    autolink: bool,
+    /// Repository `#123` and `@name` link into, e.g.
+    /// `https://github.com/owner/repo`; empty for none.
+    reference_repo: String,
+    /// Render `H~2~O` and `E=mc^2^` as subscript and superscript.
+    sub_superscript: bool,
+    /// Number every ordered list from 1 instead of from its first item.
+    renumber_lists: bool,
+    /// Words and phrases wrapped in `<mark>` wherever they appear in prose.
+    highlight_terms: Vec<String>,
+    sanitize: bool,
+    sanitize_allowed_tags: Vec<String>,
+    sanitize_allowed_attributes: Vec<(String, String)>,
+    /// Run with each input's path appended before it's read, e.g. a generator
+    /// that writes the Markdown; empty for none. A failure fails that file.
+    pre_process_command: String,
+    /// Check each written page and log what's wrong with it; never blocks writing.
+    validate_html: bool,
+    /// External validator run with the page's path appended, e.g. `vnu`;
+    /// empty means the built-in well-formedness check.
+    validator_command: String,
+    heading_ids: bool,
+    /// Log headings whose text repeats, with the ids they were given.
+    warn_duplicate_headings: bool,
+    /// CSS length such as `4rem` kept above headings jumped to by anchor, so a
+    /// sticky header doesn't cover them; empty for none.
+    scroll_margin_top: String,
+    /// Widest the text gets, e.g. `70ch`; only with the built-in styles.
+    max_content_width: Option<String>,
+    heading_case: HeadingCase,
+    embed_fonts: bool,
+    font_family: String,
+    font_path: Option<PathBuf>,
+    emit_lastmod: bool,
+    show_updated_line: bool,
+    code_language_badges: bool,
+    /// Adjacent fenced blocks marked `tab="Label"` become one tabbed group.
+    code_tabs: bool,
+    /// Extra classes and attributes for generated elements, e.g. `table`.
+    element_rules: Vec<inject::ElementRule>,
+    /// Click-to-sort table headers; turning it on also parses pipe tables.
+    sortable_tables: SortableTables,
+    /// Also write each table to `<name>.tableN.csv` next to the page.
+    extract_tables_csv: bool,
+    alert_preset: AlertPreset,
+    /// Turn `> [!NOTE]` style blockquotes into alert boxes.
+    github_alerts: bool,
+    /// How `[^1]` footnotes are laid out; off leaves the syntax as text.
+    footnote_mode: FootnoteMode,
+    layout: OutputLayout,
+    plantuml_renderer: PlantUmlRenderer,
+    plantuml_jar: Option<PathBuf>,
+    plantuml_server: String,
+    /// Standalone images with a title become `<figure>`s captioned by it.
+    figures_from_titles: bool,
+    /// Standalone images with alt text become `<figure>`s captioned by it;
+    /// a title, when `figures_from_titles` is on, still wins.
+    images_as_figures: bool,
+    /// Start figure captions with "Figure N:".
+    number_figures: bool,
+    /// Replace `<img>`s of small local SVG files with the SVG itself.
+    inline_svg: bool,
+    /// SVGs above this many KB stay images; 0 means 50.
+    inline_svg_max_kb: u64,
+    /// Seconds a single diagram may take to render; 0 means the default of 10.
+    diagram_timeout_secs: u64,
+    /// Write a page per section, cutting before every heading of this level
+    /// or higher, with previous/next links and a table of contents across them.
+    split_by_heading: Option<u8>,
+    /// When printing, start a new page before every heading down to this level.
+    page_break_level: Option<u8>,
+    /// Write each page's heading tree to `<name>.outline.json` next to it.
+    outline_json: bool,
+    /// Write only the converted body, without the surrounding document.
+    fragment_output: bool,
+    /// Keep at most one blank line in a row, outside `<pre>` blocks.
+    collapse_blank_lines: bool,
+    /// Element wrapped around the converted body, e.g. `main`; empty for none.
+    wrapper_tag: String,
+    wrapper_class: String,
+    wrapper_id: String,
+    /// Keep the wrapper in body-fragment output too.
+    fragment_wrapper: bool,
+    /// Put the body in `<main>` and label the table of contents' `<nav>`.
+    aria_landmarks: bool,
+    /// HTML page with `{{title}}`, `{{css}}`, `{{body}}` and `{{toc}}`
+    /// placeholders, used instead of the built-in document and layout.
+    template_path: Option<PathBuf>,
+    /// Write a sitemap of the converted pages here after each batch.
+    generate_sitemap: Option<PathBuf>,
+    /// Write an Atom feed of the converted pages here after each batch.
+    generate_feed: Option<PathBuf>,
+    /// The feed's title; empty uses its folder's name.
+    feed_title: String,
+    /// Write `manifest.json` with each page's size and SHA-256 after each batch.
+    write_manifest: bool,
+    /// Serve the folder containing the outputs over HTTP after each batch.
+    serve: bool,
+    /// 0 means 8000.
+    serve_port: u16,
+    #[serde(skip)]
+    server: Option<serve::Server>,
+    /// Absolute URL the sitemap's and feed's directory is served from.
+    site_base_url: String,
+    /// Base path such as `/docs/` put in front of root-relative link and image
+    /// URLs, for sites served from a sub-path.
+    url_prefix: String,
+    /// Point relative links to `.md` files at the `.html` pages made from them.
+    rewrite_md_links: bool,
+    /// Swap a leading folder of local image sources, `(from, to)`, e.g.
+    /// `assets` for `/static/assets`; applied before `asset_paths`.
+    image_src_rewrite: Option<(String, String)>,
+    /// How local image and link paths are written when outputs go elsewhere.
+    asset_paths: AssetPaths,
+    /// Where assets are served from, for `AssetPaths::BaseUrl`.
+    asset_base_url: String,
+    /// Copy local images and stylesheets the Markdown refers to next to the
+    /// output, at the same relative paths.
+    copy_assets: bool,
+    /// Inputs above this many MB need confirming before a batch; 0 means 10.
+    large_input_threshold_mb: u64,
+    /// Put each batch's outputs in a subfolder named from the current time.
+    dated_subfolder: bool,
+    /// strftime pattern for the subfolder; empty means `dates::DEFAULT_SUBFOLDER_PATTERN`.
+    dated_subfolder_pattern: String,
+    /// Files converted at the same time; 0 means one per CPU core.
+    max_parallel: usize,
+    /// I/O-bound sub-tasks such as diagram renders in flight at once, across
+    /// all files; 0 means 4.
+    max_parallel_io: usize,
+    /// Append a line per converted or failed file here, across runs.
+    log_file: Option<PathBuf>,
+    /// Tries at opening the browser after conversion before giving up; 0
+    /// means 3.
+    browser_open_attempts: u32,
+    /// Pages from the last batches still being opened in the browser.
     #[serde(skip)]
-    status_message: String,
+    browser_open: browser::BackgroundOpen,
+    /// Size at which the log file is rotated to `<name>.1`; 0 means 1024 KB.
+    log_file_max_kb: u64,
+    /// Seconds one file may take before it fails and the batch moves on;
+    /// 0 means no limit.
+    per_file_timeout_secs: u64,
+    entries: Vec<FileEntry>,
+    state_format: StateFormat,
+    /// Inputs with only whitespace or front matter in them.
+    on_empty_input: EmptyInput,
+    /// Convert files dropped onto the window straight away instead of only
+    /// queuing them.
+    convert_on_drop: bool,
+    /// Convert drafts too, e.g. for a local preview. They still stay out of the sitemap.
+    include_drafts: bool,
+    /// Reconvert inputs when they change, and everything when a stylesheet
+    /// or template does.
+    watch: bool,
+    #[serde(skip)]
+    watcher: watch::Watcher,
+    /// Set while a batch runs; still set on launch if the app died mid-batch.
+    batch_in_progress: bool,
+    /// The batch converting in the background, if any.
+    #[serde(skip)]
+    batch: Option<batch_runner::Runner>,
+    /// The dated subfolder of the current batch, reused when resuming it.
+    batch_folder: Option<String>,
+    #[serde(skip)]
+    resume_prompt: bool,
+    #[serde(skip)]
+    status_message: Message,
     #[serde(skip)]
     progress: f32,
+    #[serde(skip)]
+    log: Vec<Message>,
+    /// How the status line, log and command-line output show messages.
+    message_style: MessageStyle,
+    /// Enabled entries over the size threshold, awaiting confirmation, and
+    /// the entries the batch is limited to, if any.
+    #[serde(skip)]
+    large_input_confirm: Option<(Vec<usize>, Option<Vec<usize>>)>,
+    #[serde(skip)]
+    show_rename_dialog: bool,
+    #[serde(skip)]
+    rename_rule: rename::RenameRule,
+    /// Output paths from before the last applied rename.
+    #[serde(skip)]
+    rename_undo: Option<Vec<PathBuf>>,
+    /// Input of the entry added most recently this session, which
+    /// Ctrl+Backspace removes wherever pinning has moved it.
+    #[serde(skip)]
+    last_added: Option<PathBuf>,
+    #[serde(skip)]
+    palette: Option<commands::Palette>,
+    /// Project waiting for confirmation before it replaces the workspace.
+    #[serde(skip)]
+    project_confirm: Option<PathBuf>,
+    /// In-memory conversion of one entry: its input and the generated HTML.
+    #[serde(skip)]
+    html_preview: Option<(PathBuf, String)>,
+    #[serde(skip)]
+    scratch: Option<scratch::Scratch>,
+    #[serde(skip)]
+    replace_preview: Option<replace_preview::ReplacePreview>,
+    #[serde(skip)]
+    workspace_diff: Option<workspace_diff::WorkspaceDiff>,
+    /// Workspace tabs; the active one's files and settings are the fields above.
+    workspaces: Vec<workspaces::Workspace>,
+    active_workspace: usize,
+    /// Last title sent to the window, so it's only updated on change.
+    #[serde(skip)]
+    window_title: String,
+    /// Problems with the next batch, refreshed every frame when stale.
+    #[serde(skip)]
+    batch_check: batch_check::BatchCheck,
+    /// A file row to scroll into view on the next frame.
+    #[serde(skip)]
+    jump_to_row: Option<usize>,
+    #[serde(skip)]
+    new_allowed_tag: String,
+    #[serde(skip)]
+    new_allowed_attribute: (String, String),
 }
 
 impl AppState {
-    fn convert_all(&mut self) {
+    fn convert_all(&mut self, only_pending: bool) {
+        self.convert_batch(only_pending, None);
+    }
+    /// Converts every included entry, or only those in `only`. With
+    /// `only_pending`, finished entries are skipped and an interrupted
+    /// batch's dated subfolder is reused.
+    fn convert_batch(&mut self, only_pending: bool, only: Option<&[usize]>) {
+        if self.batch_running() {
+            self.status_message = Message::info("⏳", "A batch is already running.");
+            return;
+        }
         if self.input_files.len() != self.output_files.len() {
-            self.status_message = "❌ Input/output file count mismatch.".to_string();
+            self.status_message = Message::error("Input/output file count mismatch.");
+            return;
+        }
+        self.refresh_file_info();
+        let folder = if !self.dated_subfolder {
+            None
+        } else if let (true, Some(folder)) = (only_pending, self.batch_folder.clone()) {
+            Some(folder)
+        } else {
+            match dates::folder_stamp(&self.dated_subfolder_pattern, &chrono::Local::now()) {
+                Ok(folder) => Some(folder),
+                Err(e) => {
+                    self.status_message = Message::error(format!("Dated subfolder: {}", e));
+                    return;
+                }
+            }
+        };
+        self.batch_folder = folder.clone();
+        self.batch_in_progress = true;
+        self.resume_prompt = false;
+        self.progress = 0.0;
+        self.log.clear();
+        let skipped_drafts = self.entries.iter().filter(|e| e.enabled && e.draft).count();
+        if skipped_drafts > 0 && !self.include_drafts {
+            self.log.push(Message::info("📝", format!("Skipping {} draft(s)", skipped_drafts)));
+        }
+        let total = self.input_files.len();
+        let candidates: Vec<usize> = (0..total)
+            .filter(|&i| self.entries[i].included(self.include_drafts) && !(only_pending && self.entries[i].status == EntryStatus::Done))
+            .filter(|i| only.is_none_or(|only| only.contains(i)))
+            .collect();
+        let jobs: Vec<(usize, PathBuf, PathBuf)> = candidates
+            .into_iter()
+            .map(|i| {
+                let output = match &folder {
+                    Some(folder) => dated_output(&self.output_files[i], folder),
+                    None => self.output_files[i].clone(),
+                };
+                (i, self.input_files[i].clone(), output)
+            })
+            .collect();
+        // Checked before anything is written, since the later entry would
+        // silently replace the earlier one's page.
+        let job_outputs: Vec<PathBuf> = jobs.iter().map(|(_, _, output)| output.clone()).collect();
+        let collisions = rename::duplicate_indices(&job_outputs);
+        if !collisions.is_empty() {
+            for &j in &collisions {
+                let (_, input, output) = &jobs[j];
+                self.log.push(Message::error(format!("{} → {} is written by another entry too", input.display(), output.display())));
+            }
+            self.status_message = Message::error(format!(
+                "{} entries share output paths; nothing was written. Rename them, or add -2, -3… suffixes from the batch check.",
+                collisions.len()
+            ));
+            self.log.push(self.status_message.clone());
+            self.batch_in_progress = false;
+            return;
+        }
+        if self.bundles_css() && !jobs.is_empty() {
+            let outputs: Vec<&Path> = jobs.iter().map(|(_, _, output)| output.as_path()).collect();
+            if let Err(message) = self.write_css_bundles(&outputs) {
+                self.log.push(message.clone());
+                self.status_message = message;
+                self.batch_in_progress = false;
+                return;
+            }
+        }
+        let settings = match self.settings_snapshot() {
+            Ok(settings) => settings,
+            Err(e) => {
+                self.status_message = Message::error(format!("Failed to copy the settings for the batch: {}", e));
+                self.batch_in_progress = false;
+                return;
+            }
+        };
+        let jobs: Vec<Job> = jobs
+            .into_iter()
+            .map(|(index, input, output)| Job {
+                index,
+                input,
+                output,
+                range: self.range_override.or(self.entries[index].line_range),
+                template: self.entries[index].template.clone(),
+            })
+            .collect();
+        self.status_message = Message::info("⏳", format!("Converting {} file(s)…", jobs.len()));
+        // Both limits are read per batch, so changes apply to the next one.
+        self.batch = Some(batch_runner::Runner::start(
+            settings,
+            jobs,
+            folder.is_some(),
+            limits::resolve_parallel(self.max_parallel),
+            self.io_worker_count(),
+        ));
+        if self.headless {
+            while self.poll_batch() {
+                thread::sleep(Duration::from_millis(20));
+            }
+        }
+    }
+    /// Takes in the files the running batch has finished since the last call,
+    /// and wraps the batch up once it's over. Returns whether it still runs.
+    fn poll_batch(&mut self) -> bool {
+        let Some(runner) = &self.batch else {
+            return false;
+        };
+        let (groups, over) = runner.collect();
+        for group in groups {
+            if !self.take_finished_group(group) {
+                self.batch = None;
+                self.batch_in_progress = false;
+                self.save_state();
+                return false;
+            }
+            // Persist after every file (or parallel group) so an interrupted batch can be resumed.
+            self.save_state();
+        }
+        if !over {
+            return true;
+        }
+        let Some(runner) = self.batch.take() else {
+            return false;
+        };
+        self.batch_in_progress = false;
+        let converted = runner.converted;
+        if converted.len() > 1 {
+            let bytes: u64 = converted.iter().filter_map(|&i| self.entries[i].output_size).sum();
+            self.status_message = Message::success(format!("Converted {} files, {} written", converted.len(), format_bytes(bytes)));
+            self.log.push(self.status_message.clone());
+            let timed: Vec<(&Path, PhaseTimings)> = converted
+                .iter()
+                .filter_map(|&i| Some((self.input_files[i].as_path(), self.entries[i].timings?)))
+                .collect();
+            let report = timing::batch_report(&timed);
+            self.log.extend(report);
+        }
+        if runner.skipped_empty > 0 {
+            let note = format!("{} empty file(s) skipped", runner.skipped_empty);
+            if converted.is_empty() {
+                self.status_message = Message::warning(note.clone());
+            } else {
+                self.status_message.text = format!("{}; {}", self.status_message.text, note);
+            }
+            self.log.push(Message::warning(note));
+        }
+        self.finish_batch();
+        self.save_state();
+        false
+    }
+    /// Records one finished group of the running batch on its entries.
+    /// Returns false when a file failed, which ends the batch.
+    fn take_finished_group(&mut self, group: batch_runner::Group) -> bool {
+        let mut failed = None;
+        let mut records = Vec::new();
+        let mut to_open = Vec::new();
+        for (job, (mut log, result)) in group {
+            let Job { input, output, .. } = job;
+            self.log.append(&mut log);
+            self.watcher.settle(&input);
+            let status = match &result {
+                Ok(Converted { skipped_empty: true, .. }) => "skipped",
+                Ok(_) => "ok",
+                Err(message) if timeout::is_timeout(message) => "timeout",
+                Err(_) => "failed",
+            };
+            let bytes = result.as_ref().map_or(0, |converted| converted.bytes_written);
+            records.push(audit::Record { input: input.clone(), output: output.clone(), status, bytes });
+            // The list may have been edited while the batch ran.
+            let i = match self.input_files.get(job.index) {
+                Some(file) if *file == input => Some(job.index),
+                _ => self.input_files.iter().position(|file| *file == input),
+            };
+            match (result, i) {
+                (Ok(Converted { skipped_empty: true, .. }), _) => {
+                    if let Some(runner) = &mut self.batch {
+                        runner.skipped_empty += 1;
+                    }
+                }
+                (Ok(Converted { timings, split_pages, title, bytes_written, warnings, .. }), Some(i)) => {
+                    self.entries[i].status = EntryStatus::Done;
+                    self.entries[i].timings = Some(timings);
+                    self.entries[i].split_pages = split_pages;
+                    self.entries[i].last_output = Some(output.clone());
+                    self.entries[i].output_size = Some(bytes_written);
+                    self.entries[i].title = Some(title);
+                    self.entries[i].warnings = warnings;
+                    self.entries[i].converted_at = Some(chrono::Local::now().to_rfc3339());
+                    self.entries[i].input_size = fs::metadata(&input).map(|m| m.len()).ok();
+                    if let Some(runner) = &mut self.batch {
+                        runner.converted.push(i);
+                    }
+                    to_open.push(output.clone());
+                    self.status_message = Message::success(format!(
+                        "Converted: {} → {} ({})",
+                        input.display(),
+                        output.display(),
+                        timing::format_duration(timings.total())
+                    ));
+                    self.log.push(self.status_message.clone());
+                    for page in &self.entries[i].split_pages {
+                        self.log.push(Message::info("📄", format!("Split page: {}", page.display())));
+                    }
+                }
+                (Ok(_), None) => {
+                    to_open.push(output.clone());
+                    self.log.push(Message::success(format!("Converted: {} → {} (no longer listed)", input.display(), output.display())));
+                }
+                (Err(message), i) => {
+                    if let Some(i) = i {
+                        self.entries[i].status = EntryStatus::Failed;
+                    }
+                    self.log.push(message.clone());
+                    if timeout::is_timeout(&message) {
+                        self.status_message = message;
+                    } else {
+                        failed.get_or_insert(message);
+                    }
+                }
+            }
+            if let Some(runner) = &mut self.batch {
+                runner.completed += 1;
+                self.progress = runner.completed as f32 / runner.total as f32;
+            }
+        }
+        if let Some(log_file) = &self.log_file {
+            let max_bytes = match self.log_file_max_kb {
+                0 => 1024,
+                kb => kb,
+            } * 1024;
+            if let Err(e) = audit::append(log_file, &records, max_bytes) {
+                self.log.push(Message::warning(format!("Log file: {}", e)));
+            }
+        }
+        if self.preview && !to_open.is_empty() {
+            self.browser_open.start(to_open, self.browser_open_attempts);
+        }
+        match failed {
+            Some(message) => {
+                self.status_message = message;
+                false
+            }
+            None => true,
+        }
+    }
+    fn io_worker_count(&self) -> usize {
+        match self.max_parallel_io {
+            0 => 4,
+            n => n,
+        }
+    }
+    /// Converts one batch entry, creating its dated subfolder first if needed.
+    fn convert_job(&self, job: &Job, in_folder: bool, io_slots: &Slots, cancel: &Cancel) -> batch_runner::Outcome {
+        let mut log = Vec::new();
+        let (input, output) = (&job.input, &job.output);
+        let entry = EntryOptions { range: job.range, template: job.template.as_deref() };
+        let result = match output.parent().filter(|p| !p.as_os_str().is_empty()) {
+            Some(dir) if in_folder => fs::create_dir_all(dir)
+                .map_err(|e| Message::error(format!("Failed to create {}: {}", dir.display(), e)))
+                .and_then(|_| self.convert_file(input, Sink::File(output), entry, io_slots, cancel, &mut log)),
+            _ => self.convert_file(input, Sink::File(output), entry, io_slots, cancel, &mut log),
+        };
+        (log, result)
+    }
+    fn progress_bar_min_words(&self) -> usize {
+        match self.progress_bar_min_words {
+            0 => 1000,
+            words => words,
+        }
+    }
+    fn back_to_top_min_words(&self) -> usize {
+        match self.back_to_top_min_words {
+            0 => 1500,
+            words => words,
+        }
+    }
+    fn large_input_threshold(&self) -> u64 {
+        let mb = match self.large_input_threshold_mb {
+            0 => 10,
+            mb => mb,
+        };
+        mb * 1024 * 1024
+    }
+    /// Starts a batch from the UI, first asking about unusually large inputs.
+    fn request_convert(&mut self) {
+        self.request_batch(None);
+    }
+    /// Checks the list and confirms large inputs like the Convert button,
+    /// then converts the entries in `only`, or every entry.
+    fn request_batch(&mut self, only: Option<Vec<usize>>) {
+        self.refresh_file_info();
+        self.refresh_batch_check(true);
+        if !self.batch_check.runnable() {
+            self.status_message = Message::error("Fix the problems listed above Convert first.");
+            return;
+        }
+        let threshold = self.large_input_threshold();
+        let large: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| self.entries[i].included(self.include_drafts) && self.entries[i].input_size.is_some_and(|size| size > threshold))
+            .filter(|i| only.as_ref().is_none_or(|only| only.contains(i)))
+            .collect();
+        if large.is_empty() {
+            self.convert_batch(false, only.as_deref());
+            self.save_state();
+        } else {
+            self.large_input_confirm = Some((large, only));
+        }
+    }
+    /// Re-reads each input's size and draft flag.
+    fn refresh_file_info(&mut self) {
+        self.sync_entries();
+        for (entry, input) in self.entries.iter_mut().zip(&self.input_files) {
+            entry.input_size = fs::metadata(input).map(|m| m.len()).ok();
+            entry.draft = frontmatter::file_is_draft(input);
+        }
+    }
+    fn large_input_dialog(&mut self, ctx: &egui::Context) {
+        let Some((large, only)) = self.large_input_confirm.clone() else {
+            return;
+        };
+        let mut choice = None;
+        egui::Window::new("⚠ Large input files")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "These files are larger than {} and may take a while to convert:",
+                    format_bytes(self.large_input_threshold())
+                ));
+                for &i in &large {
+                    ui.monospace(format!(
+                        "📄 {} ({})",
+                        self.input_files[i].display(),
+                        format_bytes(self.entries[i].input_size.unwrap_or_default())
+                    ));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("🚀 Convert anyway").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("🚫 Exclude them and convert").clicked() {
+                        choice = Some(false);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.large_input_confirm = None;
+                    }
+                });
+            });
+        if let Some(include_large) = choice {
+            self.large_input_confirm = None;
+            if !include_large {
+                for &i in &large {
+                    self.entries[i].enabled = false;
+                }
+            }
+            self.convert_batch(false, only.as_deref());
+            self.save_state();
+        }
+    }
+    /// Converts one file, or only `entry.range` of its lines, into `sink`,
+    /// appending notes about it to `log`. `entry.template` overrides any
+    /// other template.
+    ///
+    /// I/O-bound sub-tasks wait for one of `io_slots` so parallel files don't
+    /// flood the disk or a diagram server, and everything stops early once
+    /// `cancel` is set.
+    fn convert_file(
+        &self,
+        input: &Path,
+        mut sink: Sink,
+        entry: EntryOptions,
+        io_slots: &Slots,
+        cancel: &Cancel,
+        log: &mut Vec<Message>,
+    ) -> Result<Converted, Message> {
+        let mut timings = PhaseTimings::default();
+        let mut split_pages = Vec::new();
+        let mut clock = Instant::now();
+        let first_log_line = log.len();
+        let EntryOptions { range, template: entry_template } = entry;
+        // The hook may write files, which in-memory renders must not.
+        if matches!(sink, Sink::File(_)) && !self.pre_process_command.trim().is_empty() {
+            hooks::run_pre_process(&self.pre_process_command, input, cancel, log)?;
+            cancel.check()?;
+        }
+        let source = fs::read_to_string(input).map_err(|e| Message::error(format!("Failed to read {}: {}", input.display(), e)))?;
+        if self.lint || self.lint_strict {
+            let warnings = lint::lint_source(&source, &self.lint_disabled_rules);
+            log.extend(warnings.iter().map(|w| Message::warning(format!("{}: {}", input.display(), w))));
+            if self.lint_strict && !warnings.is_empty() {
+                return Err(Message::error(format!(
+                    "{}: {} lint warning(s) with --strict-lint",
+                    input.display(),
+                    warnings.len()
+                )));
+            }
+        }
+        let source = match range {
+            Some(range) => {
+                let (excerpt, first, last) = range.slice(&source);
+                if excerpt.is_empty() {
+                    log.push(Message::info("✂", format!("{}: lines {} are past the end of the file", input.display(), range)));
+                } else {
+                    log.push(Message::info("✂", format!("{}: lines {}–{}", input.display(), first, last)));
+                }
+                excerpt
+            }
+            None => &source,
+        };
+        let source = replace::apply_replacements(source, &self.replacements)
+            .map_err(|e| Message::error(format!("{}: {}", input.display(), e)))?;
+        let (front_matter, md, warning) = frontmatter::split_front_matter(&source);
+        if let Some(warning) = warning {
+            log.push(Message::warning(format!("{}: {}", input.display(), warning)));
+        }
+        if let Some(fm) = &front_matter {
+            log.push(Message::info("📋", format!("{}: {} front matter", input.display(), fm.format.label())));
+        }
+        // Checked on what the hook, range and replacements left, since any of
+        // them can fill or empty the page.
+        if matches!(sink, Sink::File(_)) && self.on_empty_input != EmptyInput::Convert && empty_input::is_empty(md) {
+            let skip = self.on_empty_input == EmptyInput::Skip;
+            let verb = if skip { "skipped" } else { "converting anyway" };
+            log.push(Message::warning(format!("Empty input, {}: {}", verb, input.display())));
+            if skip {
+                let title = self.page_title(input, front_matter.as_ref());
+                return Ok(Converted { timings, split_pages, title, bytes_written: 0, warnings: 1, skipped_empty: true });
+            }
+        }
+        let front_matter_comment = match (&front_matter, self.front_matter_comment) {
+            (Some(_), true) => frontmatter::as_html_comment(&source, md),
+            _ => String::new(),
+        };
+        let md = if self.wiki_links { wiki::convert_wiki_links(md) } else { Cow::Borrowed(md) };
+        let md = md.as_ref();
+        let md = if self.sub_superscript { sub_sup::convert_sub_superscript(md) } else { Cow::Borrowed(md) };
+        let md = md.as_ref();
+        let scratch = matches!(sink, Sink::Scratch(_));
+        if (self.check_links || self.check_remote_links) && !scratch {
+            log.extend(links::check_links(md, input, self.check_remote_links, io_slots, cancel));
+            cancel.check()?;
+        }
+        // The entry's own template, then front matter's `template:` relative
+        // to the file, then the global one.
+        let template_path = entry_template.map(Path::to_path_buf).or_else(|| {
+            let relative = front_matter.as_ref().and_then(|fm| fm.get_str("template"))?;
+            Some(input.parent().unwrap_or(Path::new("")).join(relative))
+        });
+        let template = match (template_path.as_ref().or(self.template_path.as_ref()), self.fragment_output) {
+            (Some(path), false) => Some(fs::read_to_string(path).map_err(|e| {
+                Message::error(format!("{}: failed to read template {}: {}", input.display(), path.display(), e))
+            })?),
+            _ => None,
+        };
+        clock = timings.lap(Phase::Read, clock);
+        let mut options = self.extensions.0;
+        options.set(Options::ENABLE_FOOTNOTES, false);
+        if self.footnote_mode != FootnoteMode::Off {
+            options.insert(Options::ENABLE_FOOTNOTES);
+        }
+        if self.sortable_tables != SortableTables::Off {
+            options.insert(Options::ENABLE_TABLES);
+        }
+        let url_prefix = urls::normalize_prefix(&self.url_prefix);
+        let output_dir = match &sink {
+            Sink::File(output) => output.parent().unwrap_or(Path::new("")).to_path_buf(),
+            Sink::Memory(_) | Sink::Scratch(_) => input.parent().unwrap_or(Path::new("")).to_path_buf(),
+        };
+        let asset_paths = assets::PathRewriter::new(self.asset_paths, input, &output_dir, &self.asset_base_url);
+        let mut highlighter = highlight::Highlighter::new(&self.highlight_terms);
+        let highlighting = highlighter.is_active();
+        // Other pages are assumed to be written with this page's extension.
+        let page_extension = match &sink {
+            Sink::File(output) => output.extension().map_or_else(|| "html".to_string(), |e| e.to_string_lossy().into_owned()),
+            Sink::Memory(_) | Sink::Scratch(_) => "html".to_string(),
+        };
+        let md_links = if self.rewrite_md_links { Some(page_extension.as_str()) } else { None };
+        // These only rewrite an image's `Start` event. Its `End` keeps the path
+        // as written, which SVG inlining resolves against the input's folder.
+        let parser = autolink::autolink(Parser::new_ext(md, options), self.autolink, &self.reference_repo)
+            .map(|event| urls::md_link_event(event, md_links))
+            .map(|event| assets::rewrite_image_prefix(event, self.image_src_rewrite.as_ref()))
+            .map(|event| assets::rewrite_event(event, asset_paths.as_ref()))
+            .map(|event| urls::prefix_event(event, &url_prefix))
+            .map(|event| lists::renumber_event(event, self.renumber_lists));
+        let use_sidebar = self.layout == OutputLayout::SidebarToc && !self.fragment_output && template.is_none();
+        let mut generated_css = String::new();
+        if highlighting {
+            generated_css += highlight::MARK_CSS;
+        }
+        let mut generated_js = String::new();
+        if self.code_language_badges {
+            generated_css += code_blocks::BADGE_CSS;
+        }
+        if self.code_tabs && code_blocks::has_code_tabs(md) {
+            generated_css += code_blocks::TABS_CSS;
+        }
+        if self.split_by_heading.is_some() {
+            generated_css += split::PAGER_CSS;
+        }
+        if let (Some(css), true) = (self.footnote_mode.css(), footnotes::has_footnotes(md)) {
+            generated_css += css;
+        }
+        if code_blocks::has_diff_block(md) {
+            generated_css += code_blocks::DIFF_CSS;
+        }
+        if (self.figures_from_titles || self.images_as_figures) && figures::has_images(md) {
+            generated_css += figures::FIGURE_CSS;
+        }
+        if self.sortable_tables != SortableTables::Off && tables::may_have_tables(md) {
+            generated_css += tables::SORTABLE_CSS;
+            generated_js += tables::SORTABLE_JS;
+        }
+        clock = timings.lap(Phase::Render, clock);
+        if self.embed_fonts && !self.fragment_output {
+            match &self.font_path {
+                Some(font) => match fonts::embedded_font_css(&self.font_family, font) {
+                    Ok(css) => generated_css += &css,
+                    Err(e) => log.push(Message::warning(e)),
+                },
+                None => log.push(Message::warning("Font embedding is on but no font file is selected.")),
+            }
+        }
+        if self.heading_ids || use_sidebar {
+            if let Some(css) = layout::scroll_margin_css(&self.scroll_margin_top) {
+                generated_css += &css;
+            }
+        }
+        if use_sidebar {
+            generated_css += layout::SIDEBAR_CSS;
+            generated_js += layout::SCROLLSPY_JS;
+        }
+        if let (None, Some(css)) = (&template, self.max_width_css()) {
+            generated_css += &css;
+        }
+        if let Some(level) = self.page_break_level {
+            generated_css += &layout::page_break_css(level);
+        }
+        let words = md.split_whitespace().count();
+        let long_page_feature = |key: &str, enabled: bool, min_words: usize| {
+            !self.fragment_output
+                && template.is_none()
+                && match front_matter.as_ref().and_then(|fm| fm.get_bool(key)) {
+                    Some(on) => on,
+                    None => enabled && words >= min_words,
+                }
+        };
+        let show_progress = long_page_feature("progress_bar", self.progress_bar, self.progress_bar_min_words());
+        if show_progress {
+            generated_css += layout::PROGRESS_CSS;
+            generated_js += layout::PROGRESS_JS;
+        }
+        let show_back_to_top = long_page_feature("back_to_top", self.back_to_top, self.back_to_top_min_words());
+        if show_back_to_top {
+            generated_css += layout::BACK_TO_TOP_CSS;
+            generated_js += layout::BACK_TO_TOP_JS;
+        }
+        let lastmod = if self.emit_lastmod {
+            dates::document_date(front_matter.as_ref(), input)
+        } else {
+            None
+        };
+        let title = self.page_title(input, front_matter.as_ref());
+        let mut head_elements = if self.fragment_output {
+            String::new()
+        } else {
+            let document_css = self.document_css(front_matter.as_ref(), input, log);
+            self.head_elements(front_matter.as_ref(), lastmod, &generated_css, document_css.as_deref())
+        };
+        if !self.fragment_output && !self.json_ld_type.trim().is_empty() {
+            let (script, warnings) =
+                json_ld::script(&self.json_ld_type, &title, front_matter.as_ref(), &self.json_ld_author, input);
+            head_elements += &script;
+            log.extend(warnings.into_iter().map(|w| Message::warning(format!("{}: {}", input.display(), w))));
+        }
+        let head = if self.fragment_output || template.is_some() {
+            String::new()
+        } else {
+            document_head(&title, &head_elements)
+        };
+        clock = timings.lap(Phase::Assets, clock);
+        let mut updated_line = String::new();
+        if let (Some(date), true) = (lastmod, self.show_updated_line) {
+            updated_line = format!(
+                "<p class=\"last-updated\">Updated on <time datetime=\"{}\">{}</time></p>\n",
+                dates::iso(&date),
+                date.format("%Y-%m-%d")
+            );
+        }
+        // The sidebar layout has its own `<main>`, and a template or the page
+        // a fragment goes into decides its own landmarks.
+        let main_landmark = self.aria_landmarks && !use_sidebar && !self.fragment_output && template.is_none();
+        let (wrap_open, wrap_close) = self.body_wrapper(main_landmark);
+        let mut foot = String::new();
+        if !self.fragment_output && template.is_none() {
+            if show_progress {
+                foot += layout::PROGRESS_HTML;
+            }
+            if show_back_to_top {
+                foot += layout::BACK_TO_TOP_HTML;
+            }
+            if !generated_js.is_empty() {
+                foot += &format!("<script>{}</script>", generated_js);
+            }
+            foot += "</body></html>";
+        }
+
+        if !self.needs_buffered_body() && template.is_none() {
+            // Nothing needs the whole body, so render straight into the file.
+            cancel.check()?;
+            write_to(&mut sink, |writer| {
+                writer.write_all(head.as_bytes())?;
+                writer.write_all(front_matter_comment.as_bytes())?;
+                writer.write_all(wrap_open.as_bytes())?;
+                let events = parser.flat_map(|event| highlighter.apply(event));
+                html::write_html(&mut *writer, code_blocks::highlight_diff_blocks(events))?;
+                writer.write_all(updated_line.as_bytes())?;
+                writer.write_all(wrap_close.as_bytes())?;
+                writer.write_all(foot.as_bytes())
+            })?;
+            // Rendering and writing overlap here, so it all counts as rendering.
+            timings.lap(Phase::Render, clock);
+        } else {
+            let mut events: Vec<Event> = parser.collect();
+            clock = timings.lap(Phase::Render, clock);
+            heading_case::apply_heading_case(&mut events, self.heading_case);
+            let headings = if self.heading_ids
+                || use_sidebar
+                || self.split_by_heading.is_some()
+                || self.outline_json
+                || self.warn_duplicate_headings
+                || template.as_deref().is_some_and(template::wants_toc)
+            {
+                toc::assign_heading_ids(&mut events)
+            } else {
+                Vec::new()
+            };
+            if self.warn_duplicate_headings {
+                for (text, ids) in toc::duplicate_headings(&headings) {
+                    log.push(Message::warning(format!(
+                        "{}: heading \"{}\" appears {} times (ids {})",
+                        input.display(),
+                        text,
+                        ids.len(),
+                        ids.join(", ")
+                    )));
+                }
+            }
+            if self.code_tabs {
+                events = code_blocks::group_code_tabs(events);
+            }
+            if self.code_language_badges {
+                events = code_blocks::add_language_badges(events);
+            }
+            events = footnotes::render_sidenotes(events, self.footnote_mode);
+            if self.github_alerts {
+                events = alerts::render_alerts(events);
+            }
+            // After alerts, so a term such as "note" can't hide an alert's
+            // `[!NOTE]` marker, and after code handling, which wants plain text.
+            if highlighting {
+                events = events.into_iter().flat_map(|event| highlighter.apply(event)).collect();
+            }
+            events = diagrams::render_plantuml(
+                events,
+                &diagrams::PlantUmlSettings {
+                    renderer: if scratch { PlantUmlRenderer::Off } else { self.plantuml_renderer },
+                    jar: self.plantuml_jar.as_deref(),
+                    server: &self.plantuml_server,
+                    timeout: Duration::from_secs(match self.diagram_timeout_secs {
+                        0 => 10,
+                        secs => secs,
+                    }),
+                    io_slots,
+                    cancel,
+                },
+                log,
+            );
+            cancel.check()?;
+            if self.figures_from_titles || self.images_as_figures {
+                let captions = figures::Captions { titles: self.figures_from_titles, alt: self.images_as_figures };
+                events = figures::wrap_figures(events, captions, self.number_figures);
+            }
+            // The sanitizer would drop inlined SVGs altogether; as `<img>`s
+            // their scripts never run anyway.
+            if self.inline_svg && !self.sanitize {
+                let max_kb = match self.inline_svg_max_kb {
+                    0 => 50,
+                    kb => kb,
+                };
+                let base_dir = input.parent().unwrap_or(Path::new(""));
+                events = inline_svg::inline_svg_images(events, base_dir, max_kb * 1024, log);
+            }
+            let pages = match (self.split_by_heading, &sink) {
+                (Some(level), Sink::File(output)) => split::plan_pages(&events, &headings, level.into(), output),
+                _ => Vec::new(),
+            };
+            let toc_html = if pages.len() > 1 {
+                split::link_across_pages(&mut events, &pages);
+                toc::build_toc_with(&headings, |heading| split::heading_href(&pages, &heading.id))
+            } else {
+                toc::build_toc(&headings)
+            };
+            if let (true, Sink::File(output)) = (self.outline_json, &sink) {
+                let file_name = output.file_name().unwrap_or_default().to_string_lossy();
+                let href = |heading: &toc::Heading| match pages.len() {
+                    0 | 1 => format!("{}#{}", file_name, heading.id),
+                    _ => split::heading_href(&pages, &heading.id),
+                };
+                match outline::write_outline(output, &headings, href) {
+                    Ok(path) => log.push(Message::info("🧭", format!("{}: outline written to {}", input.display(), path.display()))),
+                    Err(e) => log.push(Message::warning(format!("{}: failed to write outline: {}", input.display(), e))),
+                }
+            }
+            clock = timings.lap(Phase::PostProcess, clock);
+            // Everything that works on a page's finished HTML.
+            let finish_page = |mut html_body: String, page_title: &str| {
+                if self.sanitize {
+                    html_body = sanitize::sanitize_html(
+                        &html_body,
+                        &self.sanitize_allowed_tags,
+                        &self.sanitize_allowed_attributes,
+                    );
+                }
+                let rules: Vec<inject::ElementRule> =
+                    self.element_rules.iter().cloned().chain(self.sortable_tables.rule()).collect();
+                if !rules.is_empty() {
+                    html_body = inject::inject_attributes(&html_body, &rules);
+                }
+                html_body += &updated_line;
+                if !wrap_open.is_empty() {
+                    html_body = format!("{}{}{}", wrap_open, html_body, wrap_close);
+                }
+                if use_sidebar {
+                    html_body = layout::wrap_sidebar(&html_body, &toc_html, self.aria_landmarks);
+                }
+                html_body.insert_str(0, &front_matter_comment);
+                if let Some(template) = &template {
+                    html_body = template::render(
+                        template,
+                        &template::TemplateValues {
+                            title: &toc::escape_text(page_title),
+                            css: &head_elements,
+                            body: &html_body,
+                            toc: &toc_html,
+                        },
+                    );
+                }
+                if self.collapse_blank_lines {
+                    html_body = whitespace::collapse_blank_lines(&html_body);
+                }
+                html_body
+            };
+            if pages.len() > 1 {
+                for (p, page) in pages.iter().enumerate() {
+                    let mut html_body = String::new();
+                    let page_events = events[page.events.clone()].iter().cloned();
+                    html::push_html(&mut html_body, code_blocks::highlight_diff_blocks(page_events));
+                    html_body += &split::pager(&pages, p);
+                    let page_title = match &page.title {
+                        Some(section) if p > 0 => format!("{} – {}", title, section),
+                        _ => title.clone(),
+                    };
+                    let html_body = finish_page(html_body, &page_title);
+                    let page_head = if head.is_empty() { String::new() } else { document_head(&page_title, &head_elements) };
+                    cancel.check()?;
+                    write_output(&page.output, |writer| {
+                        writer.write_all(page_head.as_bytes())?;
+                        writer.write_all(html_body.as_bytes())?;
+                        writer.write_all(foot.as_bytes())
+                    })?;
+                    if p > 0 {
+                        split_pages.push(page.output.clone());
+                    }
+                }
+                log.push(Message::info("📑", format!("{}: split into {} pages", input.display(), pages.len())));
+                timings.lap(Phase::Write, clock);
+            } else {
+                let mut html_body = String::new();
+                html::push_html(&mut html_body, code_blocks::highlight_diff_blocks(events.into_iter()));
+                clock = timings.lap(Phase::Render, clock);
+                let html_body = finish_page(html_body, &title);
+                clock = timings.lap(Phase::PostProcess, clock);
+                cancel.check()?;
+                write_to(&mut sink, |writer| {
+                    writer.write_all(head.as_bytes())?;
+                    writer.write_all(html_body.as_bytes())?;
+                    writer.write_all(foot.as_bytes())
+                })?;
+                timings.lap(Phase::Write, clock);
+            }
+        }
+        let bytes_written = match &sink {
+            Sink::File(output) => std::iter::once(*output)
+                .chain(split_pages.iter().map(PathBuf::as_path))
+                .filter_map(|page| fs::metadata(page).map(|m| m.len()).ok())
+                .sum(),
+            Sink::Memory(buffer) | Sink::Scratch(buffer) => buffer.len() as u64,
+        };
+        if let Sink::File(output) = sink {
+            if self.extract_tables_csv {
+                log.extend(tables::write_tables_csv(md, options, input, output));
+            }
+            if self.copy_assets {
+                let clock = Instant::now();
+                log.extend(assets::copy_assets(md, options, input, output));
+                timings.lap(Phase::Assets, clock);
+            }
+            if self.validate_html {
+                let clock = Instant::now();
+                for page in std::iter::once(output).chain(split_pages.iter().map(PathBuf::as_path)) {
+                    log.extend(validate::validate_file(page, &self.validator_command));
+                }
+                timings.lap(Phase::PostProcess, clock);
+            }
+        }
+        let warnings = log[first_log_line..].iter().filter(|message| message.is_warning()).count();
+        Ok(Converted { timings, split_pages, title, bytes_written, warnings, skipped_empty: false })
+    }
+
+    /// Opening and closing tags around the body, or empty strings for none.
+    ///
+    /// The wrapper always carries `md2html-wrapper` so the bundled styles can
+    /// constrain it rather than `<body>`. An invalid tag name means no wrapper.
+    /// With `main_landmark`, a `<main>` goes around it unless the wrapper
+    /// already is one.
+    fn body_wrapper(&self, main_landmark: bool) -> (String, String) {
+        let tag = match self.valid_wrapper_tag() {
+            Some(tag) if !self.fragment_output || self.fragment_wrapper => tag,
+            _ => {
+                return if main_landmark {
+                    ("<main>\n".to_string(), "</main>\n".to_string())
+                } else {
+                    (String::new(), String::new())
+                };
+            }
+        };
+        let class = format!("md2html-wrapper {}", self.wrapper_class.trim());
+        let mut open = format!("<{} class=\"{}\"", tag, toc::escape_attr(class.trim()));
+        if !self.wrapper_id.trim().is_empty() {
+            open += &format!(" id=\"{}\"", toc::escape_attr(self.wrapper_id.trim()));
+        }
+        open += ">\n";
+        let mut close = format!("</{}>\n", tag);
+        if main_landmark && !tag.eq_ignore_ascii_case("main") {
+            open.insert_str(0, "<main>\n");
+            close += "</main>\n";
+        }
+        (open, close)
+    }
+
+    /// The wrapper element's tag, unless it's empty or not a valid name.
+    fn valid_wrapper_tag(&self) -> Option<&str> {
+        let tag = self.wrapper_tag.trim();
+        let valid = tag.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+            && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        valid.then_some(tag)
+    }
+
+    /// Whether the body has to be rendered into memory before writing.
+    ///
+    /// Sanitizing, element rules, the sidebar layout, templates and collapsing
+    /// blank lines work on the finished HTML, splitting by heading needs the
+    /// whole event list, and heading ids and case,
+    /// language badges, sidenotes, diagrams and inlined SVGs rewrite the
+    /// event list in place, so any of them forces the buffered path.
+    /// Otherwise the body is streamed.
+    fn needs_buffered_body(&self) -> bool {
+        self.sanitize
+            || self.heading_ids
+            || self.heading_case != HeadingCase::None
+            || (self.layout == OutputLayout::SidebarToc && !self.fragment_output)
+            || (self.template_path.is_some() && !self.fragment_output)
+            || self.code_language_badges
+            || self.code_tabs
+            || self.footnote_mode.inline_notes()
+            || self.github_alerts
+            || self.plantuml_renderer != PlantUmlRenderer::Off
+            || self.figures_from_titles
+            || self.images_as_figures
+            || self.inline_svg
+            || !self.element_rules.is_empty()
+            || self.sortable_tables == SortableTables::All
+            || self.collapse_blank_lines
+            || self.split_by_heading.is_some()
+            || self.outline_json
+            || self.warn_duplicate_headings
+    }
+
+    fn page_title(&self, input: &Path, front_matter: Option<&frontmatter::FrontMatter>) -> String {
+        if !self.title.is_empty() {
+            self.title.clone()
+        } else if let Some(title) = front_matter.and_then(|fm| fm.title()) {
+            title.to_string()
+        } else {
+            self.filename_title.title(input)
+        }
+    }
+
+    /// Meta tags and styles that follow the `<title>`.
+    fn head_elements(
+        &self,
+        front_matter: Option<&frontmatter::FrontMatter>,
+        lastmod: Option<chrono::DateTime<chrono::FixedOffset>>,
+        generated_css: &str,
+        document_css: Option<&Path>,
+    ) -> String {
+        let mut head = String::new();
+        if let Some(date) = lastmod {
+            head += &format!("<meta name=\"last-modified\" content=\"{}\">", dates::iso(&date));
+        }
+        if let Some(fm) = front_matter {
+            if let Some(author) = fm.author() {
+                head += &format!("<meta name=\"author\" content=\"{}\">", toc::escape_attr(author));
+            }
+            if let Some(description) = fm.description() {
+                head += &format!(
+                    "<meta name=\"description\" content=\"{}\">",
+                    toc::escape_attr(description)
+                );
+            }
+            let tags = fm.tags();
+            if !tags.is_empty() {
+                head += &format!(
+                    "<meta name=\"keywords\" content=\"{}\">",
+                    toc::escape_attr(&tags.join(", "))
+                );
+            }
+        }
+        let style = |css: &str| -> String {
+            if self.minify_css {
+                css_minify::minify_css(css)
+            } else {
+                css.to_string()
+            }
+        };
+        if self.bundles_css() {
+            head += &format!("<link rel=\"stylesheet\" href=\"{}\">", CSS_BUNDLE_NAME);
+            // The bundle is shared, so a document's own stylesheet goes on top of it.
+            if let Some(css) = document_css.and_then(|path| fs::read_to_string(path).ok()) {
+                head += &format!("<style>{}</style>", style(&css));
+            }
+            return head;
+        }
+        if !generated_css.is_empty() {
+            head += &format!("<style>{}</style>", style(generated_css));
+        }
+        if self.reader_mode {
+            head += &format!("<style>{}</style>", style(READER_CSS));
+        }
+        let user_css = document_css
+            .or(self.css_path.as_deref())
+            .filter(|_| !(self.reader_mode && self.reader_replaces_css));
+        if let Some(css_path) = user_css {
+            match fs::read_to_string(css_path) {
+                Ok(css) if self.minify_css => {
+                    head += &format!("<style>{}</style>", style(&css));
+                }
+                Ok(css) => {
+                    head += &format!("<style>\n{}\n</style>", css);
+                }
+                Err(_) => {
+                    head += &format!(
+                        "<link rel=\"stylesheet\" href=\"{}\">",
+                        css_path.display()
+                    );
+                }
+            }
+        }
+        let preset = self.alert_preset.css(self.github_alerts);
+        if !preset.is_empty() {
+            head += &format!("<style>{}</style>", style(preset));
+        }
+        head
+    }
+    /// The reading width rule, unless the user's stylesheet or the sidebar
+    /// layout decides how wide the text is.
+    fn max_width_css(&self) -> Option<String> {
+        let user_css = self.css_path.is_some() && !(self.reader_mode && self.reader_replaces_css);
+        if user_css || self.layout == OutputLayout::SidebarToc || self.fragment_output {
+            return None;
+        }
+        layout::max_width_css(self.max_content_width.as_deref()?, self.valid_wrapper_tag().is_some())
+    }
+    fn bundles_css(&self) -> bool {
+        self.bundle_css && !self.fragment_output
+    }
+    /// Every stylesheet a page of this batch could use, each once. Feature
+    /// styles only match their own classes, so pages without the feature
+    /// are unaffected.
+    fn css_bundle(&self) -> Result<String, String> {
+        let mut parts: Vec<Cow<str>> = vec![
+            code_blocks::BADGE_CSS.into(),
+            code_blocks::DIFF_CSS.into(),
+            code_blocks::TABS_CSS.into(),
+            layout::SIDEBAR_CSS.into(),
+            layout::PROGRESS_CSS.into(),
+            layout::BACK_TO_TOP_CSS.into(),
+            split::PAGER_CSS.into(),
+            tables::SORTABLE_CSS.into(),
+            figures::FIGURE_CSS.into(),
+        ];
+        if let Some(css) = layout::scroll_margin_css(&self.scroll_margin_top) {
+            parts.push(css.into());
+        }
+        if let Some(css) = self.max_width_css() {
+            parts.push(css.into());
+        }
+        if let Some(level) = self.page_break_level {
+            parts.push(layout::page_break_css(level).into());
+        }
+        // Sidenotes make room in the margin on every page, so only when asked for.
+        if let Some(css) = self.footnote_mode.css() {
+            parts.push(css.into());
+        }
+        // Styles every `<mark>`, including ones written by hand.
+        if highlight::Highlighter::new(&self.highlight_terms).is_active() {
+            parts.push(highlight::MARK_CSS.into());
+        }
+        if let (true, Some(font)) = (self.embed_fonts, &self.font_path) {
+            parts.push(fonts::embedded_font_css(&self.font_family, font)?.into());
+        }
+        if self.reader_mode {
+            parts.push(READER_CSS.into());
+        }
+        let user_css = self.css_path.as_ref().filter(|_| !(self.reader_mode && self.reader_replaces_css));
+        if let Some(css_path) = user_css {
+            let css = fs::read_to_string(css_path).map_err(|e| format!("{}: {}", css_path.display(), e))?;
+            parts.push(css.into());
+        }
+        parts.push(self.alert_preset.css(self.github_alerts).into());
+        let css = parts.iter().map(|part| part.trim()).filter(|part| !part.is_empty()).collect::<Vec<_>>().join("\n\n") + "\n";
+        Ok(if self.minify_css { css_minify::minify_css(&css) } else { css })
+    }
+    /// Writes the CSS bundle once into each folder the batch writes pages to.
+    fn write_css_bundles(&mut self, outputs: &[&Path]) -> Result<(), Message> {
+        let mut folders: Vec<&Path> = outputs.iter().map(|output| output.parent().unwrap_or(Path::new(""))).collect();
+        folders.sort();
+        folders.dedup();
+        let bundle = self.css_bundle().map_err(|e| Message::error(format!("CSS bundle: {}", e)))?;
+        for folder in folders {
+            let path = folder.join(CSS_BUNDLE_NAME);
+            if !folder.as_os_str().is_empty() {
+                fs::create_dir_all(folder).map_err(|e| Message::error(format!("Failed to create {}: {}", folder.display(), e)))?;
+            }
+            write_output(&path, |writer| writer.write_all(bundle.as_bytes()))?;
+            self.log.push(Message::info("🧩", format!("Wrote CSS bundle: {} ({})", path.display(), format_bytes(bundle.len() as u64))));
+        }
+        Ok(())
+    }
+    /// Batch-wide outputs written once every file has been converted.
+    fn finish_batch(&mut self) {
+        let pages: Vec<PathBuf> = self
+            .entries
+            .iter()
+            .zip(&self.output_files)
+            .filter(|(entry, _)| entry.status == EntryStatus::Done && !entry.draft)
+            .flat_map(|(entry, output)| {
+                let first = entry.last_output.clone().unwrap_or_else(|| output.clone());
+                std::iter::once(first).chain(entry.split_pages.iter().cloned())
+            })
+            .collect();
+        if let Some(sitemap) = self.generate_sitemap.clone() {
+            match sitemap::write_sitemap(&sitemap, &self.site_base_url, &pages) {
+                Ok(mut warnings) => {
+                    self.log.append(&mut warnings);
+                    self.log.push(Message::info("🗺", format!("Wrote sitemap with {} pages: {}", pages.len(), sitemap.display())));
+                }
+                Err(e) => self.log.push(Message::error(format!("Sitemap: {}", e))),
+            }
+        }
+        if let Some(feed) = self.generate_feed.clone() {
+            let posts: Vec<feed::Post> = self
+                .entries
+                .iter()
+                .zip(&self.input_files)
+                .zip(&self.output_files)
+                .filter(|((entry, _), _)| entry.status == EntryStatus::Done && !entry.draft)
+                .map(|((entry, input), output)| {
+                    let title = entry.title.clone().unwrap_or_else(|| self.filename_title.title(input));
+                    feed::Post::read(input, entry.last_output.as_ref().unwrap_or(output), title)
+                })
+                .collect();
+            let count = posts.len();
+            match feed::write_feed(&feed, &self.feed_title, &self.site_base_url, posts) {
+                Ok(mut warnings) => {
+                    self.log.append(&mut warnings);
+                    self.log.push(Message::info("📰", format!("Wrote feed with {} posts: {}", count, feed.display())));
+                }
+                Err(e) => self.log.push(Message::error(format!("Feed: {}", e))),
+            }
+        }
+        if self.write_manifest {
+            let outputs: Vec<(PathBuf, usize)> = self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.status == EntryStatus::Done)
+                .filter_map(|(i, entry)| {
+                    let first = entry.last_output.clone().or_else(|| self.output_files.get(i).cloned())?;
+                    Some(std::iter::once(first).chain(entry.split_pages.iter().cloned()).map(move |output| (output, i)))
+                })
+                .flatten()
+                .filter(|(output, _)| output.is_file())
+                .collect();
+            let pages: Vec<manifest::Page> = outputs
+                .iter()
+                .map(|(output, i)| manifest::Page {
+                    output,
+                    source: &self.input_files[*i],
+                    converted_at: self.entries[*i].converted_at.as_deref(),
+                })
+                .collect();
+            if !pages.is_empty() {
+                match manifest::write_manifest(&pages) {
+                    Ok(path) => self.log.push(Message::info("🧾", format!("Wrote manifest of {} files: {}", pages.len(), path.display()))),
+                    Err(e) => self.log.push(Message::error(format!("Manifest: {}", e))),
+                }
+            }
+        }
+        if self.serve && !self.headless {
+            self.start_server();
+        }
+    }
+    /// Serves the folder holding every converted page, restarting the server
+    /// if that folder or the port changed.
+    fn start_server(&mut self) {
+        let outputs: Vec<PathBuf> = self
+            .entries
+            .iter()
+            .zip(&self.output_files)
+            .filter(|(entry, _)| entry.status == EntryStatus::Done)
+            .map(|(entry, output)| entry.last_output.as_ref().unwrap_or(output))
+            .map(|output| paths::absolute(output))
+            .collect();
+        let Some(root) = paths::common_folder(&outputs) else {
+            return;
+        };
+        let port = match self.serve_port {
+            0 => 8000,
+            port => port,
+        };
+        if self.server.as_ref().is_some_and(|server| server.root == root && server.port == port) {
+            return;
+        }
+        // Free the port before binding it again.
+        self.server = None;
+        match serve::Server::start(root, port) {
+            Ok(server) => {
+                self.log.push(Message::info("📡", format!("Serving {} at {}", server.root.display(), server.urls.join(" and "))));
+                self.server = Some(server);
+            }
+            Err(e) => self.log.push(Message::error(format!("Could not start the HTTP server on {}", e))),
+        }
+    }
+    fn serve_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.serve, "📡 Serve outputs over HTTP")
+                .on_hover_text("After each batch, serve the output folder so phones and other devices on the network can open the pages");
+            if self.serve {
+                ui.label("port (0 = 8000):");
+                ui.add(egui::DragValue::new(&mut self.serve_port).clamp_range(0..=65535));
+            }
+        });
+        let mut stop = false;
+        if let Some(server) = &self.server {
+            ui.horizontal(|ui| {
+                ui.label(format!("📡 Serving {}", server.root.display()));
+                for url in &server.urls {
+                    ui.hyperlink(url);
+                }
+                stop = ui.button("⏹ Stop").clicked();
+            });
+        }
+        if stop {
+            self.server = None;
+            self.status_message = Message::info("⏹", "Stopped the HTTP server");
+        }
+    }
+    /// Keeps `entries` the same length as `input_files`, e.g. for state files
+    /// written before per-entry metadata existed.
+    fn sync_entries(&mut self) {
+        self.entries.resize_with(self.input_files.len(), FileEntry::default);
+    }
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::AddFile => self.pick_input(),
+            Command::RemoveLastAdded => {
+                if let Some(removed) = self.last_added_index() {
+                    self.status_message = Message::info("🗑", format!("Removed {}", self.input_files[removed].display()));
+                    self.remove_file(removed);
+                    self.last_added = None;
+                }
+            }
+            Command::ClearUnpinned => self.clear_unpinned(),
+            Command::RenameOutputs => self.show_rename_dialog = true,
+            Command::ExportScript => self.export_script(),
+            Command::OpenProject => self.open_project_dialog(),
+            Command::SaveProject => self.save_project_dialog(),
+            Command::Convert => self.request_convert(),
+            Command::Resume => {
+                self.convert_all(true);
+                self.save_state();
+            }
+            Command::ResetStatuses => self.reset_statuses(),
+            Command::TogglePreview => self.preview = !self.preview,
+            Command::ToggleDrafts => self.include_drafts = !self.include_drafts,
+            Command::ToggleReaderMode => self.reader_mode = !self.reader_mode,
+            Command::ToggleSanitize => self.sanitize = !self.sanitize,
+            Command::OpenPalette => self.palette = Some(Default::default()),
+            Command::OpenScratch => {
+                self.scratch.get_or_insert_with(Default::default);
+            }
+            Command::CompareWorkspaces => self.open_workspace_diff(),
+        }
+    }
+    fn menu_bar(&mut self, ctx: &egui::Context) {
+        let mut clicked = None;
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                for category in commands::CATEGORIES {
+                    ui.menu_button(category, |ui| {
+                        for info in COMMANDS.iter().filter(|info| info.category == category) {
+                            let mut button = egui::Button::new(info.name);
+                            if let Some(shortcut) = &info.shortcut {
+                                button = button.shortcut_text(ctx.format_shortcut(shortcut));
+                            }
+                            if ui.add_enabled((info.enabled)(self), button).clicked() {
+                                clicked = Some(info.command);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                }
+            });
+        });
+        if let Some(command) = clicked {
+            self.run_command(command);
+        }
+    }
+    /// Fuzzy-searchable list of every command; arrows and Enter pick, Esc closes.
+    fn command_palette(&mut self, ctx: &egui::Context) {
+        let Some(mut palette) = self.palette.take() else {
+            return;
+        };
+        let found = commands::matches(&palette.query);
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+        if down && palette.selected + 1 < found.len() {
+            palette.selected += 1;
+        }
+        if up {
+            palette.selected = palette.selected.saturating_sub(1);
+        }
+        let mut run = enter
+            .then(|| found.get(palette.selected).copied())
+            .flatten()
+            .filter(|info| (info.enabled)(self));
+        let mut open = !escape && run.is_none();
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                let query = ui.add(
+                    egui::TextEdit::singleline(&mut palette.query)
+                        .hint_text("Type a command…")
+                        .desired_width(360.0),
+                );
+                query.request_focus();
+                if query.changed() {
+                    palette.selected = 0;
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for (i, info) in found.iter().enumerate() {
+                        let enabled = (info.enabled)(self);
+                        ui.horizontal(|ui| {
+                            let label = format!("{}: {}", info.category, info.name);
+                            let response = ui.add_enabled(enabled, egui::SelectableLabel::new(i == palette.selected, label));
+                            if i == palette.selected {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                run = Some(*info);
+                            }
+                            if let Some(shortcut) = &info.shortcut {
+                                ui.weak(ctx.format_shortcut(shortcut));
+                            }
+                        });
+                    }
+                    if found.is_empty() {
+                        ui.weak("No matching commands");
+                    }
+                });
+            });
+        if run.is_some() {
+            open = false;
+        }
+        if open {
+            self.palette = Some(palette);
+        }
+        if let Some(info) = run {
+            self.run_command(info.command);
+        }
+    }
+    /// Converts entry `i` in memory and shows the result; writes nothing.
+    fn preview_entry(&mut self, i: usize) {
+        self.sync_entries();
+        let Some(input) = self.input_files.get(i).cloned() else {
+            return;
+        };
+        let mut html = Vec::new();
+        let mut log = Vec::new();
+        let range = self.range_override.or(self.entries[i].line_range);
+        let io_slots = Slots::new(self.io_worker_count());
+        let template = self.entries[i].template.clone();
+        let entry = EntryOptions { range, template: template.as_deref() };
+        let result = self.convert_file(&input, Sink::Memory(&mut html), entry, &io_slots, &Cancel::default(), &mut log);
+        self.log = log;
+        match result {
+            Ok(Converted { timings, .. }) => {
+                self.status_message = Message::info(
+                    "👁",
+                    format!("Previewed {} ({})", input.display(), timing::format_duration(timings.total())),
+                );
+                self.html_preview = Some((input, String::from_utf8_lossy(&html).into_owned()));
+            }
+            Err(message) => self.status_message = message,
+        }
+    }
+    fn html_preview_window(&mut self, ctx: &egui::Context) {
+        let Some((input, html)) = &self.html_preview else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new(format!("👁 Preview: {}", input.file_name().unwrap_or_default().to_string_lossy()))
+            .open(&mut open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.weak(format!("{} · in memory only, nothing written", format_bytes(html.len() as u64)));
+                    if ui.button("📋 Copy HTML").clicked() {
+                        ui.output_mut(|o| o.copied_text = html.clone());
+                    }
+                });
+                egui::ScrollArea::both().show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut html.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            });
+        if !open {
+            self.html_preview = None;
+        }
+    }
+    fn pick_input(&mut self) {
+        if let Some(md) = rfd::FileDialog::new().add_filter("Markdown", &["md"]).pick_file() {
+            self.add_input(md);
+        }
+    }
+    /// Queues Markdown files dropped onto the window, converting them right
+    /// away with `convert_on_drop`.
+    fn accept_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped: Vec<PathBuf> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect());
+        if dropped.is_empty() {
+            return;
+        }
+        self.sync_entries();
+        let mut notes = Vec::new();
+        let mut queued = Vec::new();
+        for path in dropped {
+            let markdown = path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"));
+            if !markdown {
+                notes.push(Message::warning(format!("Not a Markdown file, ignored: {}", path.display())));
+                continue;
+            }
+            let i = match self.input_files.iter().position(|input| *input == path) {
+                Some(i) => {
+                    self.entries[i].status = EntryStatus::Pending;
+                    i
+                }
+                None => {
+                    self.add_input(path);
+                    self.input_files.len() - 1
+                }
+            };
+            if !queued.contains(&i) {
+                queued.push(i);
+            }
+        }
+        if !queued.is_empty() && self.convert_on_drop {
+            let count = queued.len();
+            self.request_batch(Some(queued));
+            if self.large_input_confirm.is_none() {
+                self.log.insert(0, Message::info("📥", format!("{} dropped file(s)", count)));
+            }
+        } else if !queued.is_empty() {
+            self.status_message = Message::info("📥", format!("Queued {} dropped file(s)", queued.len()));
+        }
+        self.log.extend(notes);
+        self.save_state();
+    }
+    /// Queues a Markdown file with an `.html` output next to it.
+    fn add_input(&mut self, md: PathBuf) {
+        let mut out = md.clone();
+        out.set_extension("html");
+        self.add_file(md, out);
+    }
+    fn add_file(&mut self, input: PathBuf, output: PathBuf) {
+        self.sync_entries();
+        self.rename_undo = None;
+        let input_size = fs::metadata(&input).map(|m| m.len()).ok();
+        let draft = frontmatter::file_is_draft(&input);
+        self.last_added = Some(input.clone());
+        self.input_files.push(input);
+        self.output_files.push(output);
+        self.entries.push(FileEntry { input_size, draft, ..FileEntry::default() });
+    }
+    /// Where the most recently added entry is now, if it's still listed.
+    fn last_added_index(&self) -> Option<usize> {
+        let last_added = self.last_added.as_ref()?;
+        self.input_files.iter().rposition(|input| input == last_added)
+    }
+    fn remove_file(&mut self, i: usize) {
+        self.sync_entries();
+        self.rename_undo = None;
+        self.input_files.remove(i);
+        self.output_files.remove(i);
+        self.entries.remove(i);
+    }
+    /// Flips an entry's pin and re-sorts, returning the entry's new index.
+    fn toggle_pinned(&mut self, i: usize) -> usize {
+        self.sync_entries();
+        let Some(entry) = self.entries.get_mut(i) else {
+            return i;
+        };
+        entry.pinned = !entry.pinned;
+        let input = self.input_files[i].clone();
+        self.sort_pinned();
+        self.input_files.iter().position(|p| *p == input).unwrap_or(i)
+    }
+    /// Moves pinned entries to the top, keeping the order within each group.
+    fn sort_pinned(&mut self) {
+        self.sync_entries();
+        if self.input_files.len() != self.output_files.len() {
+            return;
+        }
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        order.sort_by_key(|&i| !self.entries[i].pinned);
+        if order.iter().enumerate().all(|(pos, &i)| pos == i) {
+            return;
+        }
+        self.rename_undo = None;
+        self.input_files = order.iter().map(|&i| self.input_files[i].clone()).collect();
+        self.output_files = order.iter().map(|&i| self.output_files[i].clone()).collect();
+        self.entries = order.iter().map(|&i| self.entries[i].clone()).collect();
+    }
+    /// Removes every entry that isn't pinned.
+    fn clear_unpinned(&mut self) {
+        self.sync_entries();
+        for i in (0..self.entries.len()).rev() {
+            if !self.entries[i].pinned {
+                self.remove_file(i);
+            }
+        }
+    }
+    fn reset_statuses(&mut self) {
+        for entry in &mut self.entries {
+            entry.status = EntryStatus::Pending;
+        }
+        self.resume_prompt = false;
+        self.batch_in_progress = false;
+        self.save_state();
+    }
+    fn rename_dialog(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_rename_dialog;
+        let mut apply = false;
+        egui::Window::new("✏ Rename outputs")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("rename_fields").num_columns(2).show(ui, |ui| {
+                    ui.label("Prefix:");
+                    ui.text_edit_singleline(&mut self.rename_rule.prefix);
+                    ui.end_row();
+                    ui.label("Suffix:");
+                    ui.text_edit_singleline(&mut self.rename_rule.suffix);
+                    ui.end_row();
+                    ui.label("Find in name:");
+                    ui.text_edit_singleline(&mut self.rename_rule.find);
+                    ui.end_row();
+                    ui.label("Replace with:");
+                    ui.text_edit_singleline(&mut self.rename_rule.replace);
+                    ui.end_row();
+                });
+                ui.separator();
+                let renamed: Vec<PathBuf> = self.output_files.iter().map(|p| self.rename_rule.apply(p)).collect();
+                let collisions = rename::duplicate_indices(&renamed);
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("rename_preview").striped(true).show(ui, |ui| {
+                        ui.strong("Before");
+                        ui.strong("After");
+                        ui.end_row();
+                        for (i, (before, after)) in self.output_files.iter().zip(&renamed).enumerate() {
+                            ui.label(before.file_name().unwrap_or_default().to_string_lossy());
+                            let name = after.file_name().unwrap_or_default().to_string_lossy().to_string();
+                            if collisions.contains(&i) {
+                                ui.colored_label(egui::Color32::LIGHT_RED, format!("{} ⚠ collision", name));
+                            } else {
+                                ui.label(name);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+                if !collisions.is_empty() {
+                    ui.colored_label(
+                        egui::Color32::LIGHT_RED,
+                        format!("❌ {} outputs would share a name.", collisions.len()),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    let can_apply = collisions.is_empty() && !self.rename_rule.is_noop();
+                    if ui.add_enabled(can_apply, egui::Button::new("✅ Apply")).clicked() {
+                        apply = true;
+                    }
+                });
+            });
+        if apply {
+            let renamed: Vec<PathBuf> = self.output_files.iter().map(|p| self.rename_rule.apply(p)).collect();
+            self.rename_undo = Some(std::mem::replace(&mut self.output_files, renamed));
+            self.rename_rule = Default::default();
+            self.status_message = Message::info("✏", "Renamed outputs. Use ↩ Undo rename to revert.");
+            self.save_state();
+            open = false;
+        }
+        self.show_rename_dialog = open;
+    }
+    fn replacements_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(format!("🔁 Text replacements ({})", self.replacements.len())).show(ui, |ui| {
+            ui.weak("Applied to the Markdown source before conversion, in order.");
+            let mut remove = None;
+            for (i, rule) in self.replacements.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut rule.find).hint_text("{{version}}").desired_width(140.0));
+                    ui.label("→");
+                    ui.add(egui::TextEdit::singleline(&mut rule.replace).hint_text("1.2.3").desired_width(140.0));
+                    ui.checkbox(&mut rule.regex, "Regex");
+                    if ui.small_button("❌").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                self.replacements.remove(i);
+            }
+            ui.horizontal(|ui| {
+                if ui.button("➕ Add replacement").clicked() {
+                    self.replacements.push(Default::default());
+                }
+                if ui
+                    .add_enabled(
+                        !self.replacements.is_empty() && !self.input_files.is_empty(),
+                        egui::Button::new("🔍 Preview on queued files"),
+                    )
+                    .on_hover_text("List every line the replacements would change before converting")
+                    .clicked()
+                {
+                    self.open_replace_preview();
+                }
+            });
+        });
+    }
+    fn highlight_terms_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(format!("🖍 Highlighted terms ({})", self.highlight_terms.len())).show(ui, |ui| {
+            ui.weak("Whole words, any case, wrapped in <mark>; code is left alone.");
+            let mut remove = None;
+            for (i, term) in self.highlight_terms.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(term).hint_text("deprecated").desired_width(180.0));
+                    if ui.small_button("❌").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                self.highlight_terms.remove(i);
+            }
+            if ui.button("➕ Add term").clicked() {
+                self.highlight_terms.push(String::new());
+            }
+        });
+    }
+    fn lint_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.lint, "🧐 Lint Markdown before converting");
+        if self.lint_strict {
+            ui.weak("--strict-lint: files with warnings fail.");
+        }
+        if !self.lint && !self.lint_strict {
+            return;
+        }
+        egui::CollapsingHeader::new("Lint rules").show(ui, |ui| {
+            egui::Grid::new("lint_rules").num_columns(2).striped(true).show(ui, |ui| {
+                for (rule, description) in lint::RULES {
+                    let mut enabled = !self.lint_disabled_rules.iter().any(|d| d == rule);
+                    if ui.checkbox(&mut enabled, rule).changed() {
+                        if enabled {
+                            self.lint_disabled_rules.retain(|d| d != rule);
+                        } else {
+                            self.lint_disabled_rules.push(rule.to_string());
+                        }
+                    }
+                    ui.label(description);
+                    ui.end_row();
+                }
+            });
+        });
+    }
+    fn element_rules_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(format!("🏷 Element classes & attributes ({})", self.element_rules.len())).show(
+            ui,
+            |ui| {
+                ui.weak("Added to every generated element of that kind, alongside classes from other options.");
+                let mut remove = None;
+                for (i, rule) in self.element_rules.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source(("element_rule", i))
+                            .selected_text(format!("<{}>", rule.element))
+                            .width(90.0)
+                            .show_ui(ui, |ui| {
+                                for element in inject::ELEMENTS {
+                                    ui.selectable_value(&mut rule.element, element.to_string(), format!("<{}>", element));
+                                }
+                            });
+                        ui.add(
+                            egui::TextEdit::singleline(&mut rule.classes)
+                                .hint_text("table table-striped")
+                                .desired_width(140.0),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut rule.attributes)
+                                .hint_text("loading=\"lazy\"")
+                                .desired_width(140.0),
+                        );
+                        if ui.small_button("❌").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    self.element_rules.remove(i);
+                }
+                if ui.button("➕ Add element rule").clicked() {
+                    self.element_rules.push(Default::default());
+                }
+            },
+        );
+    }
+    fn sitemap_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let mut enabled = self.generate_sitemap.is_some();
+            if ui.checkbox(&mut enabled, "🗺 Generate sitemap.xml").changed() {
+                self.generate_sitemap = if enabled {
+                    rfd::FileDialog::new()
+                        .add_filter("XML", &["xml"])
+                        .set_file_name("sitemap.xml")
+                        .save_file()
+                } else {
+                    None
+                };
+            }
+            if let Some(sitemap) = &self.generate_sitemap {
+                ui.monospace(sitemap.display().to_string());
+            }
+        });
+        ui.horizontal(|ui| {
+            let mut enabled = self.generate_feed.is_some();
+            if ui.checkbox(&mut enabled, "📰 Generate Atom feed").changed() {
+                self.generate_feed = if enabled {
+                    rfd::FileDialog::new()
+                        .add_filter("Atom", &["xml", "atom"])
+                        .set_file_name("feed.xml")
+                        .save_file()
+                } else {
+                    None
+                };
+            }
+            if let Some(feed) = &self.generate_feed {
+                ui.monospace(feed.display().to_string());
+            }
+        });
+        if self.generate_feed.is_some() {
+            ui.horizontal(|ui| {
+                ui.label("Feed title:");
+                ui.add(egui::TextEdit::singleline(&mut self.feed_title).hint_text("the feed's folder name"));
+            })
+            .response
+            .on_hover_text("Posts are listed newest first by front-matter date, or file time, with the description or first paragraph as summary");
+        }
+        if self.generate_sitemap.is_some() || self.generate_feed.is_some() {
+            ui.horizontal(|ui| {
+                ui.label("Site base URL:");
+                ui.add(egui::TextEdit::singleline(&mut self.site_base_url).hint_text("https://example.com/docs"));
+            });
+        }
+        ui.checkbox(&mut self.write_manifest, "🧾 Write manifest.json with checksums")
+            .on_hover_text("After each batch, list every generated page with its size, SHA-256, source and conversion time");
+        ui.horizontal(|ui| {
+            ui.label("🔗 URL prefix:");
+            ui.add(egui::TextEdit::singleline(&mut self.url_prefix).hint_text("/docs/"))
+                .on_hover_text("Put in front of root-relative links and images like /img/logo.png; relative links are left alone");
+        });
+        ui.checkbox(&mut self.rewrite_md_links, "🔗 Link to .html pages instead of .md files")
+            .on_hover_text("[see](other.md#setup) becomes other.html#setup, using this page's extension; URLs and anchors are left alone");
+        ui.horizontal(|ui| {
+            ui.label("🖼 Local asset paths:");
+            egui::ComboBox::from_id_source("asset_paths")
+                .selected_text(self.asset_paths.label())
+                .show_ui(ui, |ui| {
+                    for mode in AssetPaths::ALL {
+                        ui.selectable_value(&mut self.asset_paths, mode, mode.label());
+                    }
+                });
+            if self.asset_paths == AssetPaths::BaseUrl {
+                ui.add(egui::TextEdit::singleline(&mut self.asset_base_url).hint_text("https://cdn.example.com/docs"));
+            }
+        });
+        ui.horizontal(|ui| {
+            let mut enabled = self.image_src_rewrite.is_some();
+            if ui.checkbox(&mut enabled, "🖼 Rewrite image folder").changed() {
+                self.image_src_rewrite = enabled.then(Default::default);
+            }
+            if let Some((from, to)) = self.image_src_rewrite.as_mut() {
+                ui.add(egui::TextEdit::singleline(from).hint_text("assets").desired_width(110.0));
+                ui.label("→");
+                ui.add(egui::TextEdit::singleline(to).hint_text("/static/assets").desired_width(140.0));
+            }
+        })
+        .response
+        .on_hover_text("Only local, relative image paths that start with the folder are changed");
+        ui.checkbox(&mut self.copy_assets, "📦 Copy local images and stylesheets next to the output")
+            .on_hover_text("Keeps their paths relative to the Markdown, so the page works from the output folder");
+    }
+    fn template_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("🧾 Select HTML template").clicked() {
+                if let Some(template) = rfd::FileDialog::new().add_filter("HTML", &["html", "htm"]).pick_file() {
+                    self.template_path = Some(template);
+                }
+            }
+            if let Some(template) = self.template_path.clone() {
+                ui.monospace(template.display().to_string());
+                if ui.button("❌ Remove template").clicked() {
+                    self.template_path = None;
+                }
+            }
+        });
+        if self.template_path.is_some() {
+            ui.weak("Placeholders: {{title}} {{css}} {{body}} {{toc}}");
+        }
+    }
+    fn font_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.embed_fonts, "🔤 Embed a font in the page");
+        if !self.embed_fonts {
             return;
         }
-        for (input, output) in self.input_files.iter().zip(self.output_files.iter()) {
-            match fs::read_to_string(input) {
-                Ok(md) => {
-                    let mut options = Options::empty();
-                    options.insert(Options::ENABLE_STRIKETHROUGH);
-                    let parser = Parser::new_ext(&md, options);
-                    let mut html_body = String::new();
-                    html::push_html(&mut html_body, parser);
-                    let title = if self.title.is_empty() {
-                        input.file_name().unwrap_or_default().to_string_lossy().to_string()
-                    } else {
-                        self.title.clone()
-                    };
-                    let mut html_output = format!(
-                        "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\"><title>{}</title>",
-                        title
-                    );
-                    if let Some(css_path) = &self.css_path {
-                        match fs::read_to_string(css_path) {
-                            Ok(css) => {
-                                html_output += &format!("<style>\n{}\n</style>", css);
-                            }
-                            Err(_) => {
-                                html_output += &format!(
-                                    "<link rel=\"stylesheet\" href=\"{}\">",
-                                    css_path.display()
-                                );
-                            }
+        ui.horizontal(|ui| {
+            ui.label("Family:");
+            ui.add(egui::TextEdit::singleline(&mut self.font_family).hint_text("from file name"));
+            if ui.button("📁 Select font file").clicked() {
+                if let Some(font) = rfd::FileDialog::new()
+                    .add_filter("Fonts", &["woff2", "woff", "ttf", "otf"])
+                    .pick_file()
+                {
+                    self.font_path = Some(font);
+                }
+            }
+        });
+        if let Some(font) = &self.font_path {
+            ui.monospace(format!("Font: {}", font.display()));
+        }
+    }
+    fn plantuml_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("🌿 PlantUML:");
+            egui::ComboBox::from_id_source("plantuml_renderer")
+                .selected_text(self.plantuml_renderer.label())
+                .show_ui(ui, |ui| {
+                    for renderer in [PlantUmlRenderer::Off, PlantUmlRenderer::LocalJar, PlantUmlRenderer::Server] {
+                        ui.selectable_value(&mut self.plantuml_renderer, renderer, renderer.label());
+                    }
+                });
+        });
+        match self.plantuml_renderer {
+            PlantUmlRenderer::Off => return,
+            PlantUmlRenderer::LocalJar => {
+                ui.horizontal(|ui| {
+                    if ui.button("📦 Select plantuml.jar").clicked() {
+                        if let Some(jar) = rfd::FileDialog::new().add_filter("Java archive", &["jar"]).pick_file() {
+                            self.plantuml_jar = Some(jar);
                         }
                     }
-                    html_output += &format!("</head><body>{}</body></html>", html_body);
-                    if let Err(e) = fs::write(output, html_output) {
-                        self.status_message = format!("❌ Failed to write {}: {}", output.display(), e);
-                        return;
+                    if let Some(jar) = &self.plantuml_jar {
+                        ui.monospace(jar.display().to_string());
                     }
-                    if self.preview {
-                        let _ = open_in_browser(output);
+                });
+            }
+            PlantUmlRenderer::Server => {
+                ui.horizontal(|ui| {
+                    ui.label("Server URL:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.plantuml_server)
+                            .hint_text("https://www.plantuml.com/plantuml"),
+                    );
+                });
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("Timeout per diagram (s, 0 = 10):");
+            ui.add(egui::DragValue::new(&mut self.diagram_timeout_secs).clamp_range(0..=600));
+        });
+    }
+    fn sanitize_allowlist_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Allowed tags & attributes").show(ui, |ui| {
+            ui.label("Extra tags (script and style are always removed):");
+            let mut remove_tag = None;
+            for (i, tag) in self.sanitize_allowed_tags.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.monospace(tag);
+                    if ui.small_button("❌").clicked() {
+                        remove_tag = Some(i);
                     }
-                    self.status_message = format!("✅ Converted: {} → {}", input.display(), output.display());
-                    self.progress = 1.0;
-                }
-                Err(e) => {
-                    self.status_message = format!("❌ Failed to read {}: {}", input.display(), e);
-                    return;
+                });
+            }
+            if let Some(i) = remove_tag {
+                self.sanitize_allowed_tags.remove(i);
+            }
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.new_allowed_tag).hint_text("iframe"));
+                if ui.button("➕ Add tag").clicked() && !self.new_allowed_tag.trim().is_empty() {
+                    self.sanitize_allowed_tags.push(self.new_allowed_tag.trim().to_string());
+                    self.new_allowed_tag.clear();
                 }
+            });
+            ui.add_space(5.0);
+            ui.label("Extra attributes (use * as the tag to allow it everywhere):");
+            let mut remove_attribute = None;
+            for (i, (tag, attribute)) in self.sanitize_allowed_attributes.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.monospace(format!("{} → {}", tag, attribute));
+                    if ui.small_button("❌").clicked() {
+                        remove_attribute = Some(i);
+                    }
+                });
             }
-        }
+            if let Some(i) = remove_attribute {
+                self.sanitize_allowed_attributes.remove(i);
+            }
+            ui.horizontal(|ui| {
+                let (tag, attribute) = &mut self.new_allowed_attribute;
+                ui.add(egui::TextEdit::singleline(tag).hint_text("div").desired_width(80.0));
+                ui.add(egui::TextEdit::singleline(attribute).hint_text("class").desired_width(80.0));
+                if ui.button("➕ Add attribute").clicked()
+                    && !tag.trim().is_empty()
+                    && !attribute.trim().is_empty()
+                {
+                    self.sanitize_allowed_attributes
+                        .push((tag.trim().to_string(), attribute.trim().to_string()));
+                    tag.clear();
+                    attribute.clear();
+                }
+            });
+        });
     }
     fn save_state(&self) {
-        if let Ok(json) = serde_json::to_string_pretty(self) {
-            let _ = fs::write("app_state.json", json);
+        if self.headless {
+            return;
         }
+        let Ok(compact) = serde_json::to_string(self) else {
+            return;
+        };
+        let json = match self.state_format {
+            StateFormat::Compact => compact,
+            StateFormat::Auto if compact.len() > PRETTY_STATE_LIMIT => compact,
+            _ => serde_json::to_string_pretty(self).unwrap_or(compact),
+        };
+        let _ = fs::write("app_state.json", json);
     }
     fn load_state() -> Self {
-        fs::read_to_string("app_state.json")
-            .ok()
-            .and_then(|s| serde_json::from_str(&s).ok())
-            .unwrap_or_default()
+        let mut state: Self = match fs::read_to_string("app_state.json") {
+            Ok(s) => Self::from_settings_json(&s).unwrap_or_default(),
+            Err(_) => Self::initial_state(),
+        };
+        state.ensure_workspace();
+        state.prepare_session();
+        state
+    }
+    /// Brings a freshly loaded file list up to date and offers to resume an
+    /// interrupted batch.
+    fn prepare_session(&mut self) {
+        self.refresh_file_info();
+        self.sort_pinned();
+        self.resume_prompt = self.batch_in_progress
+            && self.entries.iter().any(|e| e.included(self.include_drafts) && e.status != EntryStatus::Done);
+    }
+    /// The current settings without the file list or batch progress.
+    fn settings_value(&self) -> Result<serde_json::Value, String> {
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        if let Some(map) = value.as_object_mut() {
+            for key in ["input_files", "output_files", "entries", "batch_in_progress", "batch_folder"] {
+                map.remove(key);
+            }
+        }
+        Ok(value)
+    }
+    /// The current settings as written next to an exported script.
+    fn options_json(&self) -> Result<String, String> {
+        let mut value = self.settings_value()?;
+        if let Some(map) = value.as_object_mut() {
+            map.remove("workspaces");
+            map.remove("active_workspace");
+        }
+        serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+    }
+    fn load_options(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_settings_json(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+    /// Reads saved state or options, bringing older files up to date.
+    fn from_settings_json(text: &str) -> Result<Self, String> {
+        Self::from_settings_value(serde_json::from_str(text).map_err(|e| e.to_string())?)
+    }
+    fn from_settings_value(mut value: serde_json::Value) -> Result<Self, String> {
+        extensions::migrate(&mut value);
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+    fn export_script(&mut self) {
+        let Some(script) = rfd::FileDialog::new()
+            .add_filter("Shell script", &["sh"])
+            .add_filter("PowerShell script", &["ps1"])
+            .set_file_name("convert.sh")
+            .save_file()
+        else {
+            return;
+        };
+        self.sync_entries();
+        let pairs: Vec<(PathBuf, PathBuf)> = (0..self.entries.len().min(self.output_files.len()))
+            .filter(|&i| self.entries[i].included(self.include_drafts))
+            .map(|i| (self.input_files[i].clone(), self.output_files[i].clone()))
+            .collect();
+        self.status_message = match self
+            .options_json()
+            .and_then(|json| script::export_script(&script, &pairs, &json).map_err(|e| e.to_string()))
+        {
+            Ok(options) => Message::info(
+                "📜",
+                format!("Exported {} conversions to {} (options in {})", pairs.len(), script.display(), options.display()),
+            ),
+            Err(e) => Message::error(format!("Failed to export script: {}", e)),
+        };
+    }
+    /// Converts a single file with no interface, printing the log, and
+    /// returns the process exit code.
+    fn convert_headless(mut self, input: PathBuf, output: PathBuf) -> i32 {
+        self.input_files = vec![input];
+        self.output_files = vec![output];
+        self.entries.clear();
+        self.run_headless()
+    }
+    /// Converts the whole file list with no interface, printing the log, and
+    /// returns the process exit code.
+    fn run_headless(mut self) -> i32 {
+        self.headless = true;
+        self.preview = false;
+        self.batch_folder = None;
+        self.convert_all(false);
+        for line in &self.log {
+            println!("{}", self.message_style.format(line));
+        }
+        let failed = self.status_message.is_error()
+            || self.entries.iter().any(|e| e.status == EntryStatus::Failed);
+        if failed {
+            eprintln!("{}", self.message_style.format(&self.status_message));
+            1
+        } else {
+            0
+        }
     }
 }
 
@@ -93,41 +2491,278 @@ impl eframe::App for AppState {
         ctx.set_visuals(egui::Visuals::default());
         #[cfg(target_os = "linux")]
         ctx.set_visuals(egui::Visuals::dark());
+        // Shortcuts would fight with text fields, e.g. Ctrl+Backspace.
+        if self.palette.is_none() && !ctx.wants_keyboard_input() {
+            let shift_p = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::P);
+            if ctx.input_mut(|i| i.consume_shortcut(&shift_p)) {
+                self.run_command(Command::OpenPalette);
+            }
+            for info in COMMANDS {
+                if let Some(shortcut) = info.shortcut {
+                    if ctx.input_mut(|i| i.consume_shortcut(&shortcut)) && (info.enabled)(self) {
+                        self.run_command(info.command);
+                    }
+                }
+            }
+        }
+        self.accept_dropped_files(ctx);
+        self.refresh_batch_check(false);
+        if self.watch {
+            self.poll_watch();
+            ctx.request_repaint_after(watch::POLL_INTERVAL);
+        }
+        if self.poll_batch() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+        if self.poll_browser_open() {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+        self.menu_bar(ctx);
+        self.workspace_tabs(ctx);
+        self.command_palette(ctx);
+        self.html_preview_window(ctx);
+        self.scratch_window(ctx);
+        self.replace_preview_window(ctx);
+        self.workspace_diff_window(ctx);
+        self.project_confirm_dialog(ctx);
+        if self.show_rename_dialog {
+            self.rename_dialog(ctx);
+        }
+        self.large_input_dialog(ctx);
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical_centered(|ui| {
                 ui.heading("📄 Markdown to HTML Converter");
             });
             ui.add_space(10.0);
+            if self.resume_prompt {
+                let done = self.entries.iter().filter(|e| e.status == EntryStatus::Done).count();
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "⚠ The last batch was interrupted ({} of {} done).",
+                            done,
+                            self.entries.len()
+                        ))
+                        .color(egui::Color32::LIGHT_YELLOW),
+                    );
+                    if ui.button("▶ Resume").clicked() {
+                        self.convert_all(true);
+                        self.save_state();
+                    }
+                    if ui.button("🔄 Start fresh").clicked() {
+                        self.reset_statuses();
+                    }
+                });
+                ui.add_space(10.0);
+            }
             egui::Frame::group(ui.style()).show(ui, |ui| {
                 ui.vertical(|ui| {
                     ui.label("📂 Input & Output Files");
-                    if ui.button("➕ Add Markdown File").clicked() {
-                        if let Some(md) = rfd::FileDialog::new()
-                            .add_filter("Markdown", &["md"])
-                            .pick_file()
+                    ui.horizontal(|ui| {
+                        if ui.button("➕ Add Markdown File").on_hover_text("Ctrl+O").clicked() {
+                            self.pick_input();
+                        }
+                        if ui
+                            .add_enabled(!self.output_files.is_empty(), egui::Button::new("✏ Rename outputs…"))
+                            .clicked()
                         {
-                            self.input_files.push(md.clone());
-                            let mut out = md.clone();
-                            out.set_extension("html");
-                            self.output_files.push(out);
+                            self.show_rename_dialog = true;
                         }
-                    }
+                        let unpinned = self.entries.iter().filter(|e| !e.pinned).count();
+                        if ui
+                            .add_enabled(unpinned > 0, egui::Button::new("🧹 Clear unpinned"))
+                            .on_hover_text("Remove every file that isn't pinned")
+                            .clicked()
+                        {
+                            self.clear_unpinned();
+                        }
+                        ui.checkbox(&mut self.include_drafts, "📝 Include drafts")
+                            .on_hover_text("Convert files marked draft: true, e.g. for a local preview");
+                        egui::ComboBox::from_id_source("on_empty_input")
+                            .selected_text(format!("Empty files: {}", self.on_empty_input.label()))
+                            .show_ui(ui, |ui| {
+                                for mode in EmptyInput::ALL {
+                                    ui.selectable_value(&mut self.on_empty_input, mode, mode.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Files with nothing but whitespace or front matter in them");
+                        ui.checkbox(&mut self.convert_on_drop, "📥 Convert on drop")
+                            .on_hover_text("Convert Markdown files dropped onto the window right away instead of just adding them");
+                        if ui
+                            .add_enabled(!self.input_files.is_empty(), egui::Button::new("📜 Export as script…"))
+                            .on_hover_text("Write a shell or PowerShell script that reproduces this batch with --convert")
+                            .clicked()
+                        {
+                            self.export_script();
+                        }
+                        if self.rename_undo.is_some() && ui.button("↩ Undo rename").clicked() {
+                            if let Some(previous) = self.rename_undo.take() {
+                                if previous.len() == self.output_files.len() {
+                                    self.output_files = previous;
+                                    self.save_state();
+                                }
+                            }
+                        }
+                    });
                     let mut remove_indices = Vec::new();
+                    let mut toggle_pin = None;
+                    let mut preview = None;
+                    let mut move_to = None;
+                    let threshold = self.large_input_threshold();
                     for (i, input) in self.input_files.iter().enumerate() {
                         if let Some(output) = self.output_files.get(i) {
-                            ui.horizontal_wrapped(|ui| {
-                                ui.label(format!("📄 {}", input.display()));
+                            let row = ui.horizontal_wrapped(|ui| {
+                                let status = match self.entries.get(i).map(|e| e.status) {
+                                    Some(EntryStatus::Done) => ui.label("✅"),
+                                    Some(EntryStatus::Failed) => ui.label("❌"),
+                                    _ => ui.label("⏳"),
+                                };
+                                if let Some(entry) = self.entries.get(i) {
+                                    let mut details = Vec::new();
+                                    if let Some(last) = &entry.last_output {
+                                        details.push(format!("Last written to {}", last.display()));
+                                    }
+                                    if let Some(title) = &entry.title {
+                                        details.push(format!("Title: {}", title));
+                                    }
+                                    if entry.warnings > 0 {
+                                        details.push(format!("⚠ {} warning(s) in the log", entry.warnings));
+                                    }
+                                    if let Some(timings) = entry.timings {
+                                        details.push(format!(
+                                            "⏱ {} total\n{}",
+                                            timing::format_duration(timings.total()),
+                                            timings.summary().replace(" · ", "\n")
+                                        ));
+                                    }
+                                    if !details.is_empty() {
+                                        status.on_hover_text(details.join("\n\n"));
+                                    }
+                                }
+                                if let Some(entry) = self.entries.get_mut(i) {
+                                    ui.checkbox(&mut entry.enabled, "").on_hover_text("Include in the next batch");
+                                    let star = if entry.pinned { "⭐" } else { "☆" };
+                                    if ui
+                                        .selectable_label(entry.pinned, star)
+                                        .on_hover_text("Pin to the top of the list")
+                                        .clicked()
+                                    {
+                                        toggle_pin = Some(i);
+                                    }
+                                }
+                                let name = ui.label(format!("📄 {}", input.display()));
+                                if self.workspaces.len() > 1 {
+                                    name.context_menu(|ui| {
+                                        ui.menu_button("🗂 Move to workspace", |ui| {
+                                            for (w, workspace) in self.workspaces.iter().enumerate() {
+                                                if w != self.active_workspace && ui.button(&workspace.name).clicked() {
+                                                    move_to = Some((i, w));
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                        });
+                                    });
+                                }
+                                if self.entries.get(i).is_some_and(|e| e.draft) {
+                                    let hover = if self.include_drafts {
+                                        "Marked draft: true; converted because drafts are included"
+                                    } else {
+                                        "Marked draft: true; skipped unless drafts are included"
+                                    };
+                                    ui.label(egui::RichText::new("📝 draft").color(egui::Color32::LIGHT_YELLOW))
+                                        .on_hover_text(hover);
+                                }
+                                if let Some(size) = self.entries.get(i).and_then(|e| e.input_size) {
+                                    let text = egui::RichText::new(format_bytes(size));
+                                    if size > threshold {
+                                        ui.label(text.color(egui::Color32::LIGHT_RED))
+                                            .on_hover_text("Larger than the large-input threshold");
+                                    } else {
+                                        ui.weak(text);
+                                    }
+                                }
                                 ui.label("➡");
                                 ui.label(format!("💾 {}", output.display()));
-                                if ui.button("❌ Remove").clicked() {
+                                if let Some(entry) = self.entries.get(i).filter(|e| !e.split_pages.is_empty()) {
+                                    let pages: Vec<String> =
+                                        entry.split_pages.iter().map(|page| format!("📄 {}", page.display())).collect();
+                                    ui.weak(format!("+{} pages", entry.split_pages.len()))
+                                        .on_hover_text(format!("Split by heading into:\n{}", pages.join("\n")));
+                                }
+                                if let Some(entry) = self.entries.get_mut(i) {
+                                    if ui
+                                        .selectable_label(entry.line_range.is_some(), "✂")
+                                        .on_hover_text("Convert only a range of lines")
+                                        .clicked()
+                                    {
+                                        entry.line_range = match entry.line_range {
+                                            Some(_) => None,
+                                            None => Some(LineRange { start: 1, end: 50 }),
+                                        };
+                                    }
+                                    if let Some(range) = entry.line_range.as_mut() {
+                                        ui.label("lines");
+                                        ui.add(egui::DragValue::new(&mut range.start).clamp_range(1..=1_000_000));
+                                        ui.label("–");
+                                        ui.add(egui::DragValue::new(&mut range.end).clamp_range(1..=1_000_000));
+                                    }
+                                    let template_button = ui.selectable_label(entry.template.is_some(), "🧾");
+                                    let template_button = match &entry.template {
+                                        Some(template) => template_button.on_hover_text(format!(
+                                            "Template: {}\nRight-click to use the global one",
+                                            template.display()
+                                        )),
+                                        None => template_button.on_hover_text(
+                                            "Use a different template for this file; `template:` in front matter works too",
+                                        ),
+                                    };
+                                    if template_button.clicked() {
+                                        if let Some(template) =
+                                            rfd::FileDialog::new().add_filter("HTML", &["html", "htm"]).pick_file()
+                                        {
+                                            entry.template = Some(template);
+                                        }
+                                    }
+                                    if template_button.secondary_clicked() {
+                                        entry.template = None;
+                                    }
+                                }
+                                if ui.button("👁 Preview").on_hover_text("Convert in memory and show the HTML").clicked() {
+                                    preview = Some(i);
+                                }
+                                let remove = ui.button("❌ Remove");
+                                let remove = if self.last_added.as_ref() == Some(input) {
+                                    remove.on_hover_text("Ctrl+Backspace")
+                                } else {
+                                    remove
+                                };
+                                if remove.clicked() {
                                     remove_indices.push(i);
                                 }
                             });
+                            if let Some((problem, error)) = self.batch_check.row_problem(i) {
+                                let color = if error { egui::Color32::LIGHT_RED } else { egui::Color32::LIGHT_YELLOW };
+                                ui.painter().rect_stroke(row.response.rect.expand(2.0), 3.0, egui::Stroke::new(1.0, color));
+                                row.response.clone().on_hover_text(problem);
+                            }
+                            if self.jump_to_row == Some(i) {
+                                row.response.scroll_to_me(Some(egui::Align::Center));
+                                self.jump_to_row = None;
+                            }
                         }
                     }
                     for &i in remove_indices.iter().rev() {
-                        self.input_files.remove(i);
-                        self.output_files.remove(i);
+                        self.remove_file(i);
+                    }
+                    if let Some(i) = toggle_pin {
+                        self.toggle_pinned(i);
+                    }
+                    if let Some(i) = preview {
+                        self.preview_entry(i);
+                    }
+                    if let Some((i, w)) = move_to {
+                        self.move_to_workspace(i, w);
                     }
                 });
             });
@@ -151,22 +2786,377 @@ impl eframe::App for AppState {
                             }
                         });
                     }
+                    self.css_presets_ui(ui);
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.progress_bar, "📏 Reading-progress bar");
+                        if self.progress_bar {
+                            ui.label("from (words, 0 = 1000):");
+                            ui.add(egui::DragValue::new(&mut self.progress_bar_min_words).clamp_range(0..=1_000_000));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.back_to_top, "⬆ Back-to-top button");
+                        if self.back_to_top {
+                            ui.label("from (words, 0 = 1500):");
+                            ui.add(egui::DragValue::new(&mut self.back_to_top_min_words).clamp_range(0..=1_000_000));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.reader_mode, "📖 Reader mode");
+                        ui.add_enabled(
+                            self.reader_mode && self.css_path.is_some(),
+                            egui::Checkbox::new(&mut self.reader_replaces_css, "Instead of my CSS"),
+                        );
+                    });
+                    ui.checkbox(&mut self.bundle_css, "🧩 Bundle CSS into one file")
+                        .on_hover_text("Write all styles to bundle.css next to the outputs and link it from each page");
+                    ui.checkbox(&mut self.minify_css, "🗜 Minify CSS")
+                        .on_hover_text("Strip comments and whitespace from inlined and bundled styles; values are kept as written");
                     ui.horizontal(|ui| {
                         ui.label("📝 Title:");
                         ui.text_edit_singleline(&mut self.title);
+                        ui.label("otherwise from the file name as");
+                        egui::ComboBox::from_id_source("filename_title")
+                            .selected_text(self.filename_title.label())
+                            .show_ui(ui, |ui| {
+                                for style in titles::FilenameTitle::ALL {
+                                    ui.selectable_value(&mut self.filename_title, style, style.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Used when neither the title above nor front matter gives one");
+                    });
+                    ui.checkbox(&mut self.front_matter_comment, "📋 Keep front matter as an HTML comment")
+                        .on_hover_text("<!-- frontmatter … --> at the top of the body, for tools that read it from the output");
+                    ui.horizontal(|ui| {
+                        ui.label("🔎 JSON-LD type:");
+                        ui.add(egui::TextEdit::singleline(&mut self.json_ld_type).hint_text("Article").desired_width(100.0))
+                            .on_hover_text("schema.org structured data with the title as headline and front matter description, author and date");
+                        if !self.json_ld_type.trim().is_empty() {
+                            ui.label("default author");
+                            ui.add(egui::TextEdit::singleline(&mut self.json_ld_author).desired_width(140.0));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.preview, "🌐 Open in browser after conversion");
+                        if self.preview {
+                            ui.label("attempts (0 = 3):");
+                            ui.add(egui::DragValue::new(&mut self.browser_open_attempts).clamp_range(0..=10));
+                        }
+                    });
+                    self.font_ui(ui);
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.heading_ids, "🔗 Add ids to headings");
+                        ui.checkbox(&mut self.warn_duplicate_headings, "warn about duplicates")
+                            .on_hover_text("Log headings with the same text, which get -1, -2… ids");
+                        if self.heading_ids || self.layout == OutputLayout::SidebarToc {
+                            ui.label("scroll margin:");
+                            let field = ui.add(
+                                egui::TextEdit::singleline(&mut self.scroll_margin_top)
+                                    .hint_text("e.g. 4rem")
+                                    .desired_width(70.0),
+                            );
+                            field.on_hover_text("Space kept above a heading jumped to by link, for sticky headers");
+                            if !self.scroll_margin_top.trim().is_empty()
+                                && layout::scroll_margin_css(&self.scroll_margin_top).is_none()
+                            {
+                                ui.colored_label(egui::Color32::LIGHT_RED, "not a CSS length");
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        let mut limit = self.max_content_width.is_some();
+                        if ui.checkbox(&mut limit, "📏 Limit text width").changed() {
+                            self.max_content_width = limit.then(|| "70ch".to_string());
+                        }
+                        if let Some(width) = self.max_content_width.as_mut() {
+                            ui.add(egui::TextEdit::singleline(width).hint_text("70ch").desired_width(70.0))
+                                .on_hover_text("Only applies with the built-in styles, not your own CSS file");
+                            if layout::max_width_css(width, false).is_none() {
+                                ui.colored_label(egui::Color32::LIGHT_RED, "not a CSS length");
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("🔠 Heading case:");
+                        egui::ComboBox::from_id_source("heading_case")
+                            .selected_text(self.heading_case.label())
+                            .show_ui(ui, |ui| {
+                                for case in [HeadingCase::None, HeadingCase::TitleCase, HeadingCase::SentenceCase] {
+                                    ui.selectable_value(&mut self.heading_case, case, case.label());
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.emit_lastmod, "🕒 Emit last-modified date");
+                        ui.add_enabled(
+                            self.emit_lastmod,
+                            egui::Checkbox::new(&mut self.show_updated_line, "Show \"Updated on\" line"),
+                        );
+                    });
+                    ui.checkbox(&mut self.code_language_badges, "🏷 Language badges on code blocks");
+                    ui.checkbox(&mut self.code_tabs, "🗂 Tabbed code groups")
+                        .on_hover_text("Adjacent code blocks fenced like ```python tab=\"Python\" become one set of tabs");
+                    ui.horizontal(|ui| {
+                        ui.label("🚨 Alert style:");
+                        egui::ComboBox::from_id_source("alert_preset")
+                            .selected_text(self.alert_preset.label())
+                            .show_ui(ui, |ui| {
+                                for preset in [AlertPreset::None, AlertPreset::GitHub] {
+                                    ui.selectable_value(&mut self.alert_preset, preset, preset.label());
+                                }
+                            });
+                        ui.checkbox(&mut self.github_alerts, "render > [!NOTE] blockquotes as alerts")
+                            .on_hover_text("Also [!TIP], [!IMPORTANT], [!WARNING] and [!CAUTION]; other blockquotes are untouched");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("📑 Footnotes:");
+                        egui::ComboBox::from_id_source("footnote_mode")
+                            .selected_text(self.footnote_mode.label())
+                            .show_ui(ui, |ui| {
+                                for mode in FootnoteMode::ALL {
+                                    ui.selectable_value(&mut self.footnote_mode, mode, mode.label());
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("↕ Sortable tables:");
+                        egui::ComboBox::from_id_source("sortable_tables")
+                            .selected_text(self.sortable_tables.label())
+                            .show_ui(ui, |ui| {
+                                for mode in SortableTables::ALL {
+                                    ui.selectable_value(&mut self.sortable_tables, mode, mode.label());
+                                }
+                            });
+                    });
+                    ui.checkbox(&mut self.extract_tables_csv, "📊 Also save tables as CSV")
+                        .on_hover_text("report.table1.csv, report.table2.csv, … next to report.html");
+                    self.plantuml_ui(ui);
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.figures_from_titles, "🖼 Image titles become figure captions");
+                        ui.checkbox(&mut self.images_as_figures, "alt text too")
+                            .on_hover_text("A standalone image without a title is captioned by its alt text");
+                        if self.figures_from_titles || self.images_as_figures {
+                            ui.checkbox(&mut self.number_figures, "number them");
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.inline_svg, "🖼 Inline small local SVG images")
+                            .on_hover_text("Skipped while sanitizing, which would remove the inlined SVGs");
+                        if self.inline_svg {
+                            ui.label("up to (KB, 0 = 50):");
+                            ui.add(egui::DragValue::new(&mut self.inline_svg_max_kb).clamp_range(0..=10_000));
+                        }
+                    });
+                    self.template_ui(ui);
+                    ui.horizontal(|ui| {
+                        ui.label("📐 Layout:");
+                        ui.add_enabled_ui(!self.fragment_output && self.template_path.is_none(), |ui| {
+                            egui::ComboBox::from_id_source("layout")
+                                .selected_text(self.layout.label())
+                                .show_ui(ui, |ui| {
+                                    for layout in [OutputLayout::Standard, OutputLayout::SidebarToc] {
+                                        ui.selectable_value(&mut self.layout, layout, layout.label());
+                                    }
+                                });
+                        });
+                    });
+                    ui.checkbox(&mut self.fragment_output, "🧩 Body fragment only (no <html>/<head>)");
+                    ui.checkbox(&mut self.collapse_blank_lines, "🧽 Collapse runs of blank lines");
+                    ui.horizontal(|ui| {
+                        let mut split = self.split_by_heading.is_some();
+                        if ui
+                            .checkbox(&mut split, "📑 Split into pages at headings")
+                            .on_hover_text("One file per section, with previous/next links and a shared table of contents")
+                            .changed()
+                        {
+                            self.split_by_heading = split.then_some(1);
+                        }
+                        if let Some(level) = self.split_by_heading.as_mut() {
+                            ui.label("up to level h");
+                            ui.add(egui::DragValue::new(level).clamp_range(1..=6));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        let mut enabled = self.page_break_level.is_some();
+                        if ui.checkbox(&mut enabled, "🖨 New printed page for each chapter").changed() {
+                            self.page_break_level = enabled.then_some(1);
+                        }
+                        if let Some(level) = self.page_break_level.as_mut() {
+                            ui.label("up to level h");
+                            ui.add(egui::DragValue::new(level).clamp_range(1..=6));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Print and PDF only: break-before: page on those headings; the screen is unchanged");
+                    ui.checkbox(&mut self.outline_json, "🧭 Write heading outline as JSON")
+                        .on_hover_text("guide.outline.json next to guide.html: nested headings with level, text, id and link");
+                    ui.horizontal(|ui| {
+                        ui.label("📦 Wrap body in:");
+                        ui.add(egui::TextEdit::singleline(&mut self.wrapper_tag).hint_text("main").desired_width(70.0));
+                        if !self.wrapper_tag.trim().is_empty() {
+                            ui.label("class");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.wrapper_class)
+                                    .hint_text("container")
+                                    .desired_width(110.0),
+                            );
+                            ui.label("id");
+                            ui.add(egui::TextEdit::singleline(&mut self.wrapper_id).desired_width(70.0));
+                            ui.add_enabled(
+                                self.fragment_output,
+                                egui::Checkbox::new(&mut self.fragment_wrapper, "in fragments too"),
+                            );
+                        }
+                    });
+                    ui.checkbox(&mut self.aria_landmarks, "♿ ARIA landmarks")
+                        .on_hover_text("Body in <main> (around the wrapper unless it is one) and a labelled table of contents <nav>; not in fragments or templates");
+                    self.sitemap_ui(ui);
+                    self.serve_ui(ui);
+                    ui.horizontal(|ui| {
+                        ui.label("🧵 Max parallel conversions (0 = auto):");
+                        ui.add(egui::DragValue::new(&mut self.max_parallel).clamp_range(0..=64));
+                        ui.label("I/O tasks (0 = 4):");
+                        ui.add(egui::DragValue::new(&mut self.max_parallel_io).clamp_range(0..=64))
+                            .on_hover_text("Diagram renders and other disk or network work running at once");
+                    });
+                    ui.horizontal(|ui| {
+                        let mut enabled = self.log_file.is_some();
+                        if ui.checkbox(&mut enabled, "🗒 Log conversions to a file").changed() {
+                            self.log_file = if enabled {
+                                rfd::FileDialog::new().set_file_name("md2html.log").save_file()
+                            } else {
+                                None
+                            };
+                        }
+                        if let Some(log_file) = &self.log_file {
+                            ui.monospace(log_file.display().to_string());
+                            ui.label("rotate at (KB, 0 = 1024):");
+                            ui.add(egui::DragValue::new(&mut self.log_file_max_kb));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Time, status, input, output and bytes per file, tab-separated, kept across runs");
+                    ui.horizontal(|ui| {
+                        ui.label("⏱ Per-file timeout (s, 0 = none):");
+                        ui.add(egui::DragValue::new(&mut self.per_file_timeout_secs).clamp_range(0..=3600))
+                            .on_hover_text("A file that takes longer fails with a timeout and the batch carries on");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("💾 Saved state format:");
+                        egui::ComboBox::from_id_source("state_format")
+                            .selected_text(self.state_format.label())
+                            .show_ui(ui, |ui| {
+                                for format in [StateFormat::Auto, StateFormat::Pretty, StateFormat::Compact] {
+                                    ui.selectable_value(&mut self.state_format, format, format.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("Auto pretty-prints small state files and writes large ones compactly");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("💬 Messages:");
+                        egui::ComboBox::from_id_source("message_style")
+                            .selected_text(self.message_style.label())
+                            .show_ui(ui, |ui| {
+                                for style in MessageStyle::ALL {
+                                    ui.selectable_value(&mut self.message_style, style, style.label());
+                                }
+                            })
+                            .response
+                            .on_hover_text("How the status line, log and command-line output mark success and errors");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("⚖ Warn about inputs larger than (MB, 0 = 10):");
+                        ui.add(egui::DragValue::new(&mut self.large_input_threshold_mb).clamp_range(0..=10_000));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.dated_subfolder, "📅 Create dated subfolder per batch");
+                        if self.dated_subfolder {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.dated_subfolder_pattern)
+                                    .hint_text(dates::DEFAULT_SUBFOLDER_PATTERN)
+                                    .desired_width(120.0),
+                            );
+                        }
+                    });
+                    if self.dated_subfolder {
+                        match dates::folder_stamp(&self.dated_subfolder_pattern, &chrono::Local::now()) {
+                            Ok(folder) => ui.weak(format!("Next batch goes into …/{}/", folder)),
+                            Err(e) => ui.colored_label(egui::Color32::LIGHT_RED, format!("❌ {}", e)),
+                        };
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("🪝 Pre-process command:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.pre_process_command)
+                                .hint_text("none, or e.g. ./generate.sh")
+                                .desired_width(180.0),
+                        )
+                        .on_hover_text("Run with each Markdown file's path before it's read; a failure skips that file");
+                    });
+                    self.replacements_ui(ui);
+                    self.highlight_terms_ui(ui);
+                    self.element_rules_ui(ui);
+                    self.lint_ui(ui);
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.check_links, "🔗 Check local links");
+                        ui.checkbox(&mut self.check_remote_links, "🌍 Check remote links too")
+                            .on_hover_text("Send a HEAD request to every http(s) link and log 404s and timeouts; slow and needs the network");
+                    });
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("🧩 Extensions:");
+                        for (flag, label, hover) in extensions::TOGGLES {
+                            let mut on = self.extensions.0.contains(flag);
+                            if ui.checkbox(&mut on, label).on_hover_text(hover).changed() {
+                                self.extensions.0.set(flag, on);
+                            }
+                        }
+                    });
+                    ui.checkbox(&mut self.wiki_links, "📎 Convert [[wiki links]] to page links");
+                    ui.checkbox(&mut self.autolink, "🔗 Link bare URLs and email addresses");
+                    ui.horizontal(|ui| {
+                        ui.label("#️⃣ Link #123 and @name to:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.reference_repo)
+                                .hint_text("https://github.com/owner/repo")
+                                .desired_width(240.0),
+                        )
+                        .on_hover_text("#123 → <repo>/issues/123, @name → the profile on the same host; not in code");
+                    });
+                    ui.checkbox(&mut self.sub_superscript, "🧪 H~2~O subscript and E=mc^2^ superscript");
+                    ui.checkbox(&mut self.renumber_lists, "🔢 Number every ordered list from 1")
+                        .on_hover_text("Off: a list whose first item is 3. starts at 3 (<ol start=\"3\">), e.g. steps continued after a code block");
+                    ui.checkbox(&mut self.sanitize, "🛡 Sanitize generated HTML");
+                    if self.sanitize {
+                        self.sanitize_allowlist_ui(ui);
+                    }
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.validate_html, "🔍 Validate generated HTML");
+                        if self.validate_html {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.validator_command)
+                                    .hint_text("built-in, or e.g. vnu / tidy -qe")
+                                    .desired_width(180.0),
+                            );
+                        }
                     });
-                    ui.checkbox(&mut self.preview, "🌐 Open in browser after conversion");
                 });
             });
             ui.add_space(15.0);
             ui.vertical_centered(|ui| {
+                self.batch_check_ui(ui);
                 if ui
-                    .add(egui::Button::new("🚀 Convert to HTML").fill(egui::Color32::from_rgb(80, 170, 255)))
+                    .add_enabled(
+                        self.batch_check.runnable() && !self.batch_running(),
+                        egui::Button::new("🚀 Convert to HTML").fill(egui::Color32::from_rgb(80, 170, 255)),
+                    )
                     .clicked()
                 {
-                    self.convert_all();
-                    self.save_state();
+                    self.request_convert();
                 }
+                ui.checkbox(&mut self.watch, "👀 Watch for changes")
+                    .on_hover_text("Reconvert a file when it's saved, and every file when the CSS or a template changes");
             });
             ui.add_space(10.0);
             ui.add(
@@ -176,29 +3166,180 @@ impl eframe::App for AppState {
             );
             if !self.status_message.is_empty() {
                 ui.label(
-                    egui::RichText::new(&self.status_message)
+                    egui::RichText::new(self.message_style.format(&self.status_message))
                         .color(egui::Color32::LIGHT_YELLOW)
                         .strong(),
                 );
             }
+            if !self.log.is_empty() {
+                egui::CollapsingHeader::new(format!("📜 Log ({})", self.log.len())).show(ui, |ui| {
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for line in &self.log {
+                            ui.monospace(self.message_style.format(line));
+                        }
+                    });
+                });
+            }
         });
     }
 }
 
-fn open_in_browser(path: &PathBuf) -> std::io::Result<()> {
-    #[cfg(target_os = "linux")]
-    return Command::new("xdg-open").arg(path).spawn().map(|_| ());
-    #[cfg(target_os = "macos")]
-    return Command::new("open").arg(path).spawn().map(|_| ());
-    #[cfg(target_os = "windows")]
-    return Command::new("cmd").args(["/C", "start", path.to_str().unwrap_or("")]).spawn().map(|_| ());
+/// Everything up to and including `<body>`.
+fn document_head(title: &str, head_elements: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\"><title>{}</title>{}</head><body>",
+        toc::escape_text(title),
+        head_elements
+    )
+}
+
+/// Writes `output` through a buffered temp file next to it, renamed into place
+/// once complete so a failed conversion never leaves a half-written page.
+fn write_output(output: &Path, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> Result<(), Message> {
+    let mut temp_name = output.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp = output.with_file_name(temp_name);
+    let result = fs::File::create(&temp).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()
+    });
+    let result = result.and_then(|_| fs::rename(&temp, output));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp);
+        return Err(Message::error(format!("Failed to write {}: {}", output.display(), e)));
+    }
+    Ok(())
+}
+
+/// What a conversion takes from its row in the file list.
+#[derive(Clone, Copy, Default)]
+struct EntryOptions<'a> {
+    /// Only these lines of the input.
+    range: Option<LineRange>,
+    /// Template used instead of any other.
+    template: Option<&'a Path>,
+}
+
+/// What one conversion produced.
+struct Converted {
+    timings: PhaseTimings,
+    /// Further pages written next to the output when splitting by heading.
+    split_pages: Vec<PathBuf>,
+    /// The page's `<title>`.
+    title: String,
+    /// Size of everything written, split pages included.
+    bytes_written: u64,
+    /// How many of the lines this conversion logged are warnings.
+    warnings: usize,
+    /// Nothing was written, the input being empty and such inputs skipped.
+    skipped_empty: bool,
+}
+
+/// Where a conversion's HTML goes.
+enum Sink<'a> {
+    File(&'a Path),
+    /// Kept in memory for the preview window; nothing is written to disk.
+    Memory(&'a mut Vec<u8>),
+    /// Kept in memory for the scratch editor, which renders while the user
+    /// types, so hooks, link checks and diagram renders don't run either.
+    Scratch(&'a mut Vec<u8>),
+}
+
+fn write_to(sink: &mut Sink, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> Result<(), Message> {
+    match sink {
+        Sink::File(output) => write_output(output, write),
+        Sink::Memory(buffer) | Sink::Scratch(buffer) => write(&mut **buffer).map_err(|e| Message::error(format!("Failed to render: {}", e))),
+    }
+}
+
+/// `output` moved into `folder` next to where it would normally go.
+fn dated_output(output: &Path, folder: &str) -> PathBuf {
+    let name = output.file_name().unwrap_or_default();
+    match output.parent() {
+        Some(parent) => parent.join(folder).join(name),
+        None => PathBuf::from(folder).join(name),
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
+
+/// Prints a command-line error in `style` and exits with `code`.
+fn exit_with_error(style: MessageStyle, text: impl Into<String>, code: i32) -> ! {
+    eprintln!("{}", style.format(&Message::error(text)));
+    std::process::exit(code);
 }
 
 fn main() -> eframe::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    // Read first, so the other argument errors come out in this style.
+    let message_style = args.iter().position(|arg| arg == "--message-style").map(|i| {
+        args.get(i + 1)
+            .and_then(|name| MessageStyle::parse(name))
+            .unwrap_or_else(|| exit_with_error(MessageStyle::default(), "--message-style needs emoji, plain or ascii", 2))
+    });
+    let style = message_style.unwrap_or_default();
+    let strict_lint = args.iter().any(|arg| arg == "--strict-lint");
+    let range_override = match args.iter().position(|arg| arg == "--range") {
+        Some(i) => match args.get(i + 1).map(|range| LineRange::parse(range)) {
+            Some(Ok(range)) => Some(range),
+            Some(Err(e)) => exit_with_error(style, format!("--range: {}", e), 2),
+            None => exit_with_error(style, "--range needs start:end, e.g. --range 10:50", 2),
+        },
+        None => None,
+    };
+    let project = match args.iter().position(|arg| arg == "--project") {
+        Some(i) => match args.get(i + 1) {
+            Some(project) => Some(PathBuf::from(project)),
+            None => exit_with_error(style, format!("--project needs a .{} file", projects::EXTENSION), 2),
+        },
+        None => None,
+    };
+    let load_project = |project: &Path| {
+        AppState::load_project(project).unwrap_or_else(|e| exit_with_error(style, format!("--project: {}", e), 2))
+    };
+    let configure = move |mut state: AppState| {
+        state.lint_strict = strict_lint;
+        state.range_override = range_override;
+        if let Some(style) = message_style {
+            state.message_style = style;
+        }
+        state
+    };
+    if let Some(i) = args.iter().position(|arg| arg == "--convert") {
+        let (Some(input), Some(output)) = (args.get(i + 1), args.get(i + 2)) else {
+            exit_with_error(style, "--convert needs an input and an output file", 2);
+        };
+        let state = match (
+            args.iter().position(|arg| arg == "--options").and_then(|i| args.get(i + 1)),
+            project.as_ref(),
+        ) {
+            (Some(options), _) => AppState::load_options(Path::new(options))
+                .unwrap_or_else(|e| exit_with_error(style, format!("--options: {}", e), 2)),
+            (None, Some(project)) => load_project(project),
+            (None, None) => AppState::load_state(),
+        };
+        std::process::exit(configure(state).convert_headless(PathBuf::from(input), PathBuf::from(output)));
+    }
+    if let Some(project) = &project {
+        std::process::exit(configure(load_project(project)).run_headless());
+    }
+    if args.iter().any(|arg| arg == "--tui") {
+        if let Err(e) = tui::run(configure(AppState::load_state())) {
+            exit_with_error(style, format!("Terminal interface failed: {}", e), 1);
+        }
+        return Ok(());
+    }
     let options = eframe::NativeOptions::default();
     eframe::run_native(
         "Markdown to HTML GUI",
         options,
-        Box::new(|_cc| Box::new(AppState::load_state())),
+        Box::new(move |_cc| Box::new(configure(AppState::load_state()))),
     )
 }
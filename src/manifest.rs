@@ -0,0 +1,60 @@
+use crate::paths::{self, common_folder, relative_path, to_slash_path};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// One converted page, as passed in by the batch.
+pub struct Page<'a> {
+    pub output: &'a Path,
+    pub source: &'a Path,
+    /// RFC 3339 time of the conversion that wrote `output`.
+    pub converted_at: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Manifest {
+    generated_at: String,
+    files: Vec<ManifestFile>,
+}
+
+#[derive(Serialize)]
+struct ManifestFile {
+    /// Relative to the manifest, with forward slashes.
+    path: String,
+    size: u64,
+    sha256: String,
+    source: String,
+    converted_at: Option<String>,
+}
+
+/// Writes `manifest.json` into the deepest folder containing every page,
+/// listing each page's size and SHA-256, sorted by path so two manifests
+/// diff cleanly. Returns the manifest's path.
+pub fn write_manifest(pages: &[Page]) -> Result<PathBuf, String> {
+    let outputs: Vec<PathBuf> = pages
+        .iter()
+        .map(|page| paths::absolute(page.output))
+        .collect();
+    let root = common_folder(&outputs).ok_or("the pages share no folder")?;
+    let mut files = Vec::new();
+    for (page, output) in pages.iter().zip(&outputs) {
+        let bytes = fs::read(output).map_err(|e| format!("{}: {}", output.display(), e))?;
+        let relative = relative_path(&root, output).unwrap_or_else(|| output.clone());
+        files.push(ManifestFile {
+            path: to_slash_path(&relative),
+            size: bytes.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(&bytes)),
+            source: page.source.display().to_string(),
+            converted_at: page.converted_at.map(str::to_string),
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let manifest = Manifest { generated_at: chrono::Local::now().to_rfc3339(), files };
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    let path = root.join(MANIFEST_NAME);
+    fs::write(&path, json + "\n").map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
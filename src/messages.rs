@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How status and log messages are shown, for terminals and log files that
+/// can't cope with emoji.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum MessageStyle {
+    #[default]
+    Emoji,
+    /// `OK:`, `ERROR:` and `WARNING:` instead of ✅, ❌ and ⚠; other icons dropped.
+    Plain,
+    /// Plain, and typographic punctuation such as → spelled in ASCII.
+    Ascii,
+}
+
+/// Punctuation the messages use and its ASCII spelling.
+const ASCII_PUNCTUATION: [(&str, &str); 10] = [
+    ("→", "->"),
+    ("←", "<-"),
+    ("–", "-"),
+    ("—", "-"),
+    ("…", "..."),
+    ("·", "|"),
+    ("“", "\""),
+    ("”", "\""),
+    ("’", "'"),
+    ("×", "x"),
+];
+
+impl MessageStyle {
+    pub const ALL: [MessageStyle; 3] = [MessageStyle::Emoji, MessageStyle::Plain, MessageStyle::Ascii];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MessageStyle::Emoji => "Emoji",
+            MessageStyle::Plain => "Plain (OK: / ERROR:)",
+            MessageStyle::Ascii => "ASCII only",
+        }
+    }
+
+    /// The style named on the command line: `emoji`, `plain` or `ascii`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "emoji" => Some(MessageStyle::Emoji),
+            "plain" => Some(MessageStyle::Plain),
+            "ascii" => Some(MessageStyle::Ascii),
+            _ => None,
+        }
+    }
+
+    /// `message` in this style: its icon, or a word for its severity and no
+    /// icon, and for ASCII the punctuation spelled out.
+    pub fn format(self, message: &Message) -> String {
+        if self == MessageStyle::Emoji {
+            return message.to_string();
+        }
+        let prefix = match message.severity {
+            Severity::Info => "",
+            Severity::Success => "OK: ",
+            Severity::Warning => "WARNING: ",
+            Severity::Error => "ERROR: ",
+        };
+        let mut text = format!("{}{}", prefix, message.text);
+        if self == MessageStyle::Ascii {
+            for (from, to) in ASCII_PUNCTUATION {
+                text = text.replace(from, to);
+            }
+        }
+        text
+    }
+}
+
+/// How serious a message is, which decides how the plain styles start it.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Severity {
+    #[default]
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// A status line or log entry: its severity, the icon the emoji style puts
+/// in front, and the text itself.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Message {
+    pub severity: Severity,
+    pub icon: &'static str,
+    pub text: String,
+}
+
+impl Message {
+    pub fn new(severity: Severity, icon: &'static str, text: impl Into<String>) -> Self {
+        Self { severity, icon, text: text.into() }
+    }
+
+    pub fn info(icon: &'static str, text: impl Into<String>) -> Self {
+        Self::new(Severity::Info, icon, text)
+    }
+
+    pub fn success(text: impl Into<String>) -> Self {
+        Self::new(Severity::Success, "✅", text)
+    }
+
+    pub fn warning(text: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, "⚠", text)
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self::new(Severity::Error, "❌", text)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.icon {
+            "" => f.write_str(&self.text),
+            icon => write!(f, "{} {}", icon, self.text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_follow_the_severity() {
+        let error = Message::error("Failed to read a.md → b.html");
+        assert_eq!(MessageStyle::Emoji.format(&error), "❌ Failed to read a.md → b.html");
+        assert_eq!(MessageStyle::Plain.format(&error), "ERROR: Failed to read a.md → b.html");
+        assert_eq!(MessageStyle::Ascii.format(&error), "ERROR: Failed to read a.md -> b.html");
+        assert_eq!(MessageStyle::Plain.format(&Message::warning("Odd")), "WARNING: Odd");
+        assert_eq!(MessageStyle::Plain.format(&Message::success("Done")), "OK: Done");
+    }
+
+    #[test]
+    fn info_icons_are_dropped_in_plain_styles() {
+        let info = Message::info("📄", "Split page: a-2.html");
+        assert_eq!(MessageStyle::Emoji.format(&info), "📄 Split page: a-2.html");
+        assert_eq!(MessageStyle::Plain.format(&info), "Split page: a-2.html");
+        assert_eq!(MessageStyle::Emoji.format(&Message::info("", "Bare")), "Bare");
+    }
+}
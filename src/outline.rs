@@ -0,0 +1,49 @@
+use crate::toc::Heading;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize)]
+struct Node {
+    level: u32,
+    text: String,
+    id: String,
+    /// Where the heading is, e.g. `guide.html#install`.
+    href: String,
+    children: Vec<Node>,
+}
+
+/// Files under the last open heading of a lower level, or at this level.
+fn insert(siblings: &mut Vec<Node>, node: Node) {
+    match siblings.last_mut() {
+        Some(last) if last.level < node.level => insert(&mut last.children, node),
+        _ => siblings.push(node),
+    }
+}
+
+/// `guide.outline.json` for `guide.html`.
+pub fn outline_path(output: &Path) -> PathBuf {
+    output.with_extension("outline.json")
+}
+
+/// Writes the heading hierarchy of `output` as nested JSON next to it. Ids
+/// are the ones in the HTML, so the links land on the headings.
+pub fn write_outline(output: &Path, headings: &[Heading], href: impl Fn(&Heading) -> String) -> Result<PathBuf, String> {
+    let mut roots = Vec::new();
+    for heading in headings {
+        insert(
+            &mut roots,
+            Node {
+                level: heading.level,
+                text: heading.text.clone(),
+                id: heading.id.clone(),
+                href: href(heading),
+                children: Vec::new(),
+            },
+        );
+    }
+    let path = outline_path(output);
+    let json = serde_json::to_string_pretty(&roots).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
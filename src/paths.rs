@@ -0,0 +1,129 @@
+use std::path::{Component, Path, PathBuf};
+
+/// `path` resolved against the current directory if it's relative.
+pub fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    }
+}
+
+/// `path` with `.` and `..` resolved without touching the file system.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            component => normalized.push(component.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// `target` relative to the directory `base`, using `..` where needed.
+///
+/// Returns `None` when the two can't be related, e.g. different Windows
+/// drives, or one absolute and the other relative.
+pub fn relative_path(base: &Path, target: &Path) -> Option<PathBuf> {
+    if base.is_absolute() != target.is_absolute() {
+        return None;
+    }
+    let base: Vec<Component> = base.components().filter(|c| *c != Component::CurDir).collect();
+    let target: Vec<Component> = target.components().filter(|c| *c != Component::CurDir).collect();
+    if let (Some(Component::Prefix(a)), Some(Component::Prefix(b))) = (base.first(), target.first()) {
+        if a != b {
+            return None;
+        }
+    }
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    if base[common..].contains(&Component::ParentDir) {
+        return None;
+    }
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in &target[common..] {
+        relative.push(component.as_os_str());
+    }
+    Some(relative)
+}
+
+/// The deepest folder containing all of `files`, which should be absolute.
+pub fn common_folder(files: &[PathBuf]) -> Option<PathBuf> {
+    let mut folders = files.iter().filter_map(|file| file.parent());
+    let mut common: Vec<Component> = folders.next()?.components().collect();
+    for folder in folders {
+        let shared = common.iter().zip(folder.components()).take_while(|(a, b)| **a == *b).count();
+        common.truncate(shared);
+    }
+    (!common.is_empty()).then(|| common.iter().collect())
+}
+
+/// A relative path with forward slashes, whatever the platform.
+pub fn to_slash_path(path: &Path) -> String {
+    path.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+/// A relative path as a URL path: forward slashes, each segment percent-encoded.
+pub fn to_url_path(path: &Path) -> String {
+    path.components()
+        .map(|c| percent_encode(&c.as_os_str().to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn percent_encode(segment: &str) -> String {
+    let mut encoded = String::new();
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// An absolute path as a `file:///` URL, drive letter included on Windows.
+pub fn file_url(path: &Path) -> String {
+    let segments: Vec<String> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Prefix(prefix) => Some(prefix.as_os_str().to_string_lossy().replace('\\', "/")),
+            Component::RootDir => None,
+            c => Some(percent_encode(&c.as_os_str().to_string_lossy())),
+        })
+        .collect();
+    format!("file:///{}", segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative(base: &str, target: &str) -> Option<PathBuf> {
+        relative_path(Path::new(base), Path::new(target))
+    }
+
+    #[test]
+    fn relative_paths_climb_out_of_the_base() {
+        assert_eq!(relative("/site/docs", "/site/docs/a.html"), Some(PathBuf::from("a.html")));
+        assert_eq!(relative("/site/docs/guide", "/site/img/logo.png"), Some(PathBuf::from("../../img/logo.png")));
+        assert_eq!(relative("./out", "out/sub/./page.html"), Some(PathBuf::from("sub/page.html")));
+        assert_eq!(relative("/site", "/site"), Some(PathBuf::new()));
+    }
+
+    #[test]
+    fn unrelated_paths_have_no_relative_path() {
+        assert_eq!(relative("/site", "docs/a.html"), None);
+        assert_eq!(relative("docs", "/site/a.html"), None);
+        // Where `..` leads from the base isn't known without the file system.
+        assert_eq!(relative("../other", "docs/a.html"), None);
+    }
+}
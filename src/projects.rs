@@ -0,0 +1,144 @@
+use crate::messages::Message;
+use crate::{paths, AppState, EntryStatus, FileEntry};
+use eframe::egui;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const EXTENSION: &str = "m2hproj";
+
+/// State that belongs to this machine's session rather than to a project.
+const SESSION_KEYS: [&str; 4] = ["workspaces", "active_workspace", "batch_in_progress", "batch_folder"];
+
+impl AppState {
+    /// Writes the file list with its per-file settings, and every option, to
+    /// `path` as JSON. File paths are stored relative to the project's folder
+    /// where they can be, so the project can move with the documents.
+    pub(crate) fn save_project(&self, path: &Path) -> Result<(), String> {
+        let base = paths::absolute(path.parent().unwrap_or(Path::new("")));
+        let relative = |file: &PathBuf| {
+            let file = paths::absolute(file);
+            paths::relative_path(&base, &file).unwrap_or(file)
+        };
+        let entries: Vec<FileEntry> = self
+            .entries
+            .iter()
+            .map(|entry| FileEntry {
+                status: EntryStatus::default(),
+                last_output: None,
+                output_size: None,
+                template: entry.template.as_ref().map(relative),
+                ..entry.clone()
+            })
+            .collect();
+        let mut project = self.settings_value()?;
+        if let Some(map) = project.as_object_mut() {
+            for key in SESSION_KEYS {
+                map.remove(key);
+            }
+            let files = |list: &[PathBuf]| list.iter().map(relative).collect::<Vec<_>>();
+            let to_value = |value: Result<serde_json::Value, serde_json::Error>| value.map_err(|e| e.to_string());
+            map.insert("input_files".to_string(), to_value(serde_json::to_value(files(&self.input_files)))?);
+            map.insert("output_files".to_string(), to_value(serde_json::to_value(files(&self.output_files)))?);
+            map.insert("entries".to_string(), to_value(serde_json::to_value(entries))?);
+        }
+        let json = serde_json::to_string_pretty(&project).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Reads a project written by [`AppState::save_project`], resolving its
+    /// file paths against the project's folder.
+    pub(crate) fn load_project(path: &Path) -> Result<Self, String> {
+        let mut state = Self::load_options(path)?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        let resolve = |file: &mut PathBuf| {
+            if file.is_relative() {
+                *file = base.join(&*file);
+            }
+        };
+        state.input_files.iter_mut().for_each(resolve);
+        state.output_files.iter_mut().for_each(resolve);
+        state.entries.iter_mut().filter_map(|entry| entry.template.as_mut()).for_each(resolve);
+        Ok(state)
+    }
+
+    pub(crate) fn save_project_dialog(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("md2html project", &[EXTENSION])
+            .set_file_name(format!("{}.{}", self.workspace_name(), EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+        self.sync_entries();
+        self.status_message = match self.save_project(&path) {
+            Ok(()) => Message::info("💼", format!("Saved project with {} files: {}", self.input_files.len(), path.display())),
+            Err(e) => Message::error(format!("Failed to save project: {}", e)),
+        };
+    }
+
+    /// Asks before replacing a workspace that already has files.
+    pub(crate) fn open_project_dialog(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("md2html project", &[EXTENSION]).pick_file() else {
+            return;
+        };
+        if self.input_files.is_empty() {
+            self.open_project(&path);
+        } else {
+            self.project_confirm = Some(path);
+        }
+    }
+
+    /// Replaces the active workspace's files and settings with the project's.
+    fn open_project(&mut self, path: &Path) {
+        let mut next = match Self::load_project(path) {
+            Ok(next) => next,
+            Err(e) => {
+                self.status_message = Message::error(format!("Failed to open project: {}", e));
+                return;
+            }
+        };
+        next.workspaces = std::mem::take(&mut self.workspaces);
+        next.active_workspace = self.active_workspace;
+        next.lint_strict = self.lint_strict;
+        next.range_override = self.range_override;
+        next.window_title = std::mem::take(&mut self.window_title);
+        next.ensure_workspace();
+        if let Some(stem) = path.file_stem() {
+            next.workspaces[next.active_workspace].name = stem.to_string_lossy().into_owned();
+        }
+        next.prepare_session();
+        next.status_message = Message::info("💼", format!("Opened project with {} files: {}", next.input_files.len(), path.display()));
+        *self = next;
+        self.save_state();
+    }
+
+    pub(crate) fn project_confirm_dialog(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.project_confirm.clone() else {
+            return;
+        };
+        let mut open = false;
+        egui::Window::new("💼 Open project")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Replace the {} files and the settings of workspace \"{}\" with this project?",
+                    self.input_files.len(),
+                    self.workspace_name()
+                ));
+                ui.monospace(path.display().to_string());
+                ui.horizontal(|ui| {
+                    if ui.button("📂 Open project").clicked() {
+                        open = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.project_confirm = None;
+                    }
+                });
+            });
+        if open {
+            self.project_confirm = None;
+            self.open_project(&path);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+/// Whether `trimmed` starts a list item: `-`, `*`, `+`, `1.` or `1)`.
+fn is_list_item(trimmed: &str) -> bool {
+    let digits = trimmed.len() - trimmed.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = &trimmed[digits..];
+    let marker = if digits > 0 { rest.strip_prefix(['.', ')']) } else { rest.strip_prefix(['-', '*', '+']) };
+    marker.is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\n', '\r']))
+}
+
+/// Copies `markdown`, passing each line outside fenced and indented code
+/// blocks through `convert_line`, for rewrites done before parsing.
+///
+/// An indented line only starts a code block after a blank line that
+/// doesn't end a list item, whose continuation paragraphs are indented too.
+pub fn convert_outside_code(markdown: &str, mut convert_line: impl FnMut(&str, &mut String)) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut fence: Option<&str> = None;
+    let mut indented_code = false;
+    let mut previous_blank = true;
+    let mut in_list = false;
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let blank = trimmed.is_empty();
+        let indented = line.starts_with("    ") || line.starts_with('\t');
+        if fence.is_none() {
+            indented_code = if blank { indented_code } else { indented && (indented_code || (previous_blank && !in_list)) };
+        }
+        let marker = if indented_code {
+            None
+        } else if trimmed.starts_with("```") {
+            Some("```")
+        } else if trimmed.starts_with("~~~") {
+            Some("~~~")
+        } else {
+            None
+        };
+        match (fence, marker) {
+            (None, Some(marker)) => {
+                fence = Some(marker);
+                out.push_str(line);
+            }
+            (Some(open), Some(marker)) if open == marker => {
+                fence = None;
+                out.push_str(line);
+            }
+            (Some(_), _) => out.push_str(line),
+            (None, None) if indented_code || blank => out.push_str(line),
+            (None, None) => convert_line(line, &mut out),
+        }
+        if fence.is_none() && !blank && !indented_code {
+            in_list = is_list_item(trimmed) || (in_list && indented);
+        }
+        previous_blank = blank;
+    }
+    out
+}
+
+/// Copies `line`, passing the text between inline code spans through
+/// `convert`. A backslash-escaped backtick doesn't open a span.
+pub fn convert_outside_code_spans(line: &str, out: &mut String, mut convert: impl FnMut(&str, &mut String)) {
+    let mut rest = line;
+    let mut from = 0;
+    while let Some(i) = rest[from..].find(['`', '\\']).map(|i| from + i) {
+        if rest.as_bytes()[i] == b'\\' {
+            // Leave the escape, and whatever it escapes, to `convert`.
+            from = i + 1 + rest[i + 1..].chars().next().map_or(0, char::len_utf8);
+            continue;
+        }
+        convert(&rest[..i], out);
+        // Copy the whole code span, however many backticks open it.
+        let ticks = rest[i..].len() - rest[i..].trim_start_matches('`').len();
+        let after = i + ticks;
+        let end = rest[after..].find(&rest[i..after]).map_or(rest.len(), |j| after + j + ticks);
+        out.push_str(&rest[i..end]);
+        rest = &rest[end..];
+        from = 0;
+    }
+    convert(rest, out);
+}
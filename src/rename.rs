@@ -0,0 +1,71 @@
+use crate::paths;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Edits applied to the file stem of every output path.
+#[derive(Default)]
+pub struct RenameRule {
+    pub prefix: String,
+    pub suffix: String,
+    pub find: String,
+    pub replace: String,
+}
+
+impl RenameRule {
+    pub fn is_noop(&self) -> bool {
+        self.prefix.is_empty() && self.suffix.is_empty() && self.find.is_empty()
+    }
+
+    /// `path` with find/replace, then prefix and suffix, applied to its stem.
+    /// The directory and extension are left alone.
+    pub fn apply(&self, path: &Path) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let stem = if self.find.is_empty() {
+            stem.to_string()
+        } else {
+            stem.replace(&self.find, &self.replace)
+        };
+        let mut name = format!("{}{}{}", self.prefix, stem, self.suffix);
+        if let Some(ext) = path.extension() {
+            name.push('.');
+            name.push_str(&ext.to_string_lossy());
+        }
+        path.with_file_name(name)
+    }
+}
+
+/// What two paths naming the same file have in common, so `out/a.html`
+/// and `./out/a.html` count as one.
+fn same_file_key(path: &Path) -> PathBuf {
+    paths::normalize(&paths::absolute(path))
+}
+
+/// Indices of the paths that appear more than once.
+pub fn duplicate_indices(paths: &[PathBuf]) -> Vec<usize> {
+    let keys: Vec<PathBuf> = paths.iter().map(|path| same_file_key(path)).collect();
+    let mut counts: HashMap<&Path, usize> = HashMap::new();
+    for key in &keys {
+        *counts.entry(key.as_path()).or_insert(0) += 1;
+    }
+    (0..keys.len()).filter(|&i| counts[keys[i].as_path()] > 1).collect()
+}
+
+/// New names for every path that repeats an earlier one: the same path
+/// with `-2`, `-3`, … after the stem, skipping names already in the list.
+pub fn suffixed_duplicates(paths: &[PathBuf]) -> Vec<(usize, PathBuf)> {
+    let mut taken: HashSet<PathBuf> = paths.iter().map(|path| same_file_key(path)).collect();
+    let mut seen = HashSet::new();
+    let mut renames = Vec::new();
+    for (i, path) in paths.iter().enumerate() {
+        if seen.insert(same_file_key(path)) {
+            continue;
+        }
+        let numbered = (2..)
+            .map(|n| RenameRule { suffix: format!("-{}", n), ..RenameRule::default() }.apply(path))
+            .find(|candidate| !taken.contains(&same_file_key(candidate)))
+            .unwrap_or_else(|| path.clone());
+        taken.insert(same_file_key(&numbered));
+        renames.push((i, numbered));
+    }
+    renames
+}
@@ -0,0 +1,66 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// A find-and-replace rule applied to the Markdown source before parsing.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Replacement {
+    pub find: String,
+    pub replace: String,
+    /// Treat `find` as a regular expression; `replace` may then use `$1`.
+    pub regex: bool,
+}
+
+/// Applies the rules in order; an invalid regex fails the whole file.
+pub fn apply_replacements<'a>(source: &'a str, rules: &[Replacement]) -> Result<Cow<'a, str>, String> {
+    let mut text = Cow::Borrowed(source);
+    for rule in rules.iter().filter(|r| !r.find.is_empty()) {
+        if rule.regex {
+            let re = Regex::new(&rule.find).map_err(|e| format!("invalid replacement regex \"{}\": {}", rule.find, e))?;
+            if let Cow::Owned(replaced) = re.replace_all(&text, rule.replace.as_str()) {
+                text = Cow::Owned(replaced);
+            }
+        } else if text.contains(&rule.find) {
+            text = Cow::Owned(text.replace(&rule.find, &rule.replace));
+        }
+    }
+    Ok(text)
+}
+
+/// One line, or run of lines, the rules would change.
+pub struct LineChange {
+    /// 1-based, in the original source.
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+}
+
+/// What [`apply_replacements`] would change in `source`, line by line.
+///
+/// When a rule adds or removes lines they can no longer be paired up, so
+/// everything between the first and last changed line is one change.
+pub fn preview_replacements(source: &str, rules: &[Replacement]) -> Result<Vec<LineChange>, String> {
+    let replaced = apply_replacements(source, rules)?;
+    if replaced == source {
+        return Ok(Vec::new());
+    }
+    let before: Vec<&str> = source.lines().collect();
+    let after: Vec<&str> = replaced.lines().collect();
+    if before.len() == after.len() {
+        return Ok(before
+            .iter()
+            .zip(&after)
+            .enumerate()
+            .filter(|(_, (b, a))| b != a)
+            .map(|(i, (b, a))| LineChange { line: i + 1, before: b.to_string(), after: a.to_string() })
+            .collect());
+    }
+    let prefix = before.iter().zip(&after).take_while(|(b, a)| b == a).count();
+    let suffix = before[prefix..].iter().rev().zip(after[prefix..].iter().rev()).take_while(|(b, a)| b == a).count();
+    Ok(vec![LineChange {
+        line: prefix + 1,
+        before: before[prefix..before.len() - suffix].join("\n"),
+        after: after[prefix..after.len() - suffix].join("\n"),
+    }])
+}
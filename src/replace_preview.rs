@@ -0,0 +1,99 @@
+use crate::replace::{self, LineChange};
+use crate::AppState;
+use eframe::egui;
+use std::fs;
+use std::path::PathBuf;
+
+/// Changes listed per file before the rest are summed up, so a rule that
+/// matches everywhere doesn't stall the window.
+const MAX_SHOWN: usize = 200;
+
+/// What the text replacements would do to each queued file, worked out
+/// when the preview is opened or refreshed rather than every frame.
+pub struct ReplacePreview {
+    files: Vec<(PathBuf, Result<Vec<LineChange>, String>)>,
+}
+
+impl AppState {
+    pub(crate) fn open_replace_preview(&mut self) {
+        let files = self
+            .input_files
+            .iter()
+            .map(|input| {
+                let changes = fs::read_to_string(input)
+                    .map_err(|e| e.to_string())
+                    .and_then(|source| replace::preview_replacements(&source, &self.replacements));
+                (input.clone(), changes)
+            })
+            .collect();
+        self.replace_preview = Some(ReplacePreview { files });
+    }
+
+    /// Every line the replacements would change in every queued file, with
+    /// a way to convert once they look right.
+    pub(crate) fn replace_preview_window(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &self.replace_preview else {
+            return;
+        };
+        let mut open = true;
+        let mut refresh = false;
+        let mut convert = false;
+        let changed_files = preview.files.iter().filter(|(_, changes)| !matches!(changes, Ok(c) if c.is_empty())).count();
+        let total: usize = preview.files.iter().filter_map(|(_, changes)| changes.as_ref().ok()).map(Vec::len).sum();
+        egui::Window::new("🔍 Replacement preview")
+            .open(&mut open)
+            .default_size([760.0, 520.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} change(s) in {} of {} file(s)", total, changed_files, preview.files.len()));
+                    if ui.button("🔄 Refresh").clicked() {
+                        refresh = true;
+                    }
+                    if ui.button("▶ Convert").clicked() {
+                        convert = true;
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (input, changes) in &preview.files {
+                        let changes = match changes {
+                            Ok(changes) if changes.is_empty() => continue,
+                            Ok(changes) => changes,
+                            Err(message) => {
+                                ui.colored_label(egui::Color32::RED, format!("❌ {}: {}", input.display(), message));
+                                continue;
+                            }
+                        };
+                        egui::CollapsingHeader::new(format!("{} ({})", input.display(), changes.len()))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                for change in changes.iter().take(MAX_SHOWN) {
+                                    ui.horizontal_top(|ui| {
+                                        ui.monospace(format!("{:>5}", change.line));
+                                        ui.vertical(|ui| {
+                                            ui.colored_label(egui::Color32::from_rgb(220, 90, 90), format!("- {}", change.before));
+                                            ui.colored_label(egui::Color32::from_rgb(90, 180, 90), format!("+ {}", change.after));
+                                        });
+                                    });
+                                }
+                                if changes.len() > MAX_SHOWN {
+                                    ui.weak(format!("… and {} more", changes.len() - MAX_SHOWN));
+                                }
+                            });
+                    }
+                    if total == 0 {
+                        ui.weak("The replacements don't change any queued file.");
+                    }
+                });
+            });
+        if !open {
+            self.replace_preview = None;
+        } else if refresh {
+            self.open_replace_preview();
+        }
+        if convert {
+            self.replace_preview = None;
+            self.request_convert();
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+/// Tags whose contents ammonia always strips. Allowing them as well would make
+/// the builder panic, and letting scripts through defeats the point anyway.
+const ALWAYS_BLOCKED_TAGS: &[&str] = &["script", "style"];
+
+/// Tags the converter itself emits beyond ammonia's defaults (the inputs
+/// and labels of code tabs and sidenote toggles).
+const GENERATED_TAGS: &[&str] = &["input", "label"];
+
+/// Attributes the converter itself emits (heading anchors, code-block
+/// decorations, tabs and footnotes), kept so sanitizing doesn't undo other output
+/// options.
+const GENERATED_ATTRIBUTES: &[(&str, &str)] = &[
+    ("h1", "id"),
+    ("h2", "id"),
+    ("h3", "id"),
+    ("h4", "id"),
+    ("h5", "id"),
+    ("h6", "id"),
+    ("div", "class"),
+    ("div", "data-lang"),
+    ("span", "class"),
+    ("span", "role"),
+    ("code", "class"),
+    ("input", "type"),
+    ("input", "name"),
+    ("input", "id"),
+    ("input", "checked"),
+    ("input", "class"),
+    ("label", "for"),
+    ("label", "class"),
+];
+
+/// Cleans `html` with ammonia's default policy extended by the user's allowlist.
+///
+/// `attributes` holds `(tag, attribute)` pairs; a tag of `*` allows the
+/// attribute on every element.
+pub fn sanitize_html(html: &str, tags: &[String], attributes: &[(String, String)]) -> String {
+    let extra_tags: HashSet<&str> = tags
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty() && !ALWAYS_BLOCKED_TAGS.contains(&t.to_ascii_lowercase().as_str()))
+        .collect();
+    let mut builder = ammonia::Builder::default();
+    builder.add_tags(extra_tags);
+    builder.add_tags(GENERATED_TAGS);
+    for (tag, attribute) in GENERATED_ATTRIBUTES {
+        builder.add_tag_attributes(tag, [attribute]);
+    }
+    for (tag, attribute) in attributes {
+        let (tag, attribute) = (tag.trim(), attribute.trim());
+        // ammonia manages `rel` itself and refuses to have it allowlisted.
+        if tag.is_empty() || attribute.is_empty() || attribute.eq_ignore_ascii_case("rel") {
+            continue;
+        }
+        if tag == "*" {
+            builder.add_generic_attributes([attribute]);
+        } else {
+            builder.add_tag_attributes(tag, [attribute]);
+        }
+    }
+    builder.clean(html).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_blocks::group_code_tabs;
+    use crate::footnotes::{render_sidenotes, FootnoteMode};
+    use pulldown_cmark::{html, Options, Parser};
+
+    fn render(md: &str) -> String {
+        let mut out = String::new();
+        html::push_html(&mut out, group_code_tabs(Parser::new_ext(md, Options::empty()).collect()).into_iter());
+        out
+    }
+
+    fn render_notes(md: &str, mode: FootnoteMode) -> String {
+        let mut out = String::new();
+        let events = Parser::new_ext(md, Options::ENABLE_FOOTNOTES).collect();
+        html::push_html(&mut out, render_sidenotes(events, mode).into_iter());
+        out
+    }
+
+    #[test]
+    fn code_tabs_survive() {
+        let html = render("```sh tab=\"Shell\"\nls\n```\n```ps tab=\"PowerShell\"\ndir\n```\n");
+        let clean = sanitize_html(&html, &[], &[]);
+        assert!(clean.contains("<input type=\"radio\" name=\"code-tabs-1\" id=\"code-tabs-1-0\" checked"), "{}", clean);
+        assert!(clean.contains("<label for=\"code-tabs-1-1\">PowerShell</label>"), "{}", clean);
+    }
+
+    #[test]
+    fn sidenotes_and_tooltips_survive() {
+        let md = "Text.[^a]\n\n[^a]: The note.\n";
+        let clean = sanitize_html(&render_notes(md, FootnoteMode::Sidenotes), &[], &[]);
+        assert!(clean.contains("<label for=\"sn-1-1\" class=\"sidenote-number\">1</label>"), "{}", clean);
+        assert!(clean.contains("<input type=\"checkbox\" id=\"sn-1-1\" class=\"sidenote-toggle\">"), "{}", clean);
+        let clean = sanitize_html(&render_notes(md, FootnoteMode::Tooltips), &[], &[]);
+        assert!(clean.contains("<span class=\"footnote-tip-text\" role=\"tooltip\">"), "{}", clean);
+    }
+
+    #[test]
+    fn scripts_and_handlers_are_still_removed() {
+        let clean = sanitize_html("<input type=\"radio\" onclick=\"x()\"><script>x()</script>", &["script".to_string()], &[]);
+        assert_eq!(clean, "<input type=\"radio\">");
+    }
+}
@@ -0,0 +1,262 @@
+use crate::limits::Slots;
+use crate::messages::Message;
+use crate::snippets::{self, Snippet, CODE_LANGUAGES};
+use crate::timeout::Cancel;
+use crate::{timing, write_output, AppState, Converted, EntryOptions, Sink};
+use eframe::egui;
+use egui::text::{CCursor, CCursorRange};
+use egui::widgets::text_edit::TextEditState;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long typing has to pause before the preview is rendered again.
+const RENDER_DELAY: Duration = Duration::from_millis(300);
+
+/// Markdown typed or pasted into the scratch editor, which isn't saved.
+#[derive(Default)]
+pub struct Scratch {
+    markdown: String,
+    html: String,
+    /// When the text changed, until the preview catches up.
+    edited_at: Option<Instant>,
+    status: Message,
+    /// Language of the next fenced code block from the toolbar.
+    code_language: String,
+    /// The URL being typed for a link, while its prompt is open.
+    link_url: Option<String>,
+}
+
+impl Scratch {
+    /// Puts `snippet` in at the editor's cursor or around its selection as
+    /// one step the editor's own undo takes back, then gives the editor
+    /// focus again with the cursor where the snippet leaves it.
+    fn insert_snippet(&mut self, ctx: &egui::Context, editor: egui::Id, snippet: Snippet) {
+        let mut state = TextEditState::load(ctx, editor).unwrap_or_default();
+        let end = CCursor::new(self.markdown.chars().count());
+        let before = state.cursor.char_range().unwrap_or(CCursorRange::one(end));
+        let [from, to] = before.sorted();
+        let mut undoer = state.undoer();
+        undoer.add_undo(&(before, self.markdown.clone()));
+        let selection = snippets::insert(&mut self.markdown, from.index..to.index, snippet);
+        let after = CCursorRange::two(CCursor::new(selection.start), CCursor::new(selection.end));
+        undoer.add_undo(&(after, self.markdown.clone()));
+        state.set_undoer(undoer);
+        state.cursor.set_char_range(Some(after));
+        state.store(ctx, editor);
+        ctx.memory_mut(|memory| memory.request_focus(editor));
+        self.edited_at = Some(Instant::now());
+    }
+
+    /// Buttons for the snippets, plus the URL prompt of a link being added.
+    /// Returns the snippet picked, if any.
+    fn toolbar(&mut self, ui: &mut egui::Ui) -> Option<Snippet<'static>> {
+        let mut picked = None;
+        ui.horizontal_wrapped(|ui| {
+            if ui.button(egui::RichText::new("B").strong()).on_hover_text("Bold (Ctrl+B)").clicked() {
+                picked = Some(Snippet::Bold);
+            }
+            if ui.button(egui::RichText::new("I").italics()).on_hover_text("Italic (Ctrl+I)").clicked() {
+                picked = Some(Snippet::Italic);
+            }
+            if ui.button("🔗 Link").on_hover_text("Link around the selection (Ctrl+K)").clicked() {
+                self.link_url = Some(String::new());
+            }
+            if ui.button("🖼 Image").clicked() {
+                picked = Some(Snippet::Image);
+            }
+            if ui.button("``` Code").on_hover_text("Fenced code block around the selection").clicked() {
+                picked = Some(Snippet::CodeBlock(""));
+            }
+            egui::ComboBox::from_id_source("scratch_code_language")
+                .selected_text(match self.code_language.as_str() {
+                    "" => "no language",
+                    language => language,
+                })
+                .show_ui(ui, |ui| {
+                    for language in CODE_LANGUAGES {
+                        let label = if language.is_empty() { "no language" } else { language };
+                        ui.selectable_value(&mut self.code_language, language.to_string(), label);
+                    }
+                });
+            if ui.button("▦ Table").on_hover_text("Three columns, a header and two rows").clicked() {
+                picked = Some(Snippet::Table);
+            }
+            if ui.button("☑ Task").clicked() {
+                picked = Some(Snippet::TaskItem);
+            }
+            if ui.button("¹ Footnote").on_hover_text("Reference here, definition at the end").clicked() {
+                picked = Some(Snippet::Footnote);
+            }
+            if ui.button("🚨 Alert").on_hover_text("A > [!NOTE] callout").clicked() {
+                picked = Some(Snippet::Alert);
+            }
+        });
+        picked
+    }
+}
+
+/// Where the text is put for the converter, which reads files. Relative
+/// links and images therefore resolve against the temp folder.
+fn scratch_input() -> PathBuf {
+    std::env::temp_dir().join(format!("md2html-scratch-{}.md", std::process::id()))
+}
+
+impl AppState {
+    /// Converts the scratch text with the current options, in memory and
+    /// without running external tools; see [`Sink::Scratch`].
+    fn render_scratch(&mut self) {
+        let Some(markdown) = self.scratch.as_ref().map(|scratch| scratch.markdown.clone()) else {
+            return;
+        };
+        let input = scratch_input();
+        let mut html = Vec::new();
+        let mut log = Vec::new();
+        let result = fs::write(&input, markdown)
+            .map_err(|e| Message::error(format!("Failed to write {}: {}", input.display(), e)))
+            .and_then(|_| {
+                let io_slots = Slots::new(self.io_worker_count());
+                self.convert_file(&input, Sink::Scratch(&mut html), EntryOptions::default(), &io_slots, &Cancel::default(), &mut log)
+            });
+        let Some(scratch) = self.scratch.as_mut() else {
+            return;
+        };
+        scratch.edited_at = None;
+        match result {
+            Ok(Converted { timings, .. }) => {
+                scratch.html = String::from_utf8_lossy(&html).into_owned();
+                scratch.status = Message::success(format!("Rendered in {}", timing::format_duration(timings.total())));
+            }
+            Err(message) => scratch.status = message,
+        }
+    }
+
+    fn save_scratch(&mut self) {
+        if self.scratch.as_ref().is_some_and(|scratch| scratch.edited_at.is_some()) {
+            self.render_scratch();
+        }
+        let Some(scratch) = self.scratch.as_mut() else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("HTML", &["html", "htm"])
+            .set_file_name("scratch.html")
+            .save_file()
+        else {
+            return;
+        };
+        scratch.status = match write_output(&path, |writer| writer.write_all(scratch.html.as_bytes())) {
+            Ok(()) => Message::info("💾", format!("Saved {}", path.display())),
+            Err(message) => message,
+        };
+    }
+
+    /// Markdown on the left, the HTML it converts to on the right, updated
+    /// shortly after typing stops.
+    pub(crate) fn scratch_window(&mut self, ctx: &egui::Context) {
+        let style = self.message_style;
+        let Some(scratch) = self.scratch.as_mut() else {
+            return;
+        };
+        let mut open = true;
+        let mut save = false;
+        egui::Window::new("📝 Scratch editor")
+            .open(&mut open)
+            .default_size([900.0, 560.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("💾 Save HTML as…").clicked() {
+                        save = true;
+                    }
+                    if ui.button("📋 Copy HTML").clicked() {
+                        ui.output_mut(|o| o.copied_text = scratch.html.clone());
+                    }
+                    ui.weak(style.format(&scratch.status));
+                });
+                let editor = egui::Id::new("scratch_markdown_editor");
+                let mut picked = scratch.toolbar(ui);
+                let mut link = None;
+                if let Some(url) = &mut scratch.link_url {
+                    ui.horizontal(|ui| {
+                        ui.label("🔗 URL:");
+                        let field = ui.add(egui::TextEdit::singleline(url).hint_text("https://…"));
+                        if !field.has_focus() && !field.lost_focus() {
+                            field.request_focus();
+                        }
+                        if field.lost_focus() {
+                            link = Some(ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                        }
+                        if ui.button("Cancel").clicked() {
+                            link = Some(false);
+                        }
+                    });
+                }
+                match link {
+                    Some(true) => {
+                        let url = scratch.link_url.take().unwrap_or_default();
+                        scratch.insert_snippet(ui.ctx(), editor, Snippet::Link(&url));
+                    }
+                    Some(false) => {
+                        scratch.link_url = None;
+                        ui.ctx().memory_mut(|memory| memory.request_focus(editor));
+                    }
+                    None => {}
+                }
+                if ui.memory(|memory| memory.has_focus(editor)) {
+                    let shortcut = |key| egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, key);
+                    if ui.input_mut(|i| i.consume_shortcut(&shortcut(egui::Key::B))) {
+                        picked = Some(Snippet::Bold);
+                    } else if ui.input_mut(|i| i.consume_shortcut(&shortcut(egui::Key::I))) {
+                        picked = Some(Snippet::Italic);
+                    } else if ui.input_mut(|i| i.consume_shortcut(&shortcut(egui::Key::K))) {
+                        scratch.link_url = Some(String::new());
+                        ui.ctx().request_repaint();
+                    }
+                }
+                match picked {
+                    Some(Snippet::CodeBlock(_)) => {
+                        let language = scratch.code_language.clone();
+                        scratch.insert_snippet(ui.ctx(), editor, Snippet::CodeBlock(&language));
+                    }
+                    Some(snippet) => scratch.insert_snippet(ui.ctx(), editor, snippet),
+                    None => {}
+                }
+                ui.columns(2, |columns| {
+                    egui::ScrollArea::vertical().id_source("scratch_markdown").show(&mut columns[0], |ui| {
+                        let edit = ui.add(
+                            egui::TextEdit::multiline(&mut scratch.markdown)
+                                .id(editor)
+                                .code_editor()
+                                .desired_rows(24)
+                                .desired_width(f32::INFINITY)
+                                .hint_text("Type or paste Markdown…"),
+                        );
+                        if edit.changed() {
+                            scratch.edited_at = Some(Instant::now());
+                        }
+                    });
+                    egui::ScrollArea::vertical().id_source("scratch_html").show(&mut columns[1], |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut scratch.html.as_str())
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                });
+            });
+        let waited = scratch.edited_at.map(|at| at.elapsed());
+        if !open {
+            self.scratch = None;
+            let _ = fs::remove_file(scratch_input());
+            return;
+        }
+        match waited {
+            Some(waited) if waited >= RENDER_DELAY => self.render_scratch(),
+            Some(waited) => ctx.request_repaint_after(RENDER_DELAY - waited),
+            None => {}
+        }
+        if save {
+            self.save_scratch();
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Writes a script that converts each `(input, output)` pair with
+/// `--convert`, plus the options file it passes via `--options`.
+///
+/// A `.ps1` path gets a PowerShell script, anything else a POSIX shell one.
+/// The options file sits next to the script and is found relative to it, so
+/// the pair can be committed together. Returns the options file's path.
+pub fn export_script(script: &Path, pairs: &[(PathBuf, PathBuf)], options_json: &str) -> io::Result<PathBuf> {
+    let stem = script.file_stem().unwrap_or_default().to_string_lossy();
+    let options_name = format!("{}.options.json", stem);
+    let options = script.with_file_name(&options_name);
+    let powershell = script.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ps1"));
+    let text = if powershell {
+        powershell_script(&options_name, pairs)
+    } else {
+        shell_script(&options_name, pairs)
+    };
+    fs::write(&options, options_json)?;
+    fs::write(script, text)?;
+    #[cfg(unix)]
+    if !powershell {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(script, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(options)
+}
+
+fn shell_script(options_name: &str, pairs: &[(PathBuf, PathBuf)]) -> String {
+    let mut text = String::from("#!/bin/sh\n# Generated by md2html_gui. Set MD2HTML to use a different binary.\nset -e\n");
+    text += "MD2HTML=\"${MD2HTML:-md2html_gui}\"\n";
+    text += &format!("OPTIONS=\"$(dirname \"$0\")/{}\"\n", options_name);
+    for (input, output) in pairs {
+        text += &format!(
+            "\"$MD2HTML\" --options \"$OPTIONS\" --convert {} {}\n",
+            sh_quote(&input.to_string_lossy()),
+            sh_quote(&output.to_string_lossy())
+        );
+    }
+    text
+}
+
+fn powershell_script(options_name: &str, pairs: &[(PathBuf, PathBuf)]) -> String {
+    let mut text = String::from("# Generated by md2html_gui. Set $env:MD2HTML to use a different binary.\n");
+    text += "$ErrorActionPreference = 'Stop'\n";
+    text += "$md2html = if ($env:MD2HTML) { $env:MD2HTML } else { 'md2html_gui' }\n";
+    text += &format!("$options = Join-Path $PSScriptRoot {}\n", ps_quote(options_name));
+    for (input, output) in pairs {
+        text += &format!(
+            "& $md2html --options $options --convert {} {}\nif ($LASTEXITCODE -ne 0) {{ exit $LASTEXITCODE }}\n",
+            ps_quote(&input.to_string_lossy()),
+            ps_quote(&output.to_string_lossy())
+        );
+    }
+    text
+}
+
+fn sh_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+fn ps_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
@@ -0,0 +1,156 @@
+use std::fs;
+use std::net::UdpSocket;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use tiny_http::{Header, Response};
+
+/// A static file server for the output folder, stopped when dropped.
+pub struct Server {
+    server: Arc<tiny_http::Server>,
+    thread: Option<JoinHandle<()>>,
+    pub root: PathBuf,
+    pub port: u16,
+    /// Where to open the pages: this machine, then its LAN address if it
+    /// can be found.
+    pub urls: Vec<String>,
+}
+
+impl Server {
+    /// Serves `root` on every interface, so other devices on the network
+    /// can open the pages too.
+    pub fn start(root: PathBuf, port: u16) -> Result<Self, String> {
+        let server = Arc::new(tiny_http::Server::http(("0.0.0.0", port)).map_err(|e| format!("port {}: {}", port, e))?);
+        let thread = {
+            let server = Arc::clone(&server);
+            let root = root.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let found = resolve(&root, request.url()).and_then(|path| Some((fs::File::open(&path).ok()?, path)));
+                    let _ = match found {
+                        Some((file, path)) => {
+                            let mut response = Response::from_file(file);
+                            if let Ok(header) = Header::from_bytes("Content-Type", content_type(&path)) {
+                                response.add_header(header);
+                            }
+                            request.respond(response)
+                        }
+                        None => request.respond(Response::from_string("Not found").with_status_code(404)),
+                    };
+                }
+            })
+        };
+        let mut urls = vec![format!("http://localhost:{}/", port)];
+        if let Some(ip) = lan_address() {
+            urls.push(format!("http://{}:{}/", ip, port));
+        }
+        Ok(Self { server, thread: Some(thread), root, port, urls })
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The file under `root` that `url` names, or `None` if it would escape
+/// `root`. Folders serve their `index.html`.
+fn resolve(root: &Path, url: &str) -> Option<PathBuf> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let mut file = root.to_path_buf();
+    for segment in percent_decode(path).split('/') {
+        let mut components = Path::new(segment).components();
+        match (components.next(), components.next()) {
+            (None, _) => {}
+            (Some(Component::Normal(name)), None) => file.push(name),
+            _ => return None,
+        }
+    }
+    if file.is_dir() {
+        file.push("index.html");
+    }
+    Some(file)
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn content_type(path: &Path) -> &'static str {
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "woff2" => "font/woff2",
+        "woff" => "font/woff",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "txt" | "md" => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The address other machines on the network reach this one at. Connecting
+/// a UDP socket only picks a route; nothing is sent.
+fn lan_address() -> Option<std::net::IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    Some(socket.local_addr().ok()?.ip()).filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_map_to_files_under_the_root() {
+        let root = Path::new("/srv/site");
+        assert_eq!(resolve(root, "/docs/a%20b.html?x=1#top"), Some(root.join("docs/a b.html")));
+        assert_eq!(resolve(root, "//img//logo.png"), Some(root.join("img/logo.png")));
+    }
+
+    #[test]
+    fn urls_cannot_escape_the_root() {
+        let root = Path::new("/srv/site");
+        for url in ["/../secret", "/docs/../../secret", "/%2e%2e/secret", "/docs/%2E%2E%2F..%2Fsecret", "/./a.html"] {
+            assert_eq!(resolve(root, url), None, "{}", url);
+        }
+    }
+
+    #[test]
+    fn folders_serve_their_index() {
+        let root = std::env::temp_dir().join(format!("md2html-serve-test-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("guide")).unwrap();
+        let resolved = resolve(&root, "/guide/");
+        std::fs::remove_dir_all(&root).ok();
+        assert_eq!(resolved, Some(root.join("guide/index.html")));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::dates;
+use crate::messages::Message;
+use crate::paths::{self, relative_path, to_url_path};
+use crate::toc::escape_text;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Writes a sitemap listing `pages`, with URLs built from `base_url` and each
+/// page's path relative to the sitemap's own directory.
+///
+/// Pages outside that directory can't be given a sensible URL and are
+/// returned as warnings instead of being listed.
+pub fn write_sitemap(sitemap: &Path, base_url: &str, pages: &[PathBuf]) -> Result<Vec<Message>, String> {
+    let sitemap = paths::absolute(sitemap);
+    let root = sitemap.parent().unwrap_or(Path::new("/"));
+    let base_url = base_url.trim().trim_end_matches('/');
+    let mut warnings = Vec::new();
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for page in pages {
+        let page = paths::absolute(page);
+        let Some(relative) = relative_path(root, &page).filter(|r| !r.starts_with("..")) else {
+            warnings.push(Message::warning(format!("Sitemap: {} is outside {}, skipped", page.display(), root.display())));
+            continue;
+        };
+        xml += &format!("  <url>\n    <loc>{}/{}</loc>\n", escape_text(base_url), escape_text(&to_url_path(&relative)));
+        if let Some(modified) = dates::file_modified(&page) {
+            xml += &format!("    <lastmod>{}</lastmod>\n", dates::iso(&modified));
+        }
+        xml += "  </url>\n";
+    }
+    xml += "</urlset>\n";
+    fs::write(&sitemap, xml).map_err(|e| format!("failed to write {}: {}", sitemap.display(), e))?;
+    Ok(warnings)
+}
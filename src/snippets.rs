@@ -0,0 +1,210 @@
+use std::ops::Range;
+
+/// Languages offered for fenced code blocks; the first means none.
+pub const CODE_LANGUAGES: [&str; 13] =
+    ["", "bash", "c", "cpp", "css", "html", "java", "javascript", "json", "python", "rust", "sql", "yaml"];
+
+/// Markup the scratch editor's toolbar puts into the text.
+#[derive(Clone, Copy)]
+pub enum Snippet<'a> {
+    Bold,
+    Italic,
+    /// A link to this URL around the selection.
+    Link(&'a str),
+    Image,
+    /// A fenced block in this language, empty for none.
+    CodeBlock(&'a str),
+    /// Three columns: a header row and two empty rows.
+    Table,
+    TaskItem,
+    /// A numbered reference, with its definition at the end of the text.
+    Footnote,
+    /// A `> [!NOTE]` blockquote, which alert rendering turns into a callout.
+    Alert,
+}
+
+/// Puts `snippet` into `text`, wrapping the characters in `selection` where
+/// it can and inserting at the cursor otherwise. Positions count characters,
+/// like the editor's cursor, and the returned range is what to select next:
+/// the wrapped text, a placeholder to type over, or an empty cursor.
+pub fn insert(text: &mut String, selection: Range<usize>, snippet: Snippet) -> Range<usize> {
+    let start = byte_offset(text, selection.start);
+    let end = byte_offset(text, selection.end.max(selection.start));
+    let selected = text[start..end].to_string();
+    // What replaces which bytes, and the bytes to select afterwards.
+    let (replace, inserted, select) = match snippet {
+        Snippet::Bold => wrap(start, &selected, "**", "**"),
+        Snippet::Italic => wrap(start, &selected, "*", "*"),
+        Snippet::Link(url) => {
+            let url = if url.trim().is_empty() { "https://" } else { url.trim() };
+            let inserted = format!("[{}]({})", selected, url);
+            let select = match selected.is_empty() {
+                // Inside the brackets, for the link text.
+                true => start + 1..start + 1,
+                false => start + inserted.len()..start + inserted.len(),
+            };
+            (start..end, inserted, select)
+        }
+        Snippet::Image => {
+            let inserted = format!("![{}](image.png)", selected);
+            let path = start + selected.len() + 4;
+            (start..end, inserted, path..path + "image.png".len())
+        }
+        Snippet::CodeBlock(language) => {
+            let before = block_break(&text[..start]);
+            let body = selected.strip_suffix('\n').unwrap_or(&selected);
+            let fence = format!("{}```{}\n", before, language.trim());
+            let body_start = start + fence.len();
+            let inserted = format!("{}{}\n```\n{}", fence, body, after_break(&text[end..]));
+            (start..end, inserted, body_start..body_start + body.len())
+        }
+        Snippet::Table => {
+            let before = block_break(&text[..end]);
+            let header_start = end + before.len() + 2;
+            let inserted = format!(
+                "{}| Column 1 | Column 2 | Column 3 |\n| --- | --- | --- |\n|  |  |  |\n|  |  |  |\n{}",
+                before,
+                after_break(&text[end..])
+            );
+            (end..end, inserted, header_start..header_start + "Column 1".len())
+        }
+        Snippet::TaskItem => {
+            let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+            // A selection ending right after a newline doesn't take in the next line.
+            let last = if end > start && text[..end].ends_with('\n') { end - 1 } else { end };
+            let line_end = text[last..].find('\n').map_or(text.len(), |i| last + i);
+            let marked: String =
+                text[line_start..line_end].split('\n').map(|line| format!("- [ ] {}", line)).collect::<Vec<_>>().join("\n");
+            let select = match selected.is_empty() {
+                true => start + 6..start + 6,
+                false => line_start..line_start + marked.len(),
+            };
+            (line_start..line_end, marked, select)
+        }
+        Snippet::Footnote => {
+            let number = (1..).find(|n| !text.contains(&format!("[^{}]", n))).unwrap_or(1);
+            let reference = format!("[^{}]", number);
+            let rest = &text[end..];
+            let tail = match rest.trim_end() {
+                "" => "\n\n",
+                _ if rest.ends_with("\n\n") => "",
+                _ if rest.ends_with('\n') => "\n",
+                _ => "\n\n",
+            };
+            // The definition goes after everything, so the rest is put back before it.
+            let inserted = format!("{}{}{}[^{}]: ", reference, rest, tail, number);
+            let cursor = end + inserted.len();
+            (end..text.len(), inserted, cursor..cursor)
+        }
+        Snippet::Alert => {
+            let before = block_break(&text[..start]);
+            let quoted: String = match selected.is_empty() {
+                true => "> ".to_string(),
+                false => selected.trim_end_matches('\n').lines().map(|line| format!("> {}", line)).collect::<Vec<_>>().join("\n"),
+            };
+            let body_start = start + before.len() + "> [!NOTE]\n".len();
+            let inserted = format!("{}> [!NOTE]\n{}\n{}", before, quoted, after_break(&text[end..]));
+            (start..end, inserted, body_start..body_start + quoted.len())
+        }
+    };
+    text.replace_range(replace, &inserted);
+    char_index(text, select.start)..char_index(text, select.end)
+}
+
+/// `selected` between `open` and `close`, kept selected, or the cursor
+/// between them when nothing was selected.
+fn wrap(start: usize, selected: &str, open: &str, close: &str) -> (Range<usize>, String, Range<usize>) {
+    let inner = start + open.len();
+    (start..start + selected.len(), format!("{}{}{}", open, selected, close), inner..inner + selected.len())
+}
+
+/// What has to go before a block so it starts a paragraph of its own.
+fn block_break(before: &str) -> &'static str {
+    if before.is_empty() || before.ends_with("\n\n") {
+        ""
+    } else if before.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    }
+}
+
+/// What has to go after a block so the following text isn't part of it.
+fn after_break(after: &str) -> &'static str {
+    match after.is_empty() || after.starts_with('\n') {
+        true => "",
+        false => "\n",
+    }
+}
+
+fn byte_offset(text: &str, chars: usize) -> usize {
+    text.char_indices().nth(chars).map_or(text.len(), |(i, _)| i)
+}
+
+fn char_index(text: &str, byte: usize) -> usize {
+    text[..byte].chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(text: &str, selection: Range<usize>, snippet: Snippet) -> (String, Range<usize>) {
+        let mut text = text.to_string();
+        let selection = insert(&mut text, selection, snippet);
+        (text, selection)
+    }
+
+    #[test]
+    fn bold_and_italic_wrap_the_selection() {
+        assert_eq!(apply("make it loud", 8..12, Snippet::Bold), ("make it **loud**".to_string(), 10..14));
+        assert_eq!(apply("ab", 1..1, Snippet::Italic), ("a**b".to_string(), 2..2));
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        assert_eq!(apply("héllo wörld", 6..11, Snippet::Bold), ("héllo **wörld**".to_string(), 8..13));
+    }
+
+    #[test]
+    fn link_and_image_leave_the_right_part_selected() {
+        assert_eq!(apply("see docs", 4..8, Snippet::Link("https://a.b")), ("see [docs](https://a.b)".to_string(), 23..23));
+        assert_eq!(apply("", 0..0, Snippet::Link("")), ("[](https://)".to_string(), 1..1));
+        assert_eq!(apply("", 0..0, Snippet::Image), ("![](image.png)".to_string(), 4..13));
+    }
+
+    #[test]
+    fn code_block_starts_its_own_paragraph() {
+        let (text, selection) = apply("Intro\nfn main() {}", 6..18, Snippet::CodeBlock("rust"));
+        assert_eq!(text, "Intro\n\n```rust\nfn main() {}\n```\n");
+        assert_eq!(&text[selection], "fn main() {}");
+    }
+
+    #[test]
+    fn table_goes_after_the_cursor_line() {
+        let (text, selection) = apply("Text", 4..4, Snippet::Table);
+        assert!(text.starts_with("Text\n\n| Column 1 | Column 2 | Column 3 |\n| --- | --- | --- |\n"));
+        assert_eq!(text.lines().filter(|line| line.starts_with('|')).count(), 4);
+        assert_eq!(&text[selection], "Column 1");
+    }
+
+    #[test]
+    fn task_item_marks_each_selected_line() {
+        assert_eq!(apply("milk\neggs\n", 2..7, Snippet::TaskItem), ("- [ ] milk\n- [ ] eggs\n".to_string(), 0..21));
+        assert_eq!(apply("milk", 4..4, Snippet::TaskItem), ("- [ ] milk".to_string(), 10..10));
+    }
+
+    #[test]
+    fn footnote_adds_the_next_free_number_and_its_definition() {
+        let (text, selection) = apply("One[^1].\n\n[^1]: First.\n", 8..8, Snippet::Footnote);
+        assert_eq!(text, "One[^1].[^2]\n\n[^1]: First.\n\n[^2]: ");
+        assert_eq!(selection, 34..34);
+    }
+
+    #[test]
+    fn alert_quotes_the_selection() {
+        let (text, selection) = apply("Careful\nnow", 0..11, Snippet::Alert);
+        assert_eq!(text, "> [!NOTE]\n> Careful\n> now\n");
+        assert_eq!(&text[selection], "> Careful\n> now");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::toc::{escape_attr, escape_text, Heading};
+use pulldown_cmark::{CowStr, Event, Tag};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Previous/next links at the bottom of each page of a split document.
+pub const PAGER_CSS: &str = r#"
+.md2html-pager { display: flex; justify-content: space-between; gap: 1em; margin-top: 3em; padding-top: 1em; border-top: 1px solid rgba(127, 127, 127, 0.3); }
+.md2html-pager .next { margin-left: auto; text-align: right; }
+"#;
+
+/// One file of a split document.
+pub struct Page {
+    pub output: PathBuf,
+    /// Text of the heading the page starts with; `None` for an untitled intro.
+    pub title: Option<String>,
+    /// The page's slice of the event list.
+    pub events: Range<usize>,
+    /// Ids of the headings on this page.
+    ids: Vec<String>,
+}
+
+/// Cuts the document before every heading of `level` or higher, e.g. every
+/// `h1` and `h2` for level 2. The first page keeps `output`; the others are
+/// named after their heading, `guide-installation.html` next to `guide.html`.
+///
+/// Headings must already have ids from [`crate::toc::assign_heading_ids`].
+/// Returns a single page when there is nothing to split at.
+pub fn plan_pages(events: &[Event], headings: &[Heading], level: u32, output: &Path) -> Vec<Page> {
+    let mut starts = Vec::new();
+    let mut next = headings.iter().peekable();
+    for (i, event) in events.iter().enumerate() {
+        let (Event::Html(html), Some(heading)) = (event, next.peek()) else {
+            continue;
+        };
+        if html.starts_with(&format!("<h{} id=\"{}\"", heading.level, escape_attr(&heading.id))) {
+            starts.push((i, *heading));
+            next.next();
+        }
+    }
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = output.extension().map_or("html".into(), |e| e.to_string_lossy());
+    let mut pages = vec![Page { output: output.to_path_buf(), title: None, events: 0..events.len(), ids: Vec::new() }];
+    for &(i, heading) in &starts {
+        if heading.level > level {
+            continue;
+        }
+        let Some(current) = pages.last_mut() else {
+            continue;
+        };
+        if i == 0 || events[current.events.start..i].iter().all(is_blank) {
+            // Nothing before this heading on the current page, so it starts here.
+            current.title = Some(heading.text.clone());
+            continue;
+        }
+        current.events.end = i;
+        pages.push(Page {
+            output: output.with_file_name(format!("{}-{}.{}", stem, heading.id, extension)),
+            title: Some(heading.text.clone()),
+            events: i..events.len(),
+            ids: Vec::new(),
+        });
+    }
+    for (i, heading) in starts {
+        if let Some(page) = pages.iter_mut().find(|page| page.events.contains(&i)) {
+            page.ids.push(heading.id.clone());
+        }
+    }
+    pages
+}
+
+fn is_blank(event: &Event) -> bool {
+    match event {
+        Event::Text(text) | Event::Html(text) => text.trim().is_empty(),
+        Event::SoftBreak | Event::HardBreak => true,
+        _ => false,
+    }
+}
+
+fn file_name(page: &Page) -> String {
+    page.output.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+/// Where a link to heading `id` points from another page.
+pub fn heading_href(pages: &[Page], id: &str) -> String {
+    match pages.iter().find(|page| page.ids.iter().any(|page_id| page_id == id)) {
+        Some(page) => format!("{}#{}", file_name(page), id),
+        None => format!("#{}", id),
+    }
+}
+
+/// Points `#id` links at the page the heading ended up on.
+pub fn link_across_pages(events: &mut [Event], pages: &[Page]) {
+    let page_of: HashMap<&str, usize> = pages
+        .iter()
+        .enumerate()
+        .flat_map(|(p, page)| page.ids.iter().map(move |id| (id.as_str(), p)))
+        .collect();
+    for (p, page) in pages.iter().enumerate() {
+        for event in &mut events[page.events.clone()] {
+            let Event::Start(Tag::Link(_, dest, _)) = event else {
+                continue;
+            };
+            let Some(target) = dest.strip_prefix('#').and_then(|id| page_of.get(id)).filter(|&&target| target != p)
+            else {
+                continue;
+            };
+            *dest = CowStr::from(format!("{}{}", file_name(&pages[*target]), dest));
+        }
+    }
+}
+
+/// Previous/next links for page `i`.
+pub fn pager(pages: &[Page], i: usize) -> String {
+    let label = |page: &Page| escape_text(page.title.as_deref().unwrap_or("Start"));
+    let mut html = String::from("<nav class=\"md2html-pager\">");
+    if let Some(prev) = i.checked_sub(1).and_then(|p| pages.get(p)) {
+        html += &format!("<a class=\"prev\" rel=\"prev\" href=\"{}\">← {}</a>", escape_attr(&file_name(prev)), label(prev));
+    }
+    if let Some(next) = pages.get(i + 1) {
+        html += &format!("<a class=\"next\" rel=\"next\" href=\"{}\">{} →</a>", escape_attr(&file_name(next)), label(next));
+    }
+    html + "</nav>\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toc::assign_heading_ids;
+    use pulldown_cmark::Parser;
+
+    fn plan(md: &str, level: u32) -> (Vec<Event<'_>>, Vec<Page>) {
+        let mut events: Vec<Event> = Parser::new(md).collect();
+        let headings = assign_heading_ids(&mut events);
+        let pages = plan_pages(&events, &headings, level, Path::new("out/guide.html"));
+        (events, pages)
+    }
+
+    fn outputs(pages: &[Page]) -> Vec<String> {
+        pages.iter().map(|page| page.output.display().to_string()).collect()
+    }
+
+    #[test]
+    fn splits_before_headings_up_to_the_level() {
+        let (_, pages) = plan("Intro\n\n# Setup\n\ntext\n\n## Linux\n\nmore\n\n# Usage\n\nend\n", 1);
+        assert_eq!(outputs(&pages), ["out/guide.html", "out/guide-setup.html", "out/guide-usage.html"]);
+        assert_eq!(pages[0].title, None);
+        assert_eq!(pages[1].title.as_deref(), Some("Setup"));
+        assert_eq!(heading_href(&pages, "linux"), "guide-setup.html#linux");
+        let (_, pages) = plan("# Setup\n\ntext\n\n## Linux\n\nmore\n", 2);
+        assert_eq!(outputs(&pages), ["out/guide.html", "out/guide-linux.html"]);
+    }
+
+    #[test]
+    fn a_leading_heading_titles_the_first_page() {
+        let (_, pages) = plan("# Guide\n\nintro\n\n# Next\n\nend\n", 1);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].title.as_deref(), Some("Guide"));
+        assert_eq!(pages[0].output, Path::new("out/guide.html"));
+        let (_, pages) = plan("# Only\n\ntext\n", 1);
+        assert_eq!(pages.len(), 1);
+    }
+
+    #[test]
+    fn links_and_pager_point_across_pages() {
+        let (mut events, pages) = plan("See [usage](#usage).\n\n# Usage\n\nBack to [top](#usage).\n", 1);
+        link_across_pages(&mut events, &pages);
+        let dests: Vec<String> = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link(_, dest, _)) => Some(dest.to_string()),
+                _ => None,
+            })
+            .collect();
+        // Only the link from the other page needs the file name.
+        assert_eq!(dests, ["guide-usage.html#usage", "#usage"]);
+        assert_eq!(
+            pager(&pages, 0),
+            "<nav class=\"md2html-pager\"><a class=\"next\" rel=\"next\" href=\"guide-usage.html\">Usage →</a></nav>\n"
+        );
+        assert_eq!(
+            pager(&pages, 1),
+            "<nav class=\"md2html-pager\"><a class=\"prev\" rel=\"prev\" href=\"guide.html\">← Start</a></nav>\n"
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use crate::frontmatter::FrontMatter;
+use crate::AppState;
+use crate::messages::Message;
+use eframe::egui;
+use std::path::{Path, PathBuf};
+
+impl AppState {
+    /// The stylesheet a document picks for itself in front matter, used
+    /// instead of the selected CSS file: `style: dark` names one of the
+    /// configured presets, `css: theme.css` is a path relative to the file.
+    /// An unknown preset is logged and leaves the global stylesheet.
+    pub(crate) fn document_css(&self, front_matter: Option<&FrontMatter>, input: &Path, log: &mut Vec<Message>) -> Option<PathBuf> {
+        let fm = front_matter?;
+        if let Some(name) = fm.get_str("style") {
+            let preset = self.css_presets.iter().find(|(preset, _)| preset.trim().eq_ignore_ascii_case(name));
+            match preset {
+                Some((_, path)) => return Some(path.clone()),
+                None => log.push(Message::warning(format!("{}: no CSS preset named \"{}\"", input.display(), name))),
+            }
+        }
+        let relative = fm.get_str("css")?;
+        Some(input.parent().unwrap_or(Path::new("")).join(relative))
+    }
+
+    pub(crate) fn css_presets_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(format!("🎭 CSS presets ({})", self.css_presets.len())).show(ui, |ui| {
+            ui.weak("Front matter style: <name> picks one for that file; css: <path> picks any file.");
+            let mut remove = None;
+            for (i, (name, path)) in self.css_presets.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(name).hint_text("dark").desired_width(100.0));
+                    if ui.button("🖌").on_hover_text("Choose the stylesheet").clicked() {
+                        if let Some(css) = rfd::FileDialog::new().add_filter("CSS", &["css"]).pick_file() {
+                            *path = css;
+                        }
+                    }
+                    ui.monospace(path.display().to_string());
+                    if ui.small_button("❌").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                self.css_presets.remove(i);
+            }
+            if ui.button("➕ Add preset").clicked() {
+                if let Some(css) = rfd::FileDialog::new().add_filter("CSS", &["css"]).pick_file() {
+                    let name = css.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+                    self.css_presets.push((name, css));
+                }
+            }
+        });
+    }
+}
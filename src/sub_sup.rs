@@ -0,0 +1,100 @@
+use crate::prose;
+use std::borrow::Cow;
+
+/// Rewrites `H~2~O` and `E=mc^2^` into `<sub>` and `<sup>` tags before
+/// parsing, Pandoc style: the text between the marks can't contain spaces.
+///
+/// Doubled tildes are left for strikethrough, and code blocks, inline code
+/// spans, link destinations, raw HTML tags, autolinks and backslash-escaped
+/// marks are left alone.
+pub fn convert_sub_superscript(markdown: &str) -> Cow<'_, str> {
+    if !markdown.contains(['~', '^']) {
+        return Cow::Borrowed(markdown);
+    }
+    Cow::Owned(prose::convert_outside_code(markdown, |line, out| {
+        if is_link_definition(line) {
+            out.push_str(line);
+        } else {
+            prose::convert_outside_code_spans(line, out, convert_text);
+        }
+    }))
+}
+
+/// Whether `line` defines a link reference, `[id]: https://…`, whose URL
+/// is left alone. Footnote definitions are prose.
+fn is_link_definition(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with('[')
+        && !trimmed.starts_with("[^")
+        && trimmed.find("]:").is_some_and(|end| !trimmed[1..end].contains(']'))
+}
+
+/// Length of an inline link's `(destination "title")` up to and including
+/// its closing paren, given the text just after the opening one.
+fn destination_len(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i + 1),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn convert_text(text: &str, out: &mut String) {
+    let mut rest = text;
+    while let Some(i) = rest.find(['\\', '~', '^', ']', '<']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let mark = rest.as_bytes()[0];
+        match mark {
+            b'\\' => {
+                let end = rest[1..].chars().next().map_or(1, |c| 1 + c.len_utf8());
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            b'~' if rest.starts_with("~~") => {
+                let run = rest.len() - rest.trim_start_matches('~').len();
+                out.push_str(&rest[..run]);
+                rest = &rest[run..];
+            }
+            b']' => {
+                // A link's or image's destination is a URL, not prose.
+                let end = rest.strip_prefix("](").and_then(destination_len).map_or(1, |len| 2 + len);
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            b'<' => {
+                // Raw HTML tags, comments and `<https://…>` autolinks.
+                let tag = rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!');
+                let end = if tag { rest.find('>').map_or(1, |j| j + 1) } else { 1 };
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            _ => {
+                let tag = if mark == b'~' { "sub" } else { "sup" };
+                let inner = &rest[1..];
+                let end = inner.find(|c: char| c.is_whitespace() || c == mark as char);
+                match end.filter(|&end| end > 0 && inner[end..].starts_with(mark as char)) {
+                    // A closing `~~` belongs to strikethrough, not to us.
+                    Some(end) if !(mark == b'~' && inner[end..].starts_with("~~")) => {
+                        out.push_str(&format!("<{tag}>{}</{tag}>", &inner[..end]));
+                        rest = &inner[end + 1..];
+                    }
+                    _ => {
+                        out.push(mark as char);
+                        rest = inner;
+                    }
+                }
+            }
+        }
+    }
+    out.push_str(rest);
+}
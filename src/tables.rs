@@ -0,0 +1,157 @@
+use crate::inject::ElementRule;
+use crate::messages::Message;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum SortableTables {
+    #[default]
+    Off,
+    /// Every table gets the `sortable` class.
+    All,
+    /// Only tables given the `sortable` class with an element rule.
+    OptedIn,
+}
+
+impl SortableTables {
+    pub const ALL: [SortableTables; 3] = [SortableTables::Off, SortableTables::All, SortableTables::OptedIn];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortableTables::Off => "Off",
+            SortableTables::All => "All tables",
+            SortableTables::OptedIn => "Tables with class \"sortable\"",
+        }
+    }
+
+    /// The element rule that marks every table, when all of them sort.
+    pub fn rule(self) -> Option<ElementRule> {
+        (self == SortableTables::All).then(|| ElementRule {
+            element: "table".to_string(),
+            classes: "sortable".to_string(),
+            attributes: String::new(),
+        })
+    }
+}
+
+/// Cheap scan for something that could become a table.
+pub fn may_have_tables(md: &str) -> bool {
+    md.contains('|') || md.contains("<table")
+}
+
+pub const SORTABLE_CSS: &str = r#"
+table.sortable th[aria-sort] { cursor: pointer; user-select: none; }
+table.sortable th[aria-sort="ascending"]::after { content: " ▲"; font-size: 0.75em; }
+table.sortable th[aria-sort="descending"]::after { content: " ▼"; font-size: 0.75em; }
+"#;
+
+/// Sorts `table.sortable` rows when a header is clicked (or activated with
+/// the keyboard), numerically if every cell in the column is a number.
+/// Tables without a single header row and body, or with merged cells, are
+/// left alone.
+pub const SORTABLE_JS: &str = r##"
+(function () {
+  function text(cell) { return cell ? cell.textContent.trim() : ''; }
+  function number(value) {
+    var cleaned = value.replace(/[,\s%$€£]/g, '');
+    return cleaned !== '' && !isNaN(cleaned) ? parseFloat(cleaned) : null;
+  }
+  document.querySelectorAll('table.sortable').forEach(function (table) {
+    if (!table.tHead || table.tHead.rows.length !== 1 || table.tBodies.length !== 1) return;
+    if (table.querySelector('[rowspan]:not([rowspan="1"]), [colspan]:not([colspan="1"])')) return;
+    var headers = Array.prototype.slice.call(table.tHead.rows[0].cells);
+    headers.forEach(function (header, column) {
+      header.tabIndex = 0;
+      header.setAttribute('aria-sort', 'none');
+      function sort() {
+        var ascending = header.getAttribute('aria-sort') !== 'ascending';
+        var body = table.tBodies[0];
+        var rows = Array.prototype.slice.call(body.rows);
+        var numeric = rows.every(function (row) {
+          var value = text(row.cells[column]);
+          return value === '' || number(value) !== null;
+        });
+        rows.sort(function (a, b) {
+          var x = text(a.cells[column]), y = text(b.cells[column]);
+          var order = numeric
+            ? (number(x) || 0) - (number(y) || 0)
+            : x.localeCompare(y, undefined, { numeric: true, sensitivity: 'base' });
+          return ascending ? order : -order;
+        });
+        rows.forEach(function (row) { body.appendChild(row); });
+        headers.forEach(function (other) { other.setAttribute('aria-sort', 'none'); });
+        header.setAttribute('aria-sort', ascending ? 'ascending' : 'descending');
+      }
+      header.addEventListener('click', sort);
+      header.addEventListener('keydown', function (event) {
+        if (event.key === 'Enter' || event.key === ' ') { event.preventDefault(); sort(); }
+      });
+    });
+  });
+})();
+"##;
+
+/// The cells of every table in `md`, header row first.
+fn table_cells(md: &str, options: Options) -> Vec<Vec<Vec<String>>> {
+    let mut tables = Vec::new();
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut cell: Option<String> = None;
+    for event in Parser::new_ext(md, options) {
+        match event {
+            Event::Start(Tag::Table(_)) => rows.clear(),
+            Event::End(Tag::Table(_)) => tables.push(std::mem::take(&mut rows)),
+            Event::Start(Tag::TableHead | Tag::TableRow) => rows.push(Vec::new()),
+            Event::Start(Tag::TableCell) => cell = Some(String::new()),
+            Event::End(Tag::TableCell) => {
+                if let (Some(text), Some(row)) = (cell.take(), rows.last_mut()) {
+                    row.push(text.trim().to_string());
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(cell) = cell.as_mut() {
+                    cell.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some(cell) = cell.as_mut() {
+                    cell.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    tables
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// `report.table2.csv` for the second table of `report.html`.
+pub fn csv_path(output: &Path, number: usize) -> PathBuf {
+    output.with_extension(format!("table{}.csv", number))
+}
+
+/// Writes each table in `md` to a CSV file next to `output`, returning a
+/// log line per file.
+pub fn write_tables_csv(md: &str, options: Options, input: &Path, output: &Path) -> Vec<Message> {
+    let mut log = Vec::new();
+    for (i, rows) in table_cells(md, options | Options::ENABLE_TABLES).into_iter().enumerate() {
+        let path = csv_path(output, i + 1);
+        let csv: String = rows
+            .iter()
+            .map(|row| row.iter().map(|cell| csv_field(cell)).collect::<Vec<_>>().join(",") + "\r\n")
+            .collect();
+        log.push(match fs::write(&path, csv) {
+            Ok(()) => Message::info("📊", format!("{}: table {} written to {}", input.display(), i + 1, path.display())),
+            Err(e) => Message::warning(format!("{}: failed to write {}: {}", input.display(), path.display(), e)),
+        });
+    }
+    log
+}
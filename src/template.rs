@@ -0,0 +1,41 @@
+/// Values substituted into a user's HTML template.
+pub struct TemplateValues<'a> {
+    /// Already escaped for use as text.
+    pub title: &'a str,
+    /// Meta tags and styles for `<head>`.
+    pub css: &'a str,
+    pub body: &'a str,
+    /// Nested `<ul>` of links to the page's headings.
+    pub toc: &'a str,
+}
+
+/// Whether the template places a table of contents, so headings need ids.
+pub fn wants_toc(template: &str) -> bool {
+    template.contains("{{toc}}") || template.contains("{{ toc }}")
+}
+
+/// Replaces `{{title}}`, `{{css}}`, `{{body}}` and `{{toc}}` in one pass, so
+/// placeholders that appear inside the substituted body are left alone.
+/// Unknown placeholders are kept as written.
+pub fn render(template: &str, values: &TemplateValues) -> String {
+    let mut out = String::with_capacity(template.len() + values.body.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let value = match rest[start + 2..start + 2 + len].trim() {
+            "title" => Some(values.title),
+            "css" => Some(values.css),
+            "body" => Some(values.body),
+            "toc" => Some(values.toc),
+            _ => None,
+        };
+        let end = start + 2 + len + 2;
+        out.push_str(&rest[..start]);
+        out.push_str(value.unwrap_or(&rest[start..end]));
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,133 @@
+use crate::batch_runner::{Job, Outcome};
+use crate::limits::Slots;
+use crate::messages::{Message, Severity};
+use crate::AppState;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+/// Icon of the error of a file that ran out of time. Such a file fails on
+/// its own instead of stopping the batch like other errors do.
+pub const TIMED_OUT: &str = "⏱";
+
+pub fn timed_out(text: impl Into<String>) -> Message {
+    Message::new(Severity::Error, TIMED_OUT, text)
+}
+
+pub fn is_timeout(message: &Message) -> bool {
+    message.icon == TIMED_OUT
+}
+
+/// Set once nobody is waiting for a conversion any more. The conversion
+/// checks it between phases, and hooks, link checks and diagram renders
+/// check it while they run, so an abandoned file stops early and never
+/// writes anything.
+#[derive(Clone, Default)]
+pub struct Cancel(Arc<AtomicBool>);
+
+impl Cancel {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Fails a conversion whose caller has stopped waiting for it.
+    pub fn check(&self) -> Result<(), Message> {
+        match self.is_cancelled() {
+            true => Err(timed_out("abandoned")),
+            false => Ok(()),
+        }
+    }
+}
+
+impl AppState {
+    /// Converts like `convert_job`, but on a thread of its own that's given
+    /// up on after `per_file_timeout_secs`, so a runaway file fails instead
+    /// of holding up the batch.
+    ///
+    /// Threads can't be stopped from outside, so an abandoned one is
+    /// cancelled and stops at its next check, still holding the batch's
+    /// settings and I/O slots rather than copies.
+    pub(crate) fn convert_job_timed(self: &Arc<Self>, job: &Job, in_folder: bool, io_slots: &Arc<Slots>) -> Outcome {
+        if self.per_file_timeout_secs == 0 {
+            return self.convert_job(job, in_folder, io_slots, &Cancel::default());
+        }
+        let cancel = Cancel::default();
+        let (settings, worker_job, worker_slots, worker_cancel) =
+            (Arc::clone(self), job.clone(), Arc::clone(io_slots), cancel.clone());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(settings.convert_job(&worker_job, in_folder, &worker_slots, &worker_cancel));
+        });
+        match receiver.recv_timeout(Duration::from_secs(self.per_file_timeout_secs)) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                cancel.cancel();
+                let message =
+                    format!("{}: gave up after {} s, nothing written", job.input.display(), self.per_file_timeout_secs);
+                (Vec::new(), Err(timed_out(message)))
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => (Vec::new(), Err(Message::error("Conversion thread panicked."))),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::hooks;
+    use std::path::{Path, PathBuf};
+    use std::time::Instant;
+
+    /// A folder for one test, with a hook script in it that sleeps for 30 s
+    /// whatever file it's given. Returns the folder and the hook command.
+    fn scratch_dir(name: &str) -> (PathBuf, String) {
+        let dir = std::env::temp_dir().join(format!("md2html-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let hook = dir.join("hook.sh");
+        std::fs::write(&hook, "sleep 30\n").unwrap();
+        (dir, format!("sh {}", hook.display()))
+    }
+
+    #[test]
+    fn cancelling_kills_a_running_hook() {
+        let (dir, hook) = scratch_dir("cancel-test");
+        let cancel = Cancel::default();
+        let canceller = cancel.clone();
+        let started = Instant::now();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+        let result = hooks::run_pre_process(&hook, Path::new("input.md"), &cancel, &mut Vec::new());
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(result.is_err_and(|e| is_timeout(&e)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn slow_file_times_out_and_writes_nothing() {
+        let (dir, hook) = scratch_dir("timeout-test");
+        let input = dir.join("slow.md");
+        let output = dir.join("slow.html");
+        std::fs::write(&input, "# Slow\n").unwrap();
+        let settings = Arc::new(AppState {
+            per_file_timeout_secs: 1,
+            pre_process_command: hook,
+            ..AppState::default()
+        });
+        let job = Job { index: 0, input: input.clone(), output: output.clone(), range: None, template: None };
+        let started = Instant::now();
+        let (_, result) = settings.convert_job_timed(&job, false, &Arc::new(Slots::new(1)));
+        assert!(result.is_err_and(|e| is_timeout(&e)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        // Give the abandoned worker time to notice and stop.
+        thread::sleep(Duration::from_millis(500));
+        assert!(!output.exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
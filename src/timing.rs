@@ -0,0 +1,92 @@
+use crate::messages::Message;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy)]
+pub enum Phase {
+    /// Reading the file plus replacements, front matter and wiki links.
+    Read,
+    Render,
+    /// Heading ids, badges, diagrams, sanitizing and the sidebar layout.
+    PostProcess,
+    /// Embedded fonts and the user stylesheet.
+    Assets,
+    Write,
+}
+
+const PHASE_NAMES: [&str; 5] = ["read", "parse + render", "post-process", "assets", "write"];
+
+/// Wall-clock time spent in each phase of one conversion.
+#[derive(Clone, Copy, Default)]
+pub struct PhaseTimings {
+    phases: [Duration; 5],
+}
+
+impl PhaseTimings {
+    /// Charges the time since `since` to `phase` and returns the new start.
+    pub fn lap(&mut self, phase: Phase, since: Instant) -> Instant {
+        let now = Instant::now();
+        self.phases[phase as usize] += now - since;
+        now
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().sum()
+    }
+
+    /// e.g. `read 0.4 ms · parse + render 12.0 ms · …`.
+    pub fn summary(&self) -> String {
+        PHASE_NAMES
+            .iter()
+            .zip(self.phases)
+            .map(|(name, d)| format!("{} {}", name, format_duration(d)))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+pub fn format_duration(d: Duration) -> String {
+    if d >= Duration::from_secs(1) {
+        format!("{:.2} s", d.as_secs_f64())
+    } else {
+        format!("{:.1} ms", d.as_secs_f64() * 1000.0)
+    }
+}
+
+/// Log lines for a finished batch: the dominant phase and the slowest files.
+pub fn batch_report(files: &[(&Path, PhaseTimings)]) -> Vec<Message> {
+    let mut totals = PhaseTimings::default();
+    for (_, timings) in files {
+        for (total, d) in totals.phases.iter_mut().zip(timings.phases) {
+            *total += d;
+        }
+    }
+    let total = totals.total();
+    if total.is_zero() {
+        return Vec::new();
+    }
+    let (name, busiest) = PHASE_NAMES
+        .iter()
+        .zip(totals.phases)
+        .max_by_key(|(_, d)| *d)
+        .unwrap_or((&PHASE_NAMES[0], Duration::ZERO));
+    let mut lines = vec![Message::info(
+        "⏱",
+        format!(
+            "{} accounted for {:.0}% of {} ({})",
+            name,
+            busiest.as_secs_f64() / total.as_secs_f64() * 100.0,
+            format_duration(total),
+            totals.summary()
+        ),
+    )];
+    let mut slowest: Vec<_> = files.iter().collect();
+    slowest.sort_by_key(|(_, timings)| std::cmp::Reverse(timings.total()));
+    let slowest: Vec<String> = slowest
+        .iter()
+        .take(3)
+        .map(|(path, timings)| format!("{} ({})", path.display(), format_duration(timings.total())))
+        .collect();
+    lines.push(Message::info("🐢", format!("Slowest: {}", slowest.join(", "))));
+    lines
+}
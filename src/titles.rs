@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How a page is titled from its file name when nothing else names it.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum FilenameTitle {
+    /// `my-notes.md` becomes "My Notes".
+    #[default]
+    Pretty,
+    /// `my-notes.md` becomes "my-notes".
+    Stem,
+    /// The file name as it is, `my-notes.md`.
+    Raw,
+}
+
+impl FilenameTitle {
+    pub const ALL: [FilenameTitle; 3] = [FilenameTitle::Pretty, FilenameTitle::Stem, FilenameTitle::Raw];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FilenameTitle::Pretty => "My Notes",
+            FilenameTitle::Stem => "my-notes",
+            FilenameTitle::Raw => "my-notes.md",
+        }
+    }
+
+    pub fn title(self, input: &Path) -> String {
+        let name = || input.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let stem = || input.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        match self {
+            FilenameTitle::Raw => name(),
+            FilenameTitle::Stem => stem(),
+            FilenameTitle::Pretty => {
+                let words: Vec<String> = stem()
+                    .split(['-', '_', ' '])
+                    .filter(|word| !word.is_empty())
+                    .map(|word| {
+                        let mut chars = word.chars();
+                        chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+                    })
+                    .collect();
+                if words.is_empty() {
+                    name()
+                } else {
+                    words.join(" ")
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use pulldown_cmark::{CowStr, Event, HeadingLevel, Tag};
+use std::collections::{HashMap, HashSet};
+
+pub struct Heading {
+    pub level: u32,
+    pub text: String,
+    pub id: String,
+}
+
+/// GitHub-style anchor slug: lowercase alphanumerics, spaces and dashes become `-`.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() || c == '_' {
+            slug.extend(c.to_lowercase());
+        } else if (c == ' ' || c == '-') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+struct OpenHeading {
+    start: usize,
+    level: u32,
+    explicit_id: Option<String>,
+    classes: Vec<String>,
+    text: String,
+}
+
+fn level_number(level: HeadingLevel) -> u32 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// Gives every heading an id and returns them in document order.
+///
+/// Explicit ids, from `{#id}` heading attributes, are kept as-is along with
+/// their classes; generated ones are made unique with `-1`, `-2`, … suffixes
+/// and never take an id some other heading asked for. The heading tags are replaced with raw HTML because
+/// pulldown-cmark can only carry ids borrowed from the source text.
+pub fn assign_heading_ids(events: &mut [Event]) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut open: Option<OpenHeading> = None;
+    let explicit: HashSet<String> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Start(Tag::Heading(_, Some(id), _)) => Some(id.to_string()),
+            _ => None,
+        })
+        .collect();
+    for i in 0..events.len() {
+        match &events[i] {
+            Event::Start(Tag::Heading(level, id, classes)) => {
+                open = Some(OpenHeading {
+                    start: i,
+                    level: level_number(*level),
+                    explicit_id: id.map(str::to_string),
+                    classes: classes.iter().map(|c| c.to_string()).collect(),
+                    text: String::new(),
+                });
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = open.as_mut() {
+                    heading.text.push_str(text);
+                }
+            }
+            Event::End(Tag::Heading(..)) => {
+                let Some(OpenHeading { start, level, explicit_id, classes, text }) = open.take() else {
+                    continue;
+                };
+                let id = match explicit_id {
+                    Some(id) => id,
+                    None => {
+                        let base = match slugify(&text) {
+                            s if s.is_empty() => "section".to_string(),
+                            s => s,
+                        };
+                        loop {
+                            let count = seen.entry(base.clone()).or_insert(0);
+                            let id = if *count == 0 { base.clone() } else { format!("{}-{}", base, count) };
+                            *count += 1;
+                            if !explicit.contains(&id) {
+                                break id;
+                            }
+                        }
+                    }
+                };
+                let class_attr = if classes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" class=\"{}\"", escape_attr(&classes.join(" ")))
+                };
+                events[start] = Event::Html(CowStr::from(format!(
+                    "<h{} id=\"{}\"{}>",
+                    level,
+                    escape_attr(&id),
+                    class_attr
+                )));
+                events[i] = Event::Html(CowStr::from(format!("</h{}>\n", level)));
+                headings.push(Heading { level, text, id });
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// Headings that share their text's slug, so all but the first got a
+/// suffixed id: the first one's text and every id, in document order.
+pub fn duplicate_headings(headings: &[Heading]) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<(String, String, Vec<String>)> = Vec::new();
+    for heading in headings {
+        let slug = slugify(&heading.text);
+        match groups.iter_mut().find(|(s, _, _)| *s == slug) {
+            Some((_, _, ids)) => ids.push(heading.id.clone()),
+            None => groups.push((slug, heading.text.clone(), vec![heading.id.clone()])),
+        }
+    }
+    groups.into_iter().filter(|(_, _, ids)| ids.len() > 1).map(|(_, text, ids)| (text, ids)).collect()
+}
+
+/// Renders the headings as nested `<ul>` lists of anchor links.
+pub fn build_toc(headings: &[Heading]) -> String {
+    build_toc_with(headings, |heading| format!("#{}", heading.id))
+}
+
+/// Like [`build_toc`], with each link's target from `href`, e.g. for
+/// headings spread over several files.
+pub fn build_toc_with(headings: &[Heading], href: impl Fn(&Heading) -> String) -> String {
+    let Some(base) = headings.iter().map(|h| h.level).min() else {
+        return String::new();
+    };
+    let mut html = String::new();
+    let mut depth = 0;
+    for heading in headings {
+        let target = heading.level - base + 1;
+        if target > depth {
+            while depth < target {
+                html.push_str("<ul>");
+                depth += 1;
+                if depth < target {
+                    html.push_str("<li>");
+                }
+            }
+        } else {
+            html.push_str("</li>");
+            while depth > target {
+                html.push_str("</ul></li>");
+                depth -= 1;
+            }
+        }
+        html.push_str(&format!(
+            "<li><a href=\"{}\">{}</a>",
+            escape_attr(&href(heading)),
+            escape_text(&heading.text)
+        ));
+    }
+    while depth > 0 {
+        html.push_str("</li></ul>");
+        depth -= 1;
+    }
+    html
+}
+
+pub fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+pub fn escape_attr(text: &str) -> String {
+    escape_text(text).replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Options, Parser};
+
+    fn headings(md: &str) -> Vec<Heading> {
+        let mut events: Vec<Event> = Parser::new_ext(md, Options::ENABLE_HEADING_ATTRIBUTES).collect();
+        assign_heading_ids(&mut events)
+    }
+
+    fn ids(md: &str) -> Vec<String> {
+        headings(md).into_iter().map(|heading| heading.id).collect()
+    }
+
+    #[test]
+    fn slugs_follow_github() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("  Café - menu  "), "café-menu");
+        assert_eq!(slugify("snake_case"), "snake_case");
+    }
+
+    #[test]
+    fn ids_are_unique_and_include_inline_code() {
+        assert_eq!(ids("# Intro\n# Intro\n# Intro\n"), ["intro", "intro-1", "intro-2"]);
+        assert_eq!(ids("# Using `cargo`\n# !!!\n"), ["using-cargo", "section"]);
+    }
+
+    #[test]
+    fn toc_nests_by_level() {
+        let toc = build_toc(&headings("## A\n### B\n## C\n"));
+        assert_eq!(
+            toc,
+            "<ul><li><a href=\"#a\">A</a><ul><li><a href=\"#b\">B</a></li></ul></li><li><a href=\"#c\">C</a></li></ul>"
+        );
+        assert_eq!(build_toc(&[]), "");
+        assert_eq!(build_toc(&headings("# 1 < 2 & co\n")), "<ul><li><a href=\"#1-2-co\">1 &lt; 2 &amp; co</a></li></ul>");
+    }
+
+    #[test]
+    fn duplicates_are_grouped_by_slug() {
+        let duplicates = duplicate_headings(&headings("# Setup\n## Usage\n# setup!\n## Usage\n## Other\n"));
+        assert_eq!(
+            duplicates,
+            [
+                ("Setup".to_string(), vec!["setup".to_string(), "setup-1".to_string()]),
+                ("Usage".to_string(), vec!["usage".to_string(), "usage-1".to_string()]),
+            ]
+        );
+        assert!(duplicate_headings(&headings("# A\n# B\n")).is_empty());
+    }
+
+    #[test]
+    fn generated_ids_avoid_explicit_ones() {
+        assert_eq!(ids("# Intro\n# Intro\n# Later {#intro-1}\n"), ["intro", "intro-2", "intro-1"]);
+        assert_eq!(ids("# Other {#intro}\n# Intro\n"), ["intro", "intro-1"]);
+    }
+
+    #[test]
+    fn explicit_ids_and_classes_reach_the_html() {
+        let mut events: Vec<Event> = Parser::new_ext("# Title {#top .wide}\n", Options::ENABLE_HEADING_ATTRIBUTES).collect();
+        assign_heading_ids(&mut events);
+        let mut out = String::new();
+        pulldown_cmark::html::push_html(&mut out, events.into_iter());
+        assert_eq!(out, "<h1 id=\"top\" class=\"wide\">Title</h1>\n");
+    }
+}
@@ -0,0 +1,223 @@
+use crate::excerpt::LineRange;
+use crate::messages::Message;
+use crate::{AppState, EntryStatus};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+enum Mode {
+    Normal,
+    /// Typing the path of a Markdown file to add.
+    AddInput(String),
+    /// Typing `start:end` for the selected entry; empty clears the range.
+    EditRange(usize, String),
+}
+
+struct Tui {
+    state: AppState,
+    list: ListState,
+    mode: Mode,
+}
+
+/// Runs the terminal interface until the user quits, then saves the state.
+pub fn run(state: AppState) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let mut tui = Tui {
+        list: ListState::default().with_selected((!state.input_files.is_empty()).then_some(0)),
+        state,
+        mode: Mode::Normal,
+    };
+    let result = tui.event_loop(&mut terminal);
+    ratatui::restore();
+    tui.state.save_state();
+    result
+}
+
+impl Tui {
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            let converting = self.state.poll_batch();
+            let opening = self.state.poll_browser_open();
+            terminal.draw(|frame| self.draw(frame))?;
+            // Redraw now and then until the batch and browser opens have reported back.
+            if (converting || opening) && !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match &mut self.mode {
+                Mode::AddInput(path) => match key.code {
+                    KeyCode::Enter => {
+                        let md = PathBuf::from(path.trim());
+                        self.mode = Mode::Normal;
+                        if md.is_file() {
+                            self.state.add_input(md);
+                            self.list.select(Some(self.state.input_files.len() - 1));
+                        } else {
+                            self.state.status_message = Message::error(format!("Not a file: {}", md.display()));
+                        }
+                    }
+                    KeyCode::Esc => self.mode = Mode::Normal,
+                    KeyCode::Backspace => {
+                        path.pop();
+                    }
+                    KeyCode::Char(c) => path.push(c),
+                    _ => {}
+                },
+                Mode::EditRange(i, text) => match key.code {
+                    KeyCode::Enter => {
+                        let range = match text.trim() {
+                            "" => Ok(None),
+                            text => LineRange::parse(text).map(Some),
+                        };
+                        let i = *i;
+                        self.mode = Mode::Normal;
+                        match (range, self.state.entries.get_mut(i)) {
+                            (Ok(range), Some(entry)) => entry.line_range = range,
+                            (Err(e), _) => self.state.status_message = Message::error(e),
+                            _ => {}
+                        }
+                    }
+                    KeyCode::Esc => self.mode = Mode::Normal,
+                    KeyCode::Backspace => {
+                        text.pop();
+                    }
+                    KeyCode::Char(c) => text.push(c),
+                    _ => {}
+                },
+                Mode::Normal => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('a') => self.mode = Mode::AddInput(String::new()),
+                    KeyCode::Char('d') | KeyCode::Delete => {
+                        if let Some(i) = self.list.selected() {
+                            self.state.remove_file(i);
+                            if self.state.input_files.is_empty() {
+                                self.list.select(None);
+                            } else if i >= self.state.input_files.len() {
+                                self.list.select(Some(i - 1));
+                            }
+                        }
+                    }
+                    KeyCode::Char('c') => {
+                        self.state.convert_all(false);
+                        self.state.save_state();
+                    }
+                    KeyCode::Char('r') => {
+                        self.state.convert_all(true);
+                        self.state.save_state();
+                    }
+                    KeyCode::Char('f') => self.state.reset_statuses(),
+                    KeyCode::Char(' ') => {
+                        if let Some(entry) = self.list.selected().and_then(|i| self.state.entries.get_mut(i)) {
+                            entry.enabled = !entry.enabled;
+                        }
+                    }
+                    KeyCode::Char('*') => {
+                        if let Some(i) = self.list.selected() {
+                            self.list.select(Some(self.state.toggle_pinned(i)));
+                        }
+                    }
+                    KeyCode::Char('p') => self.state.preview = !self.state.preview,
+                    KeyCode::Char('l') => {
+                        if let Some(i) = self.list.selected().filter(|&i| i < self.state.entries.len()) {
+                            let current = self.state.entries[i].line_range.map(|r| r.to_string()).unwrap_or_default();
+                            self.mode = Mode::EditRange(i, current);
+                        }
+                    }
+                    KeyCode::Char('D') => self.state.include_drafts = !self.state.include_drafts,
+                    KeyCode::Char('+') => self.state.max_parallel = (self.state.max_parallel + 1).min(64),
+                    KeyCode::Char('-') => self.state.max_parallel = self.state.max_parallel.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => self.list.select_next(),
+                    KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [list_area, input_area, status_area, help_area] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let items: Vec<ListItem> = self
+            .state
+            .input_files
+            .iter()
+            .zip(&self.state.output_files)
+            .enumerate()
+            .map(|(i, (input, output))| {
+                let entry = self.state.entries.get(i);
+                let icon = match entry.map(|e| e.status) {
+                    Some(EntryStatus::Done) => "✅",
+                    Some(EntryStatus::Failed) => "❌",
+                    _ => "⏳",
+                };
+                let enabled = if entry.is_none_or(|e| e.enabled) { "[x]" } else { "[ ]" };
+                let pin = if entry.is_some_and(|e| e.pinned) { "⭐ " } else { "" };
+                let draft = if entry.is_some_and(|e| e.draft) { " 📝 draft" } else { "" };
+                let range = match entry.and_then(|e| e.line_range) {
+                    Some(range) => format!(" ✂ {}", range),
+                    None => String::new(),
+                };
+                ListItem::new(format!(
+                    "{} {} {}{}{}{} → {}",
+                    enabled,
+                    icon,
+                    pin,
+                    input.display(),
+                    range,
+                    draft,
+                    output.display()
+                ))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" 📄 Markdown to HTML Converter "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        let (input_text, input_title) = match &self.mode {
+            Mode::AddInput(path) => (path.as_str(), " Add Markdown file (Enter to add, Esc to cancel) "),
+            Mode::EditRange(_, text) => (text.as_str(), " Line range start:end (empty for the whole file) "),
+            Mode::Normal => ("", ""),
+        };
+        frame.render_widget(
+            Paragraph::new(input_text).block(Block::default().borders(Borders::ALL).title(input_title)),
+            input_area,
+        );
+        frame.render_widget(
+            Paragraph::new(Line::from(self.state.message_style.format(&self.state.status_message)))
+                .style(Style::default().fg(Color::Yellow)),
+            status_area,
+        );
+        let preview = if self.state.preview { "on" } else { "off" };
+        let drafts = if self.state.include_drafts { "on" } else { "off" };
+        let parallel = match self.state.max_parallel {
+            0 => "auto".to_string(),
+            n => n.to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(format!(
+                "a add  d remove  space include/skip  * pin  l line range  c convert  r resume pending  f start fresh  p open in browser ({})  D include drafts ({})  +/- parallel ({})  q quit",
+                preview, drafts, parallel
+            ))
+            .style(Style::default().fg(Color::DarkGray)),
+            help_area,
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use pulldown_cmark::{CowStr, Event, Tag};
+
+/// `prefix` as `/docs/`, with exactly one slash on each end, or as an
+/// absolute URL ending in a slash. Empty and `/` mean no prefix.
+pub fn normalize_prefix(prefix: &str) -> String {
+    let prefix = prefix.trim();
+    if prefix.contains("://") {
+        return format!("{}/", prefix.trim_end_matches('/'));
+    }
+    match prefix.trim_matches('/') {
+        "" => String::new(),
+        path => format!("/{}/", path),
+    }
+}
+
+/// `url` under `prefix` if it's root-relative (`/img/a.png`). Relative,
+/// protocol-relative and absolute URLs, fragments, and URLs already under
+/// the prefix are left alone.
+pub fn apply_prefix(url: &str, prefix: &str) -> Option<String> {
+    if prefix.is_empty() || !url.starts_with('/') || url.starts_with("//") || url.starts_with(prefix) {
+        return None;
+    }
+    Some(format!("{}{}", prefix, &url[1..]))
+}
+
+/// Prefixes the destination of a link or image; see [`apply_prefix`].
+pub fn prefix_event<'a>(event: Event<'a>, prefix: &str) -> Event<'a> {
+    match event {
+        Event::Start(Tag::Link(kind, dest, title)) => match apply_prefix(&dest, prefix) {
+            Some(url) => Event::Start(Tag::Link(kind, CowStr::from(url), title)),
+            None => Event::Start(Tag::Link(kind, dest, title)),
+        },
+        Event::Start(Tag::Image(kind, dest, title)) => match apply_prefix(&dest, prefix) {
+            Some(url) => Event::Start(Tag::Image(kind, CowStr::from(url), title)),
+            None => Event::Start(Tag::Image(kind, dest, title)),
+        },
+        event => event,
+    }
+}
+
+/// `url` with a `.md` or `.markdown` file name swapped for `.{extension}`,
+/// keeping any `?query` or `#fragment`, for relative links between
+/// Markdown files. Absolute and root-relative URLs, other schemes and bare
+/// fragments are left alone.
+pub fn md_link_to_html(url: &str, extension: &str) -> Option<String> {
+    if url.starts_with(['/', '#']) || url.split(['/', '?', '#']).next().is_some_and(|first| first.contains(':')) {
+        return None;
+    }
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, rest) = url.split_at(end);
+    let lower = path.to_ascii_lowercase();
+    let stem_len = if lower.ends_with(".md") {
+        path.len() - 3
+    } else if lower.ends_with(".markdown") {
+        path.len() - 9
+    } else {
+        return None;
+    };
+    if stem_len == 0 || path[..stem_len].ends_with('/') {
+        return None;
+    }
+    Some(format!("{}.{}{}", &path[..stem_len], extension, rest))
+}
+
+/// Points links to other Markdown files at their HTML pages; see
+/// [`md_link_to_html`]. Does nothing without an `extension`.
+pub fn md_link_event<'a>(event: Event<'a>, extension: Option<&str>) -> Event<'a> {
+    let Some(extension) = extension else {
+        return event;
+    };
+    match event {
+        Event::Start(Tag::Link(kind, dest, title)) => match md_link_to_html(&dest, extension) {
+            Some(url) => Event::Start(Tag::Link(kind, CowStr::from(url), title)),
+            None => Event::Start(Tag::Link(kind, dest, title)),
+        },
+        event => event,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_with_trailing_slash() {
+        let prefix = normalize_prefix("/docs/");
+        assert_eq!(prefix, "/docs/");
+        assert_eq!(apply_prefix("/img/a.png", &prefix).as_deref(), Some("/docs/img/a.png"));
+        assert_eq!(apply_prefix("/docs/img/a.png", &prefix), None);
+    }
+
+    #[test]
+    fn prefix_without_trailing_slash() {
+        let prefix = normalize_prefix("docs");
+        assert_eq!(prefix, "/docs/");
+        assert_eq!(apply_prefix("/guide.html#setup", &prefix).as_deref(), Some("/docs/guide.html#setup"));
+        assert_eq!(normalize_prefix("https://example.com/docs"), "https://example.com/docs/");
+        assert_eq!(
+            apply_prefix("/a.html", "https://example.com/docs/").as_deref(),
+            Some("https://example.com/docs/a.html")
+        );
+    }
+
+    #[test]
+    fn empty_prefix_changes_nothing() {
+        let prefix = normalize_prefix("  /  ");
+        assert_eq!(prefix, "");
+        assert_eq!(apply_prefix("/img/a.png", &prefix), None);
+    }
+
+    #[test]
+    fn only_root_relative_urls_are_prefixed() {
+        for url in ["img/a.png", "//cdn.example.com/a.png", "https://example.com/", "#top"] {
+            assert_eq!(apply_prefix(url, "/docs/"), None, "{}", url);
+        }
+    }
+}
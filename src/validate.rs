@@ -0,0 +1,146 @@
+use crate::messages::Message;
+use std::path::Path;
+use std::process::Command;
+
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// Only this many problems are reported per file.
+const MAX_PROBLEMS: usize = 20;
+
+/// Checks a written page and returns one log line per problem found.
+///
+/// An empty `command` uses the built-in well-formedness check; otherwise it's
+/// run with the page's path appended, e.g. `vnu` or `tidy -qe`, and whatever
+/// it prints is reported when it exits unsuccessfully.
+pub fn validate_file(path: &Path, command: &str) -> Vec<Message> {
+    let problems = if command.trim().is_empty() {
+        match std::fs::read_to_string(path) {
+            Ok(html) => check_well_formed(&html),
+            Err(e) => vec![format!("could not read output: {}", e)],
+        }
+    } else {
+        run_validator(command, path)
+    };
+    let mut log: Vec<Message> = problems
+        .iter()
+        .take(MAX_PROBLEMS)
+        .map(|problem| Message::warning(format!("{}: {}", path.display(), problem)))
+        .collect();
+    if problems.len() > MAX_PROBLEMS {
+        log.push(Message::warning(format!("{}: … and {} more", path.display(), problems.len() - MAX_PROBLEMS)));
+    }
+    if log.is_empty() {
+        log.push(Message::info("🔍", format!("{}: no validation issues", path.display())));
+    }
+    log
+}
+
+fn run_validator(command: &str, path: &Path) -> Vec<String> {
+    let mut parts = command.split_whitespace();
+    let Some(program) = parts.next() else {
+        return Vec::new();
+    };
+    let output = match Command::new(program).args(parts).arg(path).output() {
+        Ok(output) => output,
+        Err(e) => return vec![format!("could not run {}: {}", program, e)],
+    };
+    if output.status.success() {
+        return Vec::new();
+    }
+    let mut problems: Vec<String> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stdout).lines())
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    if problems.is_empty() {
+        problems.push(format!("{} exited with {}", program, output.status));
+    }
+    problems
+}
+
+/// Reports unclosed, stray and misnested tags, with line numbers.
+///
+/// Comments, doctypes and the contents of `<script>`/`<style>` are skipped,
+/// and void elements never need closing.
+pub fn check_well_formed(html: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut open: Vec<(String, usize)> = Vec::new();
+    let line_at = |pos: usize| html[..pos].matches('\n').count() + 1;
+    let mut pos = 0;
+    while let Some(offset) = html[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &html[start..];
+        if rest.starts_with("<!--") {
+            match rest.find("-->") {
+                Some(end) => pos = start + end + 3,
+                None => {
+                    problems.push(format!("line {}: unterminated comment", line_at(start)));
+                    break;
+                }
+            }
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            problems.push(format!("line {}: unterminated tag", line_at(start)));
+            break;
+        };
+        let tag = &rest[1..end];
+        pos = start + end + 1;
+        if tag.starts_with('!') || tag.starts_with('?') {
+            continue;
+        }
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            // A lone `<` in text, e.g. `a < b`, isn't a tag.
+            continue;
+        }
+        if closing {
+            match open.iter().rposition(|(open_name, _)| *open_name == name) {
+                Some(i) => {
+                    for (unclosed, line) in open.drain(i + 1..) {
+                        problems.push(format!(
+                            "line {}: <{}> is not closed before </{}> on line {}",
+                            line,
+                            unclosed,
+                            name,
+                            line_at(start)
+                        ));
+                    }
+                    open.pop();
+                }
+                None if VOID_ELEMENTS.contains(&name.as_str()) => {}
+                None => problems.push(format!("line {}: stray </{}>", line_at(start), name)),
+            }
+        } else if tag.ends_with('/') || VOID_ELEMENTS.contains(&name.as_str()) {
+            continue;
+        } else if name == "script" || name == "style" {
+            let close = format!("</{}", name);
+            match html[pos..].to_ascii_lowercase().find(&close) {
+                Some(body) => {
+                    open.push((name, line_at(start)));
+                    pos += body;
+                }
+                None => {
+                    problems.push(format!("line {}: <{}> is never closed", line_at(start), name));
+                    break;
+                }
+            }
+        } else {
+            open.push((name, line_at(start)));
+        }
+    }
+    for (name, line) in open {
+        problems.push(format!("line {}: <{}> is never closed", line, name));
+    }
+    problems
+}
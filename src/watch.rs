@@ -0,0 +1,108 @@
+use crate::messages::Message;
+use crate::AppState;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often watched files are checked.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Modification times seen at the last poll.
+#[derive(Default)]
+pub struct Watcher {
+    stamps: HashMap<PathBuf, Option<SystemTime>>,
+    last_poll: Option<Instant>,
+}
+
+impl Watcher {
+    /// Whether `path` changed since the last poll. A path seen for the first
+    /// time only starts being watched.
+    fn changed(&mut self, path: &Path) -> bool {
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        match self.stamps.insert(path.to_path_buf(), modified) {
+            Some(previous) => previous != modified,
+            None => false,
+        }
+    }
+
+    /// Takes `path` as it is now, so a change the app made itself, e.g. a
+    /// pre-process hook rewriting an input, doesn't count as an edit.
+    pub fn settle(&mut self, path: &Path) {
+        if let Some(stamp) = self.stamps.get_mut(path) {
+            *stamp = fs::metadata(path).and_then(|m| m.modified()).ok();
+        }
+    }
+}
+
+impl AppState {
+    /// Stylesheets and templates every page depends on, so a change to any
+    /// of them reconverts the whole list.
+    fn watched_styles(&self) -> Vec<PathBuf> {
+        let mut styles: Vec<PathBuf> = self.css_path.iter().chain(&self.template_path).cloned().collect();
+        styles.extend(self.css_presets.iter().map(|(_, path)| path.clone()));
+        if self.embed_fonts {
+            styles.extend(self.font_path.iter().cloned());
+        }
+        styles.extend(self.entries.iter().filter_map(|entry| entry.template.clone()));
+        styles
+    }
+
+    /// Reconverts what changed since the last poll: everything after a
+    /// style or template change, otherwise just the edited inputs. Batches
+    /// start like the Convert button's, checked and with large inputs
+    /// confirmed, and changes wait while a batch runs or asks to go ahead.
+    pub(crate) fn poll_watch(&mut self) {
+        if self.watcher.last_poll.is_some_and(|last| last.elapsed() < POLL_INTERVAL)
+            || self.batch_running()
+            || self.large_input_confirm.is_some()
+        {
+            return;
+        }
+        self.watcher.last_poll = Some(Instant::now());
+        self.sync_entries();
+        let styles = self.watched_styles();
+        // Every path is checked, not just up to the first change, so all stamps stay current.
+        let style_changes: Vec<&PathBuf> = styles.iter().filter(|path| self.watcher.changed(path)).collect();
+        let input_changes: Vec<usize> = (0..self.input_files.len())
+            .filter(|&i| self.watcher.changed(&self.input_files[i]))
+            .collect();
+        let (note, only) = if let Some(style) = style_changes.first() {
+            (Message::info("👀", format!("{} changed, reconverting everything", style.display())), None)
+        } else if !input_changes.is_empty() {
+            (Message::info("👀", format!("{} file(s) changed", input_changes.len())), Some(input_changes))
+        } else {
+            return;
+        };
+        self.request_batch(only);
+        if self.large_input_confirm.is_none() {
+            self.log.insert(0, note);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    fn touch(path: &Path, secs: u64) {
+        let file = File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)).unwrap();
+    }
+
+    #[test]
+    fn settled_writes_are_not_changes() {
+        let path = std::env::temp_dir().join(format!("md2html-watch-{}.md", std::process::id()));
+        fs::write(&path, "# A\n").unwrap();
+        let mut watcher = Watcher::default();
+        assert!(!watcher.changed(&path));
+        touch(&path, 1_000);
+        // As if a hook had rewritten the input during its conversion.
+        watcher.settle(&path);
+        assert!(!watcher.changed(&path));
+        touch(&path, 2_000);
+        assert!(watcher.changed(&path));
+        fs::remove_file(&path).ok();
+    }
+}
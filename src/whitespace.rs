@@ -0,0 +1,37 @@
+/// Reduces each run of blank lines to a single one, leaving the contents of
+/// `<pre>` blocks exactly as they are.
+pub fn collapse_blank_lines(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut pre_depth = 0usize;
+    let mut previous_blank = false;
+    for line in html.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if !(blank && previous_blank && pre_depth == 0) {
+            out.push_str(line);
+        }
+        previous_blank = blank && pre_depth == 0;
+        let lower = line.to_ascii_lowercase();
+        pre_depth += lower.matches("<pre").count();
+        pre_depth = pre_depth.saturating_sub(lower.matches("</pre").count());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_of_blank_lines_become_one() {
+        assert_eq!(collapse_blank_lines("<p>a</p>\n\n\n  \n<p>b</p>\n\n"), "<p>a</p>\n\n<p>b</p>\n\n");
+        assert_eq!(collapse_blank_lines("no newline"), "no newline");
+    }
+
+    #[test]
+    fn pre_blocks_are_left_alone() {
+        let html = "<PRE><code>one\n\n\n\ntwo\n</code></PRE>\n\n\n<p>after</p>\n";
+        assert_eq!(collapse_blank_lines(html), "<PRE><code>one\n\n\n\ntwo\n</code></PRE>\n\n<p>after</p>\n");
+        let nested = "<pre>a\n<pre>b</pre>\n\n\nc</pre>\n";
+        assert_eq!(collapse_blank_lines(nested), nested);
+    }
+}
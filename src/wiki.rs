@@ -0,0 +1,61 @@
+use crate::prose;
+use crate::toc::slugify;
+use std::borrow::Cow;
+
+/// Rewrites `[[Page Name]]` and `[[target|label]]` into Markdown links to
+/// `page-name.html`, so a vault of notes converts into a linked site.
+///
+/// A `#section` after the target is kept as a slugified fragment. Links inside
+/// code blocks and inline code spans are left alone.
+pub fn convert_wiki_links(markdown: &str) -> Cow<'_, str> {
+    if !markdown.contains("[[") {
+        return Cow::Borrowed(markdown);
+    }
+    Cow::Owned(prose::convert_outside_code(markdown, |line, out| {
+        prose::convert_outside_code_spans(line, out, convert_text)
+    }))
+}
+
+fn convert_text(text: &str, out: &mut String) {
+    let mut rest = text;
+    while let Some(link) = rest.find("[[") {
+        out.push_str(&rest[..link]);
+        let inner = &rest[link + 2..];
+        match inner.find("]]").and_then(|end| wiki_link(&inner[..end]).map(|md| (end, md))) {
+            Some((end, md)) => {
+                out.push_str(&md);
+                rest = &inner[end + 2..];
+            }
+            None => {
+                out.push_str("[[");
+                rest = inner;
+            }
+        }
+    }
+    out.push_str(rest);
+}
+
+/// The Markdown link for the text between `[[` and `]]`, if it names a page.
+fn wiki_link(inner: &str) -> Option<String> {
+    if inner.contains('[') || inner.contains('\n') {
+        return None;
+    }
+    let (target, label) = match inner.split_once('|') {
+        Some((target, label)) => (target.trim(), label.trim()),
+        None => (inner.trim(), inner.trim()),
+    };
+    let (page, section) = match target.split_once('#') {
+        Some((page, section)) => (page, Some(section)),
+        None => (target, None),
+    };
+    let page = slugify(page);
+    if page.is_empty() {
+        return None;
+    }
+    let mut href = format!("{}.html", page);
+    if let Some(section) = section.map(slugify).filter(|s| !s.is_empty()) {
+        href = format!("{}#{}", href, section);
+    }
+    let label = if label.is_empty() { target } else { label };
+    Some(format!("[{}]({})", label.replace(']', "\\]"), href))
+}
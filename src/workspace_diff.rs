@@ -0,0 +1,132 @@
+use crate::AppState;
+use eframe::egui;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Characters of a setting's value shown before it's cut short.
+const MAX_VALUE_CHARS: usize = 120;
+
+/// Two workspaces' settings side by side, worked out when the pair is
+/// picked rather than every frame.
+pub struct WorkspaceDiff {
+    left: usize,
+    right: usize,
+    only_differences: bool,
+    /// Setting name and both values, as compact JSON; `None` where unset.
+    rows: Vec<(String, Option<String>, Option<String>)>,
+}
+
+impl AppState {
+    /// Workspace `i`'s settings as saved, without file lists or the tabs.
+    fn workspace_settings(&self, i: usize) -> Map<String, Value> {
+        let value = if i == self.active_workspace {
+            self.settings_value()
+        } else {
+            AppState::from_settings_value(self.workspaces[i].session.clone()).and_then(|state| state.settings_value())
+        };
+        let mut map = match value {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        map.remove("workspaces");
+        map.remove("active_workspace");
+        map
+    }
+
+    fn workspace_diff_rows(&self, left: usize, right: usize) -> Vec<(String, Option<String>, Option<String>)> {
+        let (left, right) = (self.workspace_settings(left), self.workspace_settings(right));
+        let keys: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+        let show = |value: Option<&Value>| value.map(|value| value.to_string());
+        keys.into_iter()
+            .map(|key| (key.clone(), show(left.get(key)), show(right.get(key))))
+            .collect()
+    }
+
+    /// Compares the active workspace with the next one.
+    pub(crate) fn open_workspace_diff(&mut self) {
+        self.ensure_workspace();
+        let left = self.active_workspace;
+        let right = (left + 1) % self.workspaces.len();
+        let rows = self.workspace_diff_rows(left, right);
+        self.workspace_diff = Some(WorkspaceDiff { left, right, only_differences: true, rows });
+    }
+
+    /// Read-only table of the settings two workspaces have, differences
+    /// highlighted.
+    pub(crate) fn workspace_diff_window(&mut self, ctx: &egui::Context) {
+        let Some(diff) = &mut self.workspace_diff else {
+            return;
+        };
+        let names: Vec<&str> = self.workspaces.iter().map(|w| w.name.as_str()).collect();
+        diff.left = diff.left.min(names.len().saturating_sub(1));
+        diff.right = diff.right.min(names.len().saturating_sub(1));
+        let picked = (diff.left, diff.right);
+        let mut open = true;
+        let differing = diff.rows.iter().filter(|(_, left, right)| left != right).count();
+        egui::Window::new("🔀 Compare workspaces")
+            .open(&mut open)
+            .default_size([760.0, 520.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for (id, side) in [("diff_left", &mut diff.left), ("diff_right", &mut diff.right)] {
+                        egui::ComboBox::from_id_source(id)
+                            .selected_text(names.get(*side).copied().unwrap_or_default())
+                            .show_ui(ui, |ui| {
+                                for (i, name) in names.iter().enumerate() {
+                                    ui.selectable_value(side, i, *name);
+                                }
+                            });
+                    }
+                    ui.checkbox(&mut diff.only_differences, "Only differences");
+                    ui.label(format!("{} of {} setting(s) differ", differing, diff.rows.len()));
+                });
+                ui.separator();
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("workspace_diff_grid").striped(true).show(ui, |ui| {
+                        ui.strong("Setting");
+                        ui.strong(names.get(diff.left).copied().unwrap_or_default());
+                        ui.strong(names.get(diff.right).copied().unwrap_or_default());
+                        ui.end_row();
+                        for (key, left, right) in &diff.rows {
+                            let differs = left != right;
+                            if diff.only_differences && !differs {
+                                continue;
+                            }
+                            let cell = |ui: &mut egui::Ui, value: &Option<String>| {
+                                let text = match value {
+                                    Some(value) if value.chars().count() > MAX_VALUE_CHARS => {
+                                        format!("{}…", value.chars().take(MAX_VALUE_CHARS).collect::<String>())
+                                    }
+                                    Some(value) => value.clone(),
+                                    None => "—".to_string(),
+                                };
+                                let text = egui::RichText::new(text).monospace();
+                                let text = if differs { text.color(egui::Color32::from_rgb(220, 160, 60)) } else { text };
+                                let label = ui.label(text);
+                                if let Some(value) = value.as_ref().filter(|v| v.chars().count() > MAX_VALUE_CHARS) {
+                                    label.on_hover_text(value);
+                                }
+                            };
+                            ui.monospace(key);
+                            cell(ui, left);
+                            cell(ui, right);
+                            ui.end_row();
+                        }
+                    });
+                    if differing == 0 && diff.only_differences {
+                        ui.weak("These workspaces have the same settings.");
+                    }
+                });
+            });
+        let repick = (diff.left, diff.right) != picked;
+        if !open {
+            self.workspace_diff = None;
+        } else if repick {
+            let (left, right) = (diff.left, diff.right);
+            let rows = self.workspace_diff_rows(left, right);
+            if let Some(diff) = &mut self.workspace_diff {
+                diff.rows = rows;
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::messages::Message;
+use crate::AppState;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// One workspace tab. The active workspace lives in `AppState`'s own fields;
+/// the others are kept as the state they had when they were switched away
+/// from, file list and settings alike.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workspace {
+    pub name: String,
+    /// Serialized `AppState` without `workspaces`; unused while active.
+    pub session: serde_json::Value,
+}
+
+impl Workspace {
+    fn named(name: String) -> Self {
+        Self { name, session: serde_json::Value::Null }
+    }
+}
+
+impl AppState {
+    /// Makes sure there is a tab for the active workspace, e.g. after
+    /// loading state from before workspaces existed.
+    pub(crate) fn ensure_workspace(&mut self) {
+        if self.workspaces.is_empty() {
+            self.workspaces.push(Workspace::named("Default".to_string()));
+        }
+        self.active_workspace = self.active_workspace.min(self.workspaces.len() - 1);
+    }
+
+    pub(crate) fn workspace_name(&self) -> &str {
+        self.workspaces.get(self.active_workspace).map_or("Default", |w| w.name.as_str())
+    }
+
+    /// Everything but the workspace tabs, as stored for an inactive workspace.
+    fn session_value(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(map) = value.as_object_mut() {
+            map.remove("workspaces");
+            map.remove("active_workspace");
+        }
+        value
+    }
+
+    pub(crate) fn switch_workspace(&mut self, target: usize) {
+        if target == self.active_workspace || target >= self.workspaces.len() {
+            return;
+        }
+        self.sync_entries();
+        let session = self.session_value();
+        let mut workspaces = std::mem::take(&mut self.workspaces);
+        workspaces[self.active_workspace].session = session;
+        let mut next =
+            AppState::from_settings_value(std::mem::take(&mut workspaces[target].session)).unwrap_or_default();
+        next.workspaces = workspaces;
+        next.active_workspace = target;
+        next.lint_strict = self.lint_strict;
+        next.range_override = self.range_override;
+        next.window_title = std::mem::take(&mut self.window_title);
+        next.prepare_session();
+        next.status_message = Message::info("🗂", format!("Switched to {}", next.workspace_name()));
+        *self = next;
+        self.save_state();
+    }
+
+    /// Opens a new, empty workspace that starts from the current settings.
+    pub(crate) fn add_workspace(&mut self) {
+        self.ensure_workspace();
+        let mut session = self.settings_value().unwrap_or_default();
+        if let Some(map) = session.as_object_mut() {
+            map.remove("workspaces");
+            map.remove("active_workspace");
+        }
+        let name = (self.workspaces.len() + 1..)
+            .map(|n| format!("Workspace {}", n))
+            .find(|name| !self.workspaces.iter().any(|w| w.name == *name))
+            .unwrap_or_default();
+        self.workspaces.push(Workspace { name, session });
+        self.switch_workspace(self.workspaces.len() - 1);
+    }
+
+    /// Drops workspace `i` and its file list; the last one can't be closed.
+    pub(crate) fn close_workspace(&mut self, i: usize) {
+        if self.workspaces.len() < 2 || i >= self.workspaces.len() {
+            return;
+        }
+        if i == self.active_workspace {
+            self.switch_workspace(if i == 0 { 1 } else { i - 1 });
+        }
+        let closed = self.workspaces.remove(i);
+        if self.active_workspace > i {
+            self.active_workspace -= 1;
+        }
+        self.status_message = Message::info("🗑", format!("Closed workspace {}", closed.name));
+        self.save_state();
+    }
+
+    /// Moves entry `i`, with its metadata, to the end of workspace `target`'s list.
+    pub(crate) fn move_to_workspace(&mut self, i: usize, target: usize) {
+        self.sync_entries();
+        if target == self.active_workspace || target >= self.workspaces.len() || i >= self.input_files.len() {
+            return;
+        }
+        let input = self.input_files[i].clone();
+        let output = self.output_files.get(i).cloned().unwrap_or_else(|| input.with_extension("html"));
+        let entry = self.entries[i].clone();
+        let workspace = &mut self.workspaces[target];
+        let mut other = AppState::from_settings_value(std::mem::take(&mut workspace.session)).unwrap_or_default();
+        other.sync_entries();
+        other.input_files.push(input.clone());
+        other.output_files.push(output);
+        other.entries.push(entry);
+        workspace.session = other.session_value();
+        self.remove_file(i);
+        self.status_message = Message::info("🗂", format!("Moved {} to {}", input.display(), self.workspaces[target].name));
+        self.save_state();
+    }
+
+    /// Tabs across the top of the window, one per workspace.
+    pub(crate) fn workspace_tabs(&mut self, ctx: &egui::Context) {
+        self.ensure_workspace();
+        let title = format!("Markdown to HTML GUI — {}", self.workspace_name());
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+        let mut switch = None;
+        let mut close = None;
+        let mut add = false;
+        egui::TopBottomPanel::top("workspace_tabs").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let count = self.workspaces.len();
+                for (i, workspace) in self.workspaces.iter_mut().enumerate() {
+                    let tab = ui.selectable_label(i == self.active_workspace, format!("🗂 {}", workspace.name));
+                    if tab.clicked() {
+                        switch = Some(i);
+                    }
+                    tab.context_menu(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut workspace.name);
+                        if ui.add_enabled(count > 1, egui::Button::new("🗑 Close workspace")).clicked() {
+                            close = Some(i);
+                            ui.close_menu();
+                        }
+                    });
+                }
+                if ui.button("➕").on_hover_text("New workspace with the current settings").clicked() {
+                    add = true;
+                }
+            });
+        });
+        if let Some(i) = switch {
+            self.switch_workspace(i);
+        }
+        if let Some(i) = close {
+            self.close_workspace(i);
+        }
+        if add {
+            self.add_workspace();
+        }
+    }
+}